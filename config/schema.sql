@@ -1,56 +1,54 @@
+-- =================================================================
+-- MBCT SQLite Schema (reference)
+-- The source of truth are the versioned migrations in trading-common/migrations,
+-- applied by trading_common::data::migrations::run_migrations. This file shows the
+-- resulting schema at the latest version and must be kept in sync with them.
+-- =================================================================
+
 -- =================================================================
 -- Core Data Table for Quantitative Trading System: Tick Data
 -- Design Principles: Single table storage, high-performance queries, data integrity
 -- =================================================================
 
-CREATE TABLE tick_data (
-    -- 【Timestamp】UTC time, supports millisecond precision
-    -- Why use TIMESTAMP WITH TIME ZONE:
+CREATE TABLE IF NOT EXISTS tick_data (
+    -- 【Timestamp】UTC time as Unix milliseconds
+    -- Why use INTEGER:
     -- 1. Global markets require a unified timezone (UTC)
     -- 2. Supports millisecond-level precision for high-frequency trading needs
-    -- 3. Time zone info avoids issues like daylight saving time
-    timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    -- 3. Same encoding as market_states.timestamp, range queries stay on the index
+    timestamp INTEGER NOT NULL,
     
     -- 【Trading Pair】e.g., 'BTCUSDT', 'ETHUSDT'
-    -- Why use VARCHAR(20):
-    -- 1. Cryptocurrency trading pairs are typically 8-15 characters
-    -- 2. Reserved space for future new trading pairs
-    -- 3. Fixed length storage offers better performance
-    symbol VARCHAR(20) NOT NULL,
+    symbol TEXT NOT NULL,
     
-    -- 【Trade Price】Use DECIMAL to ensure precision
-    -- Why use DECIMAL(20, 8):
-    -- 1. Total 20 digits: supports prices in the trillions
-    -- 2. 8 decimal places: meets cryptocurrency precision requirements (Bitcoin has 8 decimals)
-    -- 3. Avoids floating point precision loss
-    price DECIMAL(20, 8) NOT NULL,
+    -- 【Trade Price】Decimal string to ensure precision
+    -- Why use TEXT:
+    -- 1. SQLite has no exact DECIMAL type, NUMERIC affinity would round through REAL
+    -- 2. rust_decimal round-trips the string losslessly
+    price TEXT NOT NULL,
     
-    -- 【Trade Quantity】Also uses DECIMAL to ensure precision
-    -- Why use DECIMAL(20, 8):
-    -- 1. Trade volume calculations require high precision
-    -- 2. Consistent precision with price
-    quantity DECIMAL(20, 8) NOT NULL,
+    -- 【Trade Quantity】Also a decimal string to ensure precision
+    quantity TEXT NOT NULL,
     
     -- 【Trade Side】Buy or Sell
-    -- Why use VARCHAR(4) + CHECK constraint:
+    -- Why use TEXT + CHECK constraint:
     -- 1. 'BUY'/'SELL' is more intuitive than boolean
     -- 2. CHECK constraint enforces data validity
     -- 3. Facilitates SQL querying and reporting
-    side VARCHAR(4) NOT NULL CHECK (side IN ('BUY', 'SELL')),
+    side TEXT NOT NULL CHECK (side IN ('BUY', 'SELL')),
     
     -- 【Trade ID】Original trade identifier from exchange
-    -- Why use VARCHAR(50):
+    -- Why use TEXT:
     -- 1. Different exchanges have different ID formats (numeric, alphanumeric, UUID, etc.)
     -- 2. Used for deduplication and traceability
-    -- 3. Supports various exchange ID lengths
-    trade_id VARCHAR(50) NOT NULL,
+    trade_id TEXT NOT NULL,
     
     -- 【Maker Flag】Whether the buyer is the maker (order placer)
     -- Why this field is needed:
     -- 1. Distinguish between aggressive and passive trades
     -- 2. Calculate market liquidity metrics
     -- 3. Basis for fee calculation
-    is_buyer_maker BOOLEAN NOT NULL
+    is_buyer_maker INTEGER NOT NULL
 );
 
 -- =================================================================
@@ -61,13 +59,12 @@ CREATE TABLE tick_data (
 -- 【Index 1】Real-time trading query index
 -- Use cases:
 -- - Fetch the latest price for a trading pair: WHERE symbol = 'BTCUSDT' ORDER BY timestamp DESC LIMIT 1
--- - Get recent N minutes data of a trading pair: WHERE symbol = 'BTCUSDT' AND timestamp >= NOW() - INTERVAL '5 minutes'
+-- - Get recent N minutes data of a trading pair: WHERE symbol = 'BTCUSDT' AND timestamp >= :now_ms - 300000
 -- - Real-time price push, risk control checks, and other high-frequency operations
 -- Design notes:
 -- - Composite index (symbol, timestamp DESC): group by symbol first, then order by time descending
 -- - DESC order: prioritizes newest data, aligns with real-time query needs
--- - Supports index-only scans to avoid heap fetches and improve performance
-CREATE INDEX idx_tick_symbol_time ON tick_data(symbol, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_tick_symbol_time ON tick_data(symbol, timestamp DESC);
 
 -- 【Index 2】Data integrity unique index
 -- Use cases:
@@ -75,50 +72,127 @@ CREATE INDEX idx_tick_symbol_time ON tick_data(symbol, timestamp DESC);
 -- - Data consistency checks to ensure no duplicated trade records
 -- Design notes:
 -- - Unique constraint on three fields: same symbol + same trade_id + same timestamp = unique record
--- - Unique constraint implicitly creates corresponding unique index to support fast duplicate checks
+-- - Inserts use INSERT OR IGNORE, so re-delivered trades are dropped silently
 -- - Business logic aligns with financial system requirement of no duplicate and no missing data
-CREATE UNIQUE INDEX idx_tick_unique ON tick_data(symbol, trade_id, timestamp);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tick_unique ON tick_data(symbol, trade_id, timestamp);
 
 -- 【Index 3】Backtesting time index
 -- Use cases:
--- - Multi-symbol backtesting: WHERE timestamp BETWEEN '2025-01-01' AND '2025-01-02' AND symbol IN (...)
--- - Market-wide statistics: WHERE timestamp >= '2025-01-01' GROUP BY symbol
+-- - Multi-symbol backtesting: WHERE timestamp BETWEEN :start_ms AND :end_ms AND symbol IN (...)
+-- - Market-wide statistics: WHERE timestamp >= :start_ms GROUP BY symbol
 -- - Time-range data export: batch processing historical data by time intervals
 -- Design notes:
 -- - Single-column time index: more efficient than composite index when queries do not filter by symbol
 -- - Supports range queries: BETWEEN operation fully utilizes B-tree index
 -- - Essential for backtesting: ensures performance of historical data analysis
-CREATE INDEX idx_tick_timestamp ON tick_data(timestamp);
+CREATE INDEX IF NOT EXISTS idx_tick_timestamp ON tick_data(timestamp);
+
+-- =================================================================
+-- Thermodynamic Market States (migrations 2, 3)
+-- =================================================================
+
+CREATE TABLE IF NOT EXISTS market_states (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    temperature REAL NOT NULL,
+    pressure REAL NOT NULL,
+    volume_spread REAL NOT NULL,
+    entropy_level REAL,
+    timestamp INTEGER NOT NULL,
+    regime_label TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_market_states_symbol_timestamp ON market_states(symbol, timestamp DESC);
 
 -- =================================================================
--- Design Validation and Performance Testing
+-- Researcher Forward-Labelled Records (migrations 4, 5, 8)
 -- =================================================================
 
--- Verify table structure
-\d tick_data
+CREATE TABLE IF NOT EXISTS mbct_research_v2 (
+    timestamp INTEGER,
+    symbol TEXT,
+    price REAL,
+    entropy REAL,
+    pressure REAL,
+    nrg REAL,
+    regime TEXT,
+    symmetry REAL,
+    slope REAL,
+    ret_3s REAL,
+    ret_5s REAL,
+    ret_8s REAL,
+    ret_13s REAL,
+    ret_21s REAL,
+    ret_34s REAL,
+    ret_55s REAL,
+    ret_89s REAL,
+    ret_144s REAL,
+    ret_233s REAL,
+    z_entropy_21s REAL,
+    z_pressure_21s REAL,
+    z_nrg_21s REAL,
+    z_entropy_34s REAL,
+    z_pressure_34s REAL,
+    z_nrg_34s REAL,
+    ret_377s REAL,
+    feature_set TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_mbct_research_v2_symbol_timestamp ON mbct_research_v2(symbol, timestamp);
+
+-- =================================================================
+-- Calibrated Trading Parameters (migration 6)
+-- =================================================================
 
--- View index information and sizes
-SELECT 
-    indexname,
-    indexdef,
-    pg_size_pretty(pg_relation_size(indexname::regclass)) AS size
-FROM pg_indexes 
-WHERE tablename = 'tick_data'
-ORDER BY indexname;
+CREATE TABLE IF NOT EXISTS active_trading_params (
+    symbol TEXT PRIMARY KEY,
+    l_floor REAL DEFAULT 0.35,
+    s_ceiling REAL DEFAULT 0.65,
+    is_active INTEGER DEFAULT 1,
+    sample_count INTEGER DEFAULT 0,
+    last_updated INTEGER
+);
+
+-- =================================================================
+-- Live Strategy Log (migration 7)
+-- =================================================================
+
+CREATE TABLE IF NOT EXISTS live_strategy_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp INTEGER NOT NULL,
+    strategy_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    current_price TEXT NOT NULL,
+    signal_type TEXT NOT NULL, -- BUY/SELL/HOLD
+    portfolio_value TEXT NOT NULL,
+    total_pnl TEXT NOT NULL DEFAULT '0',
+    cache_hit INTEGER DEFAULT 1, -- Mark whether to get data from cache
+    processing_time_us INTEGER -- Processing time (microseconds), reflecting cache value
+);
+CREATE INDEX IF NOT EXISTS idx_live_strategy_time ON live_strategy_log(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_live_strategy_symbol ON live_strategy_log(strategy_id, symbol);
+
+-- =================================================================
+-- Migration Bookkeeping
+-- =================================================================
+
+CREATE TABLE IF NOT EXISTS schema_migrations (
+    version INTEGER PRIMARY KEY,
+    description TEXT NOT NULL,
+    applied_at INTEGER NOT NULL
+);
 
 -- Performance test queries (run after data is inserted)
 /*
 -- Real-time query test
-EXPLAIN (ANALYZE, BUFFERS) 
+EXPLAIN QUERY PLAN
 SELECT * FROM tick_data 
 WHERE symbol = 'BTCUSDT' 
 ORDER BY timestamp DESC 
 LIMIT 10;
 
 -- Backtesting query test
-EXPLAIN (ANALYZE, BUFFERS)
-SELECT COUNT(*), AVG(price) 
+EXPLAIN QUERY PLAN
+SELECT COUNT(*), AVG(CAST(price AS REAL)) 
 FROM tick_data 
-WHERE timestamp BETWEEN NOW() - INTERVAL '1 day' AND NOW()
+WHERE timestamp BETWEEN :start_ms AND :end_ms
 AND symbol IN ('BTCUSDT', 'ETHUSDT');
 */
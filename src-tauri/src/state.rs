@@ -30,6 +30,8 @@ impl AppState {
         tracing::info!("Cache initialized");
 
         let repository = TickDataRepository::new(pool, cache);
+        let schema_version = repository.migrate().await?;
+        tracing::info!("Database schema at version {}", schema_version);
 
         Ok(Self {
             repository: Arc::new(repository),
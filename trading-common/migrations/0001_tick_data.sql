@@ -0,0 +1,14 @@
+-- Tick data (SQLite port of the original Postgres tick_data design)
+-- Timestamps are Unix milliseconds, decimals are stored as TEXT to stay exact.
+CREATE TABLE IF NOT EXISTS tick_data (
+    timestamp INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    price TEXT NOT NULL,
+    quantity TEXT NOT NULL,
+    side TEXT NOT NULL CHECK (side IN ('BUY', 'SELL')),
+    trade_id TEXT NOT NULL,
+    is_buyer_maker INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_tick_symbol_time ON tick_data(symbol, timestamp DESC);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tick_unique ON tick_data(symbol, trade_id, timestamp);
+CREATE INDEX IF NOT EXISTS idx_tick_timestamp ON tick_data(timestamp);
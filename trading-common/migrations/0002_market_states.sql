@@ -0,0 +1,11 @@
+-- Thermodynamic market states (regime_label is added by migration 3)
+CREATE TABLE IF NOT EXISTS market_states (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    temperature REAL NOT NULL,
+    pressure REAL NOT NULL,
+    volume_spread REAL NOT NULL,
+    entropy_level REAL,
+    timestamp INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_market_states_symbol_timestamp ON market_states(symbol, timestamp DESC);
@@ -0,0 +1,29 @@
+-- Researcher forward-labelled records (ret_377s is added by migration 5)
+CREATE TABLE IF NOT EXISTS mbct_research_v2 (
+    timestamp INTEGER,
+    symbol TEXT,
+    price REAL,
+    entropy REAL,
+    pressure REAL,
+    nrg REAL,
+    regime TEXT,
+    symmetry REAL,
+    slope REAL,
+    ret_3s REAL,
+    ret_5s REAL,
+    ret_8s REAL,
+    ret_13s REAL,
+    ret_21s REAL,
+    ret_34s REAL,
+    ret_55s REAL,
+    ret_89s REAL,
+    ret_144s REAL,
+    ret_233s REAL,
+    z_entropy_21s REAL,
+    z_pressure_21s REAL,
+    z_nrg_21s REAL,
+    z_entropy_34s REAL,
+    z_pressure_34s REAL,
+    z_nrg_34s REAL
+);
+CREATE INDEX IF NOT EXISTS idx_mbct_research_v2_symbol_timestamp ON mbct_research_v2(symbol, timestamp);
@@ -0,0 +1,9 @@
+-- Self-calibrated trigger thresholds per symbol (ParamManager)
+CREATE TABLE IF NOT EXISTS active_trading_params (
+    symbol TEXT PRIMARY KEY,
+    l_floor REAL DEFAULT 0.35,
+    s_ceiling REAL DEFAULT 0.65,
+    is_active INTEGER DEFAULT 1,
+    sample_count INTEGER DEFAULT 0,
+    last_updated INTEGER
+);
@@ -0,0 +1,15 @@
+-- Real-time strategy log (SQLite port of config/live_strategy_log.sql)
+CREATE TABLE IF NOT EXISTS live_strategy_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp INTEGER NOT NULL,
+    strategy_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    current_price TEXT NOT NULL,
+    signal_type TEXT NOT NULL,
+    portfolio_value TEXT NOT NULL,
+    total_pnl TEXT NOT NULL DEFAULT '0',
+    cache_hit INTEGER DEFAULT 1,
+    processing_time_us INTEGER
+);
+CREATE INDEX IF NOT EXISTS idx_live_strategy_time ON live_strategy_log(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_live_strategy_symbol ON live_strategy_log(strategy_id, symbol);
@@ -0,0 +1,317 @@
+use chrono::Utc;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use tracing::{debug, info, warn};
+
+use super::types::{DataError, DataResult};
+
+// =================================================================
+// Migration Definitions
+// =================================================================
+
+/// A single schema change inside a migration
+#[derive(Debug, Clone, Copy)]
+pub enum MigrationStep {
+    /// Plain SQL, may contain several statements
+    Sql(&'static str),
+    /// Add a column unless it already exists (ALTER TABLE has no IF NOT EXISTS in SQLite)
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+}
+
+/// Versioned schema migration
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub steps: &'static [MigrationStep],
+}
+
+/// All migrations in order. Never edit an entry once released, append a new one instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create tick_data",
+        steps: &[MigrationStep::Sql(include_str!("../../migrations/0001_tick_data.sql"))],
+    },
+    Migration {
+        version: 2,
+        description: "create market_states",
+        steps: &[MigrationStep::Sql(include_str!("../../migrations/0002_market_states.sql"))],
+    },
+    Migration {
+        version: 3,
+        description: "add market_states.regime_label",
+        steps: &[MigrationStep::AddColumn {
+            table: "market_states",
+            column: "regime_label",
+            definition: "TEXT",
+        }],
+    },
+    Migration {
+        version: 4,
+        description: "create mbct_research_v2",
+        steps: &[MigrationStep::Sql(include_str!("../../migrations/0004_mbct_research_v2.sql"))],
+    },
+    Migration {
+        version: 5,
+        description: "add mbct_research_v2.ret_377s",
+        steps: &[MigrationStep::AddColumn {
+            table: "mbct_research_v2",
+            column: "ret_377s",
+            definition: "REAL",
+        }],
+    },
+    Migration {
+        version: 6,
+        description: "create active_trading_params",
+        steps: &[MigrationStep::Sql(include_str!("../../migrations/0006_active_trading_params.sql"))],
+    },
+    Migration {
+        version: 7,
+        description: "create live_strategy_log",
+        steps: &[MigrationStep::Sql(include_str!("../../migrations/0007_live_strategy_log.sql"))],
+    },
+    Migration {
+        version: 8,
+        description: "add mbct_research_v2.feature_set",
+        steps: &[MigrationStep::AddColumn {
+            table: "mbct_research_v2",
+            column: "feature_set",
+            definition: "TEXT",
+        }],
+    },
+];
+
+/// Latest schema version known to this build
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+// =================================================================
+// Migration Runner
+// =================================================================
+
+/// Bring the database up to the latest schema version.
+///
+/// Databases created before versioning existed (tables but no `schema_migrations`)
+/// are upgraded in place: every step is idempotent, missing columns are added.
+///
+/// The version check and all pending migrations run inside one `BEGIN IMMEDIATE`
+/// transaction, so two processes opening a fresh database serialize on SQLite's
+/// write lock and the second one sees the versions applied by the first.
+pub async fn run_migrations(pool: &SqlitePool) -> DataResult<i64> {
+    let mut conn = pool.acquire().await.map_err(DataError::Database)?;
+
+    sqlx::query("BEGIN IMMEDIATE")
+        .execute(&mut *conn)
+        .await
+        .map_err(DataError::Database)?;
+
+    match migrate_locked(&mut conn).await {
+        Ok(version) => {
+            sqlx::query("COMMIT")
+                .execute(&mut *conn)
+                .await
+                .map_err(DataError::Database)?;
+            Ok(version)
+        }
+        Err(e) => {
+            if let Err(rollback) = sqlx::query("ROLLBACK").execute(&mut *conn).await {
+                warn!("Rollback of schema migration failed: {}", rollback);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Check-and-apply body of [`run_migrations`], caller holds the write lock
+async fn migrate_locked(conn: &mut SqliteConnection) -> DataResult<i64> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(DataError::Database)?;
+
+    let current = current_version_on(&mut *conn).await?;
+    if current > latest_version() {
+        return Err(DataError::Config(format!(
+            "Database schema version {} is newer than supported version {}",
+            current,
+            latest_version()
+        )));
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        apply_migration(&mut *conn, migration).await?;
+        info!(
+            "Applied schema migration {}: {}",
+            migration.version, migration.description
+        );
+    }
+
+    Ok(latest_version())
+}
+
+/// Highest applied migration version, 0 for an unversioned database
+pub async fn current_version(pool: &SqlitePool) -> DataResult<i64> {
+    let mut conn = pool.acquire().await.map_err(DataError::Database)?;
+    current_version_on(&mut conn).await
+}
+
+async fn current_version_on(conn: &mut SqliteConnection) -> DataResult<i64> {
+    let row = sqlx::query("SELECT COALESCE(MAX(version), 0) FROM schema_migrations")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(DataError::Database)?;
+    Ok(row.get(0))
+}
+
+async fn apply_migration(conn: &mut SqliteConnection, migration: &Migration) -> DataResult<()> {
+    for step in migration.steps {
+        match step {
+            MigrationStep::Sql(sql) => {
+                sqlx::query(sql)
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(DataError::Database)?;
+            }
+            MigrationStep::AddColumn {
+                table,
+                column,
+                definition,
+            } => {
+                let exists: i64 = sqlx::query(
+                    "SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?",
+                )
+                .bind(table)
+                .bind(column)
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(DataError::Database)?
+                .get(0);
+
+                if exists > 0 {
+                    debug!("Column {}.{} already present, skipping", table, column);
+                    continue;
+                }
+
+                sqlx::query(&format!(
+                    "ALTER TABLE {} ADD COLUMN {} {}",
+                    table, column, definition
+                ))
+                .execute(&mut *conn)
+                .await
+                .map_err(DataError::Database)?;
+            }
+        }
+    }
+
+    sqlx::query("INSERT INTO schema_migrations (version, description, applied_at) VALUES (?, ?, ?)")
+        .bind(migration.version)
+        .bind(migration.description)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *conn)
+        .await
+        .map_err(DataError::Database)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn memory_pool() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    async fn columns(pool: &SqlitePool, table: &str) -> Vec<String> {
+        sqlx::query("SELECT name FROM pragma_table_info(?)")
+            .bind(table)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.get(0))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_fresh_database_reaches_latest_version() {
+        let pool = memory_pool().await;
+        assert_eq!(run_migrations(&pool).await.unwrap(), latest_version());
+        assert_eq!(current_version(&pool).await.unwrap(), latest_version());
+
+        // Second run is a no-op
+        assert_eq!(run_migrations(&pool).await.unwrap(), latest_version());
+        assert!(columns(&pool, "market_states").await.contains(&"regime_label".to_string()));
+        assert!(columns(&pool, "mbct_research_v2").await.contains(&"ret_377s".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_tables_are_upgraded_in_place() {
+        let pool = memory_pool().await;
+
+        // Schema as written by the pre-migration researcher and TickDataRepository
+        sqlx::query(
+            "CREATE TABLE market_states (id INTEGER PRIMARY KEY AUTOINCREMENT, symbol TEXT NOT NULL, \
+             temperature REAL NOT NULL, pressure REAL NOT NULL, volume_spread REAL NOT NULL, \
+             entropy_level REAL, timestamp INTEGER NOT NULL); \
+             INSERT INTO market_states (symbol, temperature, pressure, volume_spread, timestamp) \
+             VALUES ('BTC', 1.0, 2.0, 3.0, 4); \
+             CREATE TABLE mbct_research_v2 (timestamp INTEGER, symbol TEXT, price REAL, ret_3s REAL); \
+             CREATE TABLE active_trading_params (symbol TEXT PRIMARY KEY, l_floor REAL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        run_migrations(&pool).await.unwrap();
+
+        assert!(columns(&pool, "market_states").await.contains(&"regime_label".to_string()));
+        assert!(columns(&pool, "mbct_research_v2").await.contains(&"ret_377s".to_string()));
+
+        let count: i64 = sqlx::query("SELECT COUNT(*) FROM market_states")
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_runners_apply_each_version_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("race.db").display());
+
+        // Two independent pools stand in for two processes starting at once
+        let a = SqlitePoolOptions::new().connect(&url).await.unwrap();
+        let b = SqlitePoolOptions::new().connect(&url).await.unwrap();
+
+        let (ra, rb) = tokio::join!(run_migrations(&a), run_migrations(&b));
+        assert_eq!(ra.unwrap(), latest_version());
+        assert_eq!(rb.unwrap(), latest_version());
+
+        let applied: i64 = sqlx::query("SELECT COUNT(*) FROM schema_migrations")
+            .fetch_one(&a)
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(applied, MIGRATIONS.len() as i64);
+    }
+}
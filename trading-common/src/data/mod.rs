@@ -1,3 +1,5 @@
 pub mod cache;
+pub mod migrations;
 pub mod repository;
+pub mod snapshot_store;
 pub mod types;
@@ -1,16 +1,20 @@
 use chrono::{DateTime, Duration, Utc};
 use rust_decimal::Decimal;
-use rust_decimal::prelude::ToPrimitive;
-use sqlx::SqlitePool;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use sqlx::query::Query;
+use sqlx::sqlite::{SqliteArguments, SqliteRow};
+use sqlx::{Row, Sqlite, SqlitePool};
 use std::collections::HashMap;
+use std::str::FromStr;
 use tracing::{debug, info, warn};
 
 use crate::data::types::{LiveStrategyLog, OHLCData, Timeframe};
 
 use super::cache::{TickDataCache, TieredCache};
+use super::migrations;
 use super::types::{
-    BacktestDataInfo, DataError, DataResult, DbStats, TickData, TickQuery,
-    MarketState,
+    BacktestDataInfo, DataError, DataResult, DbStats, SymbolDataInfo, TickData, TickQuery,
+    MarketState, MarketStateAggregate, TradeSide,
 };
 
 // =================================================================
@@ -26,9 +30,12 @@ const MAX_BATCH_SIZE: usize = 1000;
 // =================================================================
 
 /// TickData repository for database operations
-pub struct TickDataRepository {
+///
+/// Generic over the cache layer so that tests and tools can run without Redis;
+/// the application always uses the default `TieredCache`.
+pub struct TickDataRepository<C: TickDataCache = TieredCache> {
     pool: SqlitePool,
-    cache: TieredCache,
+    cache: C,
 }
 
 /// Simplified Repository for Research Engine
@@ -38,13 +45,13 @@ pub struct Repository {
 
 impl Repository {
     pub async fn new(url: &str) -> DataResult<Self> {
-        let pool = SqlitePool::connect(url).await.map_err(|e| DataError::Database(e))?;
+        let pool = SqlitePool::connect(url).await.map_err(DataError::Database)?;
         
         // Enable WAL mode for high-throughput parallel writes
         sqlx::query("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")
             .execute(&pool)
             .await
-            .map_err(|e| DataError::Database(e))?;
+            .map_err(DataError::Database)?;
 
         Ok(Self { pool })
     }
@@ -53,32 +60,16 @@ impl Repository {
         Self { pool }
     }
 
-    pub async fn ensure_market_states_table(&self) -> DataResult<()> {
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS market_states (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                symbol TEXT NOT NULL,
-                temperature REAL NOT NULL,
-                pressure REAL NOT NULL,
-                volume_spread REAL NOT NULL,
-                entropy_level REAL,
-                regime_label TEXT,
-                timestamp INTEGER NOT NULL
-            )
-            "#
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| DataError::Database(e))?;
-        Ok(())
+    /// Apply all pending schema migrations
+    pub async fn migrate(&self) -> DataResult<i64> {
+        migrations::run_migrations(&self.pool).await
     }
 
     pub async fn insert_market_state(&self, state: &MarketState) -> DataResult<()> {
         let temp = state.temperature.to_f64().unwrap_or(0.0);
         let press = state.pressure.to_f64().unwrap_or(0.0);
         let vol = state.volume_spread.to_f64().unwrap_or(0.0);
-        let entropy = state.entropy_level.map(|e| e.to_f64()).flatten();
+        let entropy = state.entropy_level.and_then(|e| e.to_f64());
         
         sqlx::query(
             r#"
@@ -96,14 +87,168 @@ impl Repository {
         .bind(state.timestamp)
         .execute(&self.pool)
         .await
-        .map_err(|e| DataError::Database(e))?;
+        .map_err(DataError::Database)?;
         Ok(())
     }
+
+    // =================================================================
+    // Market State Queries
+    // =================================================================
+
+    /// Market states of one symbol in [start_ms, end_ms], oldest first
+    pub async fn get_market_states(
+        &self,
+        symbol: &str,
+        start_ms: i64,
+        end_ms: i64,
+        limit: Option<u32>,
+    ) -> DataResult<Vec<MarketState>> {
+        let limit = limit.map(|l| l.min(MAX_QUERY_LIMIT) as i64).unwrap_or(-1);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT symbol, temperature, pressure, volume_spread, entropy_level, regime_label, timestamp
+            FROM market_states
+            WHERE symbol = ?1 AND timestamp >= ?2 AND timestamp <= ?3
+            ORDER BY timestamp ASC
+            LIMIT ?4
+            "#,
+        )
+        .bind(symbol)
+        .bind(start_ms)
+        .bind(end_ms)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DataError::Database)?;
+
+        Ok(rows.iter().map(row_to_market_state).collect())
+    }
+
+    /// Most recent market state of every symbol, ordered by symbol
+    pub async fn get_latest_market_states(&self) -> DataResult<Vec<MarketState>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT m.symbol, m.temperature, m.pressure, m.volume_spread, m.entropy_level,
+                   m.regime_label, m.timestamp
+            FROM market_states m
+            JOIN (
+                SELECT symbol, MAX(timestamp) AS max_ts
+                FROM market_states
+                GROUP BY symbol
+            ) latest ON m.symbol = latest.symbol AND m.timestamp = latest.max_ts
+            GROUP BY m.symbol
+            ORDER BY m.symbol
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DataError::Database)?;
+
+        Ok(rows.iter().map(row_to_market_state).collect())
+    }
+
+    /// Highest `spring_tension()` states in [start_ms, end_ms], optionally for one symbol.
+    /// States with zero spread have no defined tension and are skipped.
+    pub async fn get_top_market_states_by_tension(
+        &self,
+        symbol: Option<&str>,
+        start_ms: i64,
+        end_ms: i64,
+        limit: u32,
+    ) -> DataResult<Vec<MarketState>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT symbol, temperature, pressure, volume_spread, entropy_level, regime_label, timestamp
+            FROM market_states
+            WHERE volume_spread > 0
+              AND (?1 IS NULL OR symbol = ?1)
+              AND timestamp >= ?2 AND timestamp <= ?3
+            ORDER BY pressure / volume_spread DESC, timestamp DESC
+            LIMIT ?4
+            "#,
+        )
+        .bind(symbol)
+        .bind(start_ms)
+        .bind(end_ms)
+        .bind(limit.min(MAX_QUERY_LIMIT) as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DataError::Database)?;
+
+        Ok(rows.iter().map(row_to_market_state).collect())
+    }
+
+    /// Per-interval aggregates of one symbol; buckets are aligned to multiples of `bucket_ms`
+    pub async fn get_market_state_aggregates(
+        &self,
+        symbol: &str,
+        start_ms: i64,
+        end_ms: i64,
+        bucket_ms: i64,
+    ) -> DataResult<Vec<MarketStateAggregate>> {
+        if bucket_ms <= 0 {
+            return Err(DataError::Validation("bucket_ms must be positive".into()));
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT (timestamp / ?4) * ?4 AS bucket_start,
+                   COUNT(*) AS sample_count,
+                   AVG(temperature) AS mean_temperature,
+                   AVG(pressure) AS mean_pressure,
+                   AVG(volume_spread) AS mean_volume_spread,
+                   AVG(entropy_level) AS mean_entropy,
+                   MAX(CASE WHEN volume_spread > 0 THEN pressure / volume_spread END) AS max_tension
+            FROM market_states
+            WHERE symbol = ?1 AND timestamp >= ?2 AND timestamp <= ?3
+            GROUP BY bucket_start
+            ORDER BY bucket_start ASC
+            "#,
+        )
+        .bind(symbol)
+        .bind(start_ms)
+        .bind(end_ms)
+        .bind(bucket_ms)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DataError::Database)?;
+
+        Ok(rows
+            .iter()
+            .map(|row| MarketStateAggregate {
+                symbol: symbol.to_string(),
+                bucket_start: row.get("bucket_start"),
+                bucket_ms,
+                sample_count: row.get::<i64, _>("sample_count") as u64,
+                mean_temperature: row.get("mean_temperature"),
+                mean_pressure: row.get("mean_pressure"),
+                mean_volume_spread: row.get("mean_volume_spread"),
+                mean_entropy: row.get("mean_entropy"),
+                max_tension: row.get("max_tension"),
+            })
+            .collect())
+    }
+}
+
+/// Convert a `market_states` row; values are stored as REAL
+fn row_to_market_state(row: &SqliteRow) -> MarketState {
+    let decimal = |v: f64| Decimal::from_f64(v).unwrap_or(Decimal::ZERO);
+
+    MarketState {
+        symbol: row.get("symbol"),
+        temperature: decimal(row.get("temperature")),
+        pressure: decimal(row.get("pressure")),
+        volume_spread: decimal(row.get("volume_spread")),
+        entropy_level: row.get::<Option<f64>, _>("entropy_level").and_then(Decimal::from_f64),
+        timestamp: row.get("timestamp"),
+        regime: row.get("regime_label"),
+    }
 }
 
-impl TickDataRepository {
+impl<C: TickDataCache> TickDataRepository<C> {
     /// Create new repository instance
-    pub fn new(pool: SqlitePool, cache: TieredCache) -> Self {
+    pub fn new(pool: SqlitePool, cache: C) -> Self {
         Self { pool, cache }
     }
 
@@ -113,10 +258,20 @@ impl TickDataRepository {
     }
 
     /// Get cache reference
-    pub fn get_cache(&self) -> &TieredCache {
+    pub fn get_cache(&self) -> &C {
         &self.cache
     }
 
+    /// Apply all pending schema migrations (tick_data, market_states, ...)
+    pub async fn migrate(&self) -> DataResult<i64> {
+        migrations::run_migrations(&self.pool).await
+    }
+
+    /// Market state queries on the same pool
+    pub fn market_states(&self) -> Repository {
+        Repository::from_pool(self.pool.clone())
+    }
+
     // =================================================================
     // Insert Operations
     // =================================================================
@@ -130,6 +285,17 @@ impl TickDataRepository {
             tick.symbol, tick.price, tick.trade_id
         );
 
+        let inserted = Self::bind_insert(tick)
+            .execute(&self.pool)
+            .await
+            .map_err(DataError::Database)?
+            .rows_affected();
+
+        if inserted == 0 {
+            debug!("Duplicate tick ignored: trade_id={}", tick.trade_id);
+            return Ok(());
+        }
+
         // Update cache
         if let Err(e) = self.cache.push_tick(tick).await {
             warn!("Failed to update cache after insert: {}", e);
@@ -154,13 +320,28 @@ impl TickDataRepository {
 
         let mut total_inserted = 0;
         for chunk in ticks.chunks(MAX_BATCH_SIZE) {
-            // Update cache for each chunk
+            // One transaction per chunk, duplicates are skipped by the unique index
+            let mut tx = self.pool.begin().await.map_err(DataError::Database)?;
+            let mut fresh = Vec::with_capacity(chunk.len());
             for tick in chunk {
+                let affected = Self::bind_insert(tick)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(DataError::Database)?
+                    .rows_affected();
+                if affected > 0 {
+                    fresh.push(tick);
+                }
+            }
+            tx.commit().await.map_err(DataError::Database)?;
+
+            // Update cache for each chunk
+            for tick in &fresh {
                 if let Err(e) = self.cache.push_tick(tick).await {
                     warn!("Failed to update cache for tick {}: {}", tick.trade_id, e);
                 }
             }
-            total_inserted += chunk.len();
+            total_inserted += fresh.len();
         }
 
         info!(
@@ -198,7 +379,41 @@ impl TickDataRepository {
             }
         }
 
-        Ok(Vec::new())
+        let mut sql = String::from(
+            "SELECT timestamp, symbol, price, quantity, side, trade_id, is_buyer_maker \
+             FROM tick_data WHERE symbol = ?",
+        );
+        if query.start_time.is_some() {
+            sql.push_str(" AND timestamp >= ?");
+        }
+        if query.end_time.is_some() {
+            sql.push_str(" AND timestamp <= ?");
+        }
+        if query.trade_side.is_some() {
+            sql.push_str(" AND side = ?");
+        }
+        sql.push_str(" ORDER BY timestamp DESC LIMIT ?");
+
+        let mut q = sqlx::query(&sql).bind(&query.symbol);
+        if let Some(start) = query.start_time {
+            q = q.bind(start.timestamp_millis());
+        }
+        if let Some(end) = query.end_time {
+            q = q.bind(end.timestamp_millis());
+        }
+        if let Some(side) = query.trade_side {
+            q = q.bind(side.as_db_str());
+        }
+
+        let rows = q
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DataError::Database)?;
+
+        let ticks = rows.iter().map(Self::row_to_tick).collect::<DataResult<Vec<_>>>()?;
+        debug!("Database returned {} ticks for {}", ticks.len(), query.symbol);
+        Ok(ticks)
     }
 
     /// Get latest price for a symbol
@@ -212,7 +427,16 @@ impl TickDataRepository {
             return Ok(Some(latest_tick.price));
         }
 
-        Ok(None)
+        let row = sqlx::query(
+            "SELECT price FROM tick_data WHERE symbol = ? ORDER BY timestamp DESC LIMIT 1",
+        )
+        .bind(symbol)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DataError::Database)?;
+
+        row.map(|r| Self::parse_decimal(&r.get::<String, _>(0)))
+            .transpose()
     }
 
     /// Get latest prices for multiple symbols
@@ -237,6 +461,16 @@ impl TickDataRepository {
             }
         }
 
+        // Fill cache misses from the database
+        for symbol in symbols {
+            if prices.contains_key(symbol) {
+                continue;
+            }
+            if let Some(price) = self.get_latest_price(symbol).await? {
+                prices.insert(symbol.clone(), price);
+            }
+        }
+
         debug!("Retrieved latest prices for {} symbols", prices.len());
         Ok(prices)
     }
@@ -252,28 +486,81 @@ impl TickDataRepository {
         count: i64,
     ) -> DataResult<Vec<TickData>> {
         debug!("Fetching {} recent ticks for backtest: {}", count, symbol);
-        Ok(Vec::new())
+
+        let rows = sqlx::query(
+            "SELECT timestamp, symbol, price, quantity, side, trade_id, is_buyer_maker \
+             FROM tick_data WHERE symbol = ? ORDER BY timestamp DESC LIMIT ?",
+        )
+        .bind(symbol)
+        .bind(count)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DataError::Database)?;
+
+        // Backtests replay in chronological order
+        let mut ticks = rows.iter().map(Self::row_to_tick).collect::<DataResult<Vec<_>>>()?;
+        ticks.reverse();
+        Ok(ticks)
     }
 
     /// Get historical data for backtesting
     pub async fn get_historical_data_for_backtest(
         &self,
-        _symbol: &str,
-        _start_time: DateTime<Utc>,
-        _end_time: DateTime<Utc>,
-        _limit: Option<i64>,
+        symbol: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        limit: Option<i64>,
     ) -> DataResult<Vec<TickData>> {
-        Ok(Vec::new())
+        debug!(
+            "Fetching historical ticks for backtest: {} [{} - {}]",
+            symbol, start_time, end_time
+        );
+
+        let rows = sqlx::query(
+            "SELECT timestamp, symbol, price, quantity, side, trade_id, is_buyer_maker \
+             FROM tick_data WHERE symbol = ? AND timestamp >= ? AND timestamp <= ? \
+             ORDER BY timestamp ASC LIMIT ?",
+        )
+        .bind(symbol)
+        .bind(start_time.timestamp_millis())
+        .bind(end_time.timestamp_millis())
+        .bind(limit.unwrap_or(-1))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DataError::Database)?;
+
+        rows.iter().map(Self::row_to_tick).collect()
     }
 
     /// Get backtest data information
     pub async fn get_backtest_data_info(&self) -> DataResult<BacktestDataInfo> {
+        let rows = sqlx::query(
+            "SELECT symbol, COUNT(*), MIN(timestamp), MAX(timestamp), \
+             MIN(CAST(price AS REAL)), MAX(CAST(price AS REAL)) \
+             FROM tick_data GROUP BY symbol ORDER BY symbol",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DataError::Database)?;
+
+        let symbol_info: Vec<SymbolDataInfo> = rows
+            .iter()
+            .map(|r| SymbolDataInfo {
+                symbol: r.get(0),
+                records_count: r.get::<i64, _>(1) as u64,
+                earliest_time: r.get::<Option<i64>, _>(2).and_then(DateTime::from_timestamp_millis),
+                latest_time: r.get::<Option<i64>, _>(3).and_then(DateTime::from_timestamp_millis),
+                min_price: r.get::<Option<f64>, _>(4).and_then(Decimal::from_f64),
+                max_price: r.get::<Option<f64>, _>(5).and_then(Decimal::from_f64),
+            })
+            .collect();
+
         Ok(BacktestDataInfo {
-            total_records: 0,
-            symbols_count: 0,
-            earliest_time: None,
-            latest_time: None,
-            symbol_info: Vec::new(),
+            total_records: symbol_info.iter().map(|i| i.records_count).sum(),
+            symbols_count: symbol_info.len() as u64,
+            earliest_time: symbol_info.iter().filter_map(|i| i.earliest_time).min(),
+            latest_time: symbol_info.iter().filter_map(|i| i.latest_time).max(),
+            symbol_info,
         })
     }
 
@@ -281,41 +568,18 @@ impl TickDataRepository {
     // Thermodynamic State Operations
     // =================================================================
 
-    /// Ensure market_states table exists
-    pub async fn ensure_market_states_table(&self) -> DataResult<()> {
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS market_states (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                symbol TEXT NOT NULL,
-                temperature REAL NOT NULL,
-                pressure REAL NOT NULL,
-                volume_spread REAL NOT NULL,
-                entropy_level REAL,
-                timestamp INTEGER NOT NULL
-            );
-            CREATE INDEX IF NOT EXISTS idx_market_states_symbol_timestamp ON market_states(symbol, timestamp DESC);
-            "#
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| DataError::Database(e))?;
-        
-        Ok(())
-    }
-
     /// Insert thermodynamic market state
     pub async fn insert_market_state(&self, state: &MarketState) -> DataResult<()> {
         let temp = state.temperature.to_f64().ok_or_else(|| DataError::Validation("Invalid temperature".into()))?;
         let press = state.pressure.to_f64().ok_or_else(|| DataError::Validation("Invalid pressure".into()))?;
         let vol = state.volume_spread.to_f64().ok_or_else(|| DataError::Validation("Invalid volume_spread".into()))?;
-        let entropy = state.entropy_level.map(|e| e.to_f64()).flatten();
+        let entropy = state.entropy_level.and_then(|e| e.to_f64());
         
         sqlx::query(
             r#"
             INSERT INTO market_states 
-            (symbol, temperature, pressure, volume_spread, entropy_level, timestamp)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            (symbol, temperature, pressure, volume_spread, entropy_level, regime_label, timestamp)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
             "#
         )
         .bind(&state.symbol)
@@ -323,26 +587,53 @@ impl TickDataRepository {
         .bind(press)
         .bind(vol)
         .bind(entropy)
+        .bind(&state.regime)
         .bind(state.timestamp)
         .execute(&self.pool)
         .await
-        .map_err(|e| DataError::Database(e))?;
+        .map_err(DataError::Database)?;
 
         Ok(())
     }
 
     /// Clean up old tick data
-    pub async fn cleanup_old_data(&self, _days_to_keep: f64) -> DataResult<u64> {
-        Ok(0)
+    pub async fn cleanup_old_data(&self, days_to_keep: f64) -> DataResult<u64> {
+        if days_to_keep < 0.0 {
+            return Err(DataError::Validation("days_to_keep cannot be negative".into()));
+        }
+
+        let cutoff = Utc::now() - Duration::milliseconds((days_to_keep * 86_400_000.0) as i64);
+        let deleted = sqlx::query("DELETE FROM tick_data WHERE timestamp < ?")
+            .bind(cutoff.timestamp_millis())
+            .execute(&self.pool)
+            .await
+            .map_err(DataError::Database)?
+            .rows_affected();
+
+        info!("Cleaned up {} ticks older than {}", deleted, cutoff);
+        Ok(deleted)
     }
 
     /// Get database statistics
     pub async fn get_db_stats(&self, symbol: Option<&str>) -> DataResult<DbStats> {
+        let row = match symbol {
+            Some(s) => sqlx::query(
+                "SELECT COUNT(*), MIN(timestamp), MAX(timestamp) FROM tick_data WHERE symbol = ?",
+            )
+            .bind(s)
+            .fetch_one(&self.pool)
+            .await,
+            None => sqlx::query("SELECT COUNT(*), MIN(timestamp), MAX(timestamp) FROM tick_data")
+                .fetch_one(&self.pool)
+                .await,
+        }
+        .map_err(DataError::Database)?;
+
         Ok(DbStats {
             symbol: symbol.map(|s| s.to_string()),
-            total_records: 0,
-            earliest_timestamp: None,
-            latest_timestamp: None,
+            total_records: row.get::<i64, _>(0) as u64,
+            earliest_timestamp: row.get::<Option<i64>, _>(1).and_then(DateTime::from_timestamp_millis),
+            latest_timestamp: row.get::<Option<i64>, _>(2).and_then(DateTime::from_timestamp_millis),
         })
     }
 
@@ -371,6 +662,52 @@ impl TickDataRepository {
         Ok(())
     }
 
+    /// Prepare the idempotent insert statement for a tick
+    fn bind_insert(tick: &TickData) -> Query<'_, Sqlite, SqliteArguments<'_>> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO tick_data
+            (timestamp, symbol, price, quantity, side, trade_id, is_buyer_maker)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#
+        )
+        .bind(tick.timestamp.timestamp_millis())
+        .bind(&tick.symbol)
+        .bind(tick.price.to_string())
+        .bind(tick.quantity.to_string())
+        .bind(tick.side.as_db_str())
+        .bind(&tick.trade_id)
+        .bind(tick.is_buyer_maker)
+    }
+
+    /// Map a tick_data row back into TickData
+    fn row_to_tick(row: &SqliteRow) -> DataResult<TickData> {
+        let millis: i64 = row.get("timestamp");
+        let timestamp = DateTime::from_timestamp_millis(millis)
+            .ok_or_else(|| DataError::InvalidFormat(format!("Invalid timestamp: {}", millis)))?;
+
+        let side = match row.get::<String, _>("side").as_str() {
+            "BUY" => TradeSide::Buy,
+            "SELL" => TradeSide::Sell,
+            other => return Err(DataError::InvalidFormat(format!("Invalid side: {}", other))),
+        };
+
+        Ok(TickData {
+            timestamp,
+            symbol: row.get("symbol"),
+            price: Self::parse_decimal(&row.get::<String, _>("price"))?,
+            quantity: Self::parse_decimal(&row.get::<String, _>("quantity"))?,
+            side,
+            trade_id: row.get("trade_id"),
+            is_buyer_maker: row.get("is_buyer_maker"),
+        })
+    }
+
+    fn parse_decimal(value: &str) -> DataResult<Decimal> {
+        Decimal::from_str(value)
+            .map_err(|e| DataError::InvalidFormat(format!("Invalid decimal '{}': {}", value, e)))
+    }
+
     /// Check if query is for recent data (suitable for cache)
     fn is_recent_query(&self, query: &TickQuery) -> bool {
         if let Some(start_time) = query.start_time {
@@ -417,7 +754,7 @@ impl TickDataRepository {
             let window_start = timeframe.align_timestamp(tick.timestamp);
             windows
                 .entry(window_start)
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(tick);
         }
 
@@ -425,7 +762,7 @@ impl TickDataRepository {
             .into_iter()
             .filter_map(|(window_start, mut window_ticks)| {
                 if window_start >= aligned_start && window_start <= aligned_end {
-                    window_ticks.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+                    window_ticks.sort_by_key(|t| t.timestamp);
                     OHLCData::from_ticks(&window_ticks, timeframe, window_start)
                 } else {
                     None
@@ -433,7 +770,7 @@ impl TickDataRepository {
             })
             .collect();
 
-        ohlc_data.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        ohlc_data.sort_by_key(|c| c.timestamp);
         Ok(ohlc_data)
     }
 
@@ -452,9 +789,150 @@ impl TickDataRepository {
     /// Get ticks for a specific time duration
     pub async fn get_ticks_for_timespan(
         &self,
-        _symbol: &str,
-        _duration_hours: i64,
+        symbol: &str,
+        duration_hours: i64,
     ) -> DataResult<Vec<TickData>> {
-        Ok(Vec::new())
+        let end_time = Utc::now();
+        let start_time = end_time - Duration::hours(duration_hours);
+        self.get_historical_data_for_backtest(symbol, start_time, end_time, None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::cache::InMemoryTickCache;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn create_test_repository() -> TickDataRepository<InMemoryTickCache> {
+        // A single connection keeps the in-memory database alive for the whole test
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = TickDataRepository::new(pool, InMemoryTickCache::new(100, 300));
+        repo.migrate().await.unwrap();
+        repo
+    }
+
+    fn create_test_tick(symbol: &str, price: &str, trade_id: &str, offset_secs: i64) -> TickData {
+        TickData::new(
+            DateTime::from_timestamp_millis(1_700_000_000_000).unwrap() + Duration::seconds(offset_secs),
+            symbol.to_string(),
+            price.parse::<Decimal>().unwrap(),
+            "0.5".parse::<Decimal>().unwrap(),
+            TradeSide::Sell,
+            trade_id.to_string(),
+            true,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_read_back() {
+        let repo = create_test_repository().await;
+        let tick = create_test_tick("BTC", "43123.12345678", "t1", 0);
+
+        repo.insert_tick(&tick).await.unwrap();
+        // Re-delivery of the same trade must not create a second row
+        repo.insert_tick(&tick).await.unwrap();
+
+        let ticks = repo.get_recent_ticks_for_backtest("BTC", 10).await.unwrap();
+        assert_eq!(ticks, vec![tick]);
+
+        let stats = repo.get_db_stats(Some("BTC")).await.unwrap();
+        assert_eq!(stats.total_records, 1);
+    }
+
+    #[tokio::test]
+    async fn test_batch_insert_and_range_queries() {
+        let repo = create_test_repository().await;
+        let ticks: Vec<TickData> = (0..5)
+            .map(|i| create_test_tick("ETH", &format!("{}.5", 2000 + i), &format!("e{}", i), i * 60))
+            .collect();
+
+        assert_eq!(repo.batch_insert(ticks.clone()).await.unwrap(), 5);
+        assert_eq!(repo.batch_insert(ticks.clone()).await.unwrap(), 0);
+
+        let start = ticks[1].timestamp;
+        let end = ticks[3].timestamp;
+        let range = repo
+            .get_historical_data_for_backtest("ETH", start, end, None)
+            .await
+            .unwrap();
+        assert_eq!(range, ticks[1..=3].to_vec());
+
+        let info = repo.get_backtest_data_info().await.unwrap();
+        assert_eq!(info.total_records, 5);
+        assert_eq!(info.symbols_count, 1);
+        assert_eq!(info.earliest_time, Some(ticks[0].timestamp));
+        assert_eq!(info.latest_time, Some(ticks[4].timestamp));
+        assert!(info.has_sufficient_data("ETH", 5));
+
+        let ohlc = repo
+            .generate_ohlc_from_ticks("ETH", Timeframe::FiveMinutes, ticks[0].timestamp, ticks[4].timestamp, None)
+            .await
+            .unwrap();
+        assert_eq!(ohlc.iter().map(|c| c.trade_count).sum::<u64>(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_old_data() {
+        let repo = create_test_repository().await;
+        repo.insert_tick(&create_test_tick("SOL", "100", "s1", 0)).await.unwrap();
+
+        let mut recent = create_test_tick("SOL", "101", "s2", 0);
+        recent.timestamp = Utc::now();
+        repo.insert_tick(&recent).await.unwrap();
+
+        assert_eq!(repo.cleanup_old_data(1.0).await.unwrap(), 1);
+        assert_eq!(repo.get_latest_price("SOL").await.unwrap(), Some(recent.price));
+    }
+
+    fn create_test_state(symbol: &str, pressure: &str, spread: &str, timestamp: i64) -> MarketState {
+        MarketState {
+            symbol: symbol.to_string(),
+            temperature: Decimal::from(100),
+            pressure: pressure.parse().unwrap(),
+            volume_spread: spread.parse().unwrap(),
+            entropy_level: Some(Decimal::from(2)),
+            timestamp,
+            regime: Some("LAMINAR".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_market_state_queries() {
+        let repo = create_test_repository().await.market_states();
+        let states = vec![
+            create_test_state("BTC", "10", "1", 1_000),
+            create_test_state("BTC", "40", "2", 2_000),
+            create_test_state("BTC", "30", "0", 61_000),
+            create_test_state("ETH", "50", "1", 1_500),
+        ];
+        for state in &states {
+            repo.insert_market_state(state).await.unwrap();
+        }
+
+        let btc = repo.get_market_states("BTC", 0, 60_000, None).await.unwrap();
+        assert_eq!(btc, states[0..2].to_vec());
+
+        let latest = repo.get_latest_market_states().await.unwrap();
+        assert_eq!(latest, vec![states[2].clone(), states[3].clone()]);
+
+        // Zero spread is skipped, ETH (50) before BTC (20) before BTC (10)
+        let top = repo.get_top_market_states_by_tension(None, 0, i64::MAX, 10).await.unwrap();
+        assert_eq!(top, vec![states[3].clone(), states[1].clone(), states[0].clone()]);
+        let top_btc = repo.get_top_market_states_by_tension(Some("BTC"), 0, i64::MAX, 1).await.unwrap();
+        assert_eq!(top_btc[0].spring_tension(), Decimal::from(20));
+
+        let buckets = repo.get_market_state_aggregates("BTC", 0, i64::MAX, 60_000).await.unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].bucket_start, 0);
+        assert_eq!(buckets[0].sample_count, 2);
+        assert_eq!(buckets[0].mean_pressure, 25.0);
+        assert_eq!(buckets[0].max_tension, Some(20.0));
+        assert_eq!(buckets[1].bucket_start, 60_000);
+        assert_eq!(buckets[1].max_tension, None);
     }
 }
@@ -0,0 +1,449 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use super::types::{DataError, DataResult, L2Snapshot};
+
+// =================================================================
+// Segment Layout
+// =================================================================
+//
+// {root}/{symbol}/{YYYYMMDDHH}.l2z   one append-only segment per symbol and UTC hour
+//
+// Record: | time: u64 LE | len: u32 LE | deflate(JSON L2Snapshot): len bytes |
+//
+// Every record is compressed on its own, so a crash can only lose the record
+// being written. A torn tail is cut off when the segment is reopened for writing
+// and skipped by the reader.
+
+const SEGMENT_EXTENSION: &str = "l2z";
+const SEGMENT_MS: u64 = 3_600_000;
+const RECORD_HEADER_LEN: usize = 12;
+
+/// One hourly segment file of a symbol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentInfo {
+    pub symbol: String,
+    /// Start of the covered hour (Unix millis)
+    pub start_ms: u64,
+    pub path: PathBuf,
+}
+
+impl SegmentInfo {
+    /// Whether the segment can hold snapshots in [start_ms, end_ms]
+    pub fn overlaps(&self, start_ms: u64, end_ms: u64) -> bool {
+        self.start_ms <= end_ms && self.start_ms + SEGMENT_MS > start_ms
+    }
+}
+
+fn segment_name(start_ms: u64) -> String {
+    let time = DateTime::<Utc>::from_timestamp_millis(start_ms as i64).unwrap_or_default();
+    format!("{}.{}", time.format("%Y%m%d%H"), SEGMENT_EXTENSION)
+}
+
+fn parse_segment_name(name: &str) -> Option<u64> {
+    let stem = name.strip_suffix(&format!(".{}", SEGMENT_EXTENSION))?;
+    if stem.len() != 10 {
+        return None;
+    }
+    let date = NaiveDate::parse_from_str(&stem[..8], "%Y%m%d").ok()?;
+    let hour: u32 = stem[8..].parse().ok()?;
+    let start = date.and_hms_opt(hour, 0, 0)?.and_utc().timestamp_millis();
+    u64::try_from(start).ok()
+}
+
+/// Directory name of a symbol; spot pairs like "PURR/USDC" must not create subdirectories
+fn symbol_dir(symbol: &str) -> String {
+    symbol.replace(['/', '\\'], "_")
+}
+
+// =================================================================
+// Record Encoding
+// =================================================================
+
+fn encode_record(snapshot: &L2Snapshot) -> DataResult<Vec<u8>> {
+    let json = serde_json::to_vec(snapshot)?;
+    let mut encoder = DeflateEncoder::new(Vec::with_capacity(json.len() / 4), Compression::default());
+    encoder.write_all(&json)?;
+    let payload = encoder.finish()?;
+
+    let len = u32::try_from(payload.len())
+        .map_err(|_| DataError::Validation("Snapshot record exceeds 4 GiB".into()))?;
+    let mut record = Vec::with_capacity(RECORD_HEADER_LEN + payload.len());
+    record.extend_from_slice(&snapshot.time.to_le_bytes());
+    record.extend_from_slice(&len.to_le_bytes());
+    record.extend_from_slice(&payload);
+    Ok(record)
+}
+
+fn decode_payload(payload: &[u8]) -> DataResult<L2Snapshot> {
+    let mut json = Vec::new();
+    DeflateDecoder::new(payload).read_to_end(&mut json)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Split a segment into (time, compressed payload) records.
+/// Returns the records and the length of the valid prefix.
+fn split_records(bytes: &[u8]) -> (Vec<(u64, &[u8])>, usize) {
+    let mut records = Vec::new();
+    let mut offset = 0;
+
+    while bytes.len() - offset >= RECORD_HEADER_LEN {
+        let header = &bytes[offset..offset + RECORD_HEADER_LEN];
+        let time = u64::from_le_bytes(header[..8].try_into().unwrap());
+        let len = u32::from_le_bytes(header[8..].try_into().unwrap()) as usize;
+        let end = offset + RECORD_HEADER_LEN + len;
+        if end > bytes.len() {
+            break;
+        }
+        records.push((time, &bytes[offset + RECORD_HEADER_LEN..end]));
+        offset = end;
+    }
+
+    (records, offset)
+}
+
+// =================================================================
+// Snapshot Store (Writer + Index)
+// =================================================================
+
+struct OpenSegment {
+    start_ms: u64,
+    writer: BufWriter<File>,
+}
+
+/// Append-only on-disk store for raw L2 snapshots
+pub struct SnapshotStore {
+    root: PathBuf,
+    open: HashMap<String, OpenSegment>,
+}
+
+impl SnapshotStore {
+    pub fn new(root: impl Into<PathBuf>) -> DataResult<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        Ok(Self {
+            root,
+            open: HashMap::new(),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Append a snapshot to the segment of its coin and hour
+    pub fn append(&mut self, snapshot: &L2Snapshot) -> DataResult<()> {
+        let start_ms = snapshot.time - snapshot.time % SEGMENT_MS;
+        let record = encode_record(snapshot)?;
+
+        let needs_open = self
+            .open
+            .get(&snapshot.coin)
+            .map(|segment| segment.start_ms != start_ms)
+            .unwrap_or(true);
+
+        if needs_open {
+            if let Some(mut old) = self.open.remove(&snapshot.coin) {
+                old.writer.flush()?;
+            }
+            let writer = self.open_segment(&snapshot.coin, start_ms)?;
+            self.open
+                .insert(snapshot.coin.clone(), OpenSegment { start_ms, writer });
+        }
+
+        let segment = self.open.get_mut(&snapshot.coin).expect("segment opened above");
+        segment.writer.write_all(&record)?;
+        Ok(())
+    }
+
+    /// Flush buffered records of all open segments
+    pub fn flush(&mut self) -> DataResult<()> {
+        for segment in self.open.values_mut() {
+            segment.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn open_segment(&self, symbol: &str, start_ms: u64) -> DataResult<BufWriter<File>> {
+        let dir = self.root.join(symbol_dir(symbol));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(segment_name(start_ms));
+
+        // Cut off a torn record left by a crash, otherwise new records would be unreadable
+        if path.exists() {
+            let bytes = fs::read(&path)?;
+            let (_, valid_len) = split_records(&bytes);
+            if valid_len < bytes.len() {
+                warn!(
+                    "Truncating torn tail of {} ({} bytes)",
+                    path.display(),
+                    bytes.len() - valid_len
+                );
+                OpenOptions::new()
+                    .write(true)
+                    .open(&path)?
+                    .set_len(valid_len as u64)?;
+            }
+        }
+
+        debug!("Opening snapshot segment {}", path.display());
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(BufWriter::new(file))
+    }
+
+    /// Symbols (directory names) with at least one segment
+    pub fn symbols(&self) -> DataResult<Vec<String>> {
+        let mut symbols = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                symbols.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        symbols.sort();
+        Ok(symbols)
+    }
+
+    /// Time index: segments of a symbol overlapping [start_ms, end_ms], oldest first
+    pub fn segments(&self, symbol: &str, start_ms: u64, end_ms: u64) -> DataResult<Vec<SegmentInfo>> {
+        let dir = self.root.join(symbol_dir(symbol));
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut segments = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if let Some(segment_start) = parse_segment_name(&name.to_string_lossy()) {
+                let segment = SegmentInfo {
+                    symbol: symbol.to_string(),
+                    start_ms: segment_start,
+                    path: entry.path(),
+                };
+                if segment.overlaps(start_ms, end_ms) {
+                    segments.push(segment);
+                }
+            }
+        }
+        segments.sort_by_key(|s| s.start_ms);
+        Ok(segments)
+    }
+
+    /// Reader over the given symbols (all if empty) in [start_ms, end_ms]
+    pub fn reader(&self, symbols: &[String], start_ms: u64, end_ms: u64) -> DataResult<SnapshotReader> {
+        let symbols = if symbols.is_empty() {
+            self.symbols()?
+        } else {
+            symbols.to_vec()
+        };
+
+        let mut cursors = Vec::with_capacity(symbols.len());
+        for symbol in &symbols {
+            let segments = self.segments(symbol, start_ms, end_ms)?;
+            if !segments.is_empty() {
+                cursors.push(SymbolCursor {
+                    segments: segments.into_iter().map(|s| s.path).collect(),
+                    records: VecDeque::new(),
+                });
+            }
+        }
+
+        SnapshotReader::new(cursors, start_ms, end_ms)
+    }
+}
+
+impl Drop for SnapshotStore {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("Failed to flush snapshot store: {}", e);
+        }
+    }
+}
+
+// =================================================================
+// Snapshot Reader (k-way merge across symbols)
+// =================================================================
+
+struct SymbolCursor {
+    segments: VecDeque<PathBuf>,
+    /// Decoded-on-demand records of the current segment, sorted by time
+    records: VecDeque<(u64, Vec<u8>)>,
+}
+
+impl SymbolCursor {
+    /// Time of the next record in range, loading segments as needed
+    fn peek_time(&mut self, start_ms: u64, end_ms: u64) -> DataResult<Option<u64>> {
+        loop {
+            while let Some(&(time, _)) = self.records.front() {
+                if time < start_ms {
+                    self.records.pop_front();
+                } else if time > end_ms {
+                    self.records.clear();
+                    self.segments.clear();
+                    return Ok(None);
+                } else {
+                    return Ok(Some(time));
+                }
+            }
+
+            let Some(path) = self.segments.pop_front() else {
+                return Ok(None);
+            };
+            let bytes = fs::read(&path)?;
+            let (records, valid_len) = split_records(&bytes);
+            if valid_len < bytes.len() {
+                warn!("Skipping torn tail of {}", path.display());
+            }
+
+            let mut records: Vec<(u64, Vec<u8>)> = records
+                .into_iter()
+                .map(|(time, payload)| (time, payload.to_vec()))
+                .collect();
+            // Appends are in arrival order; exchange timestamps may jitter slightly
+            records.sort_by_key(|(time, _)| *time);
+            self.records = records.into();
+        }
+    }
+}
+
+/// Yields stored snapshots of several symbols in global timestamp order
+pub struct SnapshotReader {
+    cursors: Vec<SymbolCursor>,
+    heap: BinaryHeap<Reverse<(u64, usize)>>,
+    start_ms: u64,
+    end_ms: u64,
+}
+
+impl SnapshotReader {
+    fn new(mut cursors: Vec<SymbolCursor>, start_ms: u64, end_ms: u64) -> DataResult<Self> {
+        let mut heap = BinaryHeap::new();
+        for (idx, cursor) in cursors.iter_mut().enumerate() {
+            if let Some(time) = cursor.peek_time(start_ms, end_ms)? {
+                heap.push(Reverse((time, idx)));
+            }
+        }
+        Ok(Self {
+            cursors,
+            heap,
+            start_ms,
+            end_ms,
+        })
+    }
+}
+
+impl Iterator for SnapshotReader {
+    type Item = DataResult<L2Snapshot>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((_, idx)) = self.heap.pop()?;
+        let cursor = &mut self.cursors[idx];
+        let (_, payload) = cursor.records.pop_front()?;
+
+        match cursor.peek_time(self.start_ms, self.end_ms) {
+            Ok(Some(time)) => self.heap.push(Reverse((time, idx))),
+            Ok(None) => {}
+            Err(e) => return Some(Err(e)),
+        }
+
+        Some(decode_payload(&payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::types::{L2Levels, Level};
+
+    fn snapshot(coin: &str, time: u64) -> L2Snapshot {
+        L2Snapshot {
+            coin: coin.to_string(),
+            time,
+            levels: L2Levels {
+                bids: vec![Level { px: "100.5".into(), sz: "1.25".into() }],
+                asks: vec![Level { px: "100.6".into(), sz: "0.75".into() }],
+            },
+        }
+    }
+
+    #[test]
+    fn test_segment_names_round_trip() {
+        let start = 1_700_000_000_000 - 1_700_000_000_000 % SEGMENT_MS;
+        assert_eq!(parse_segment_name(&segment_name(start)), Some(start));
+        assert_eq!(parse_segment_name("notes.txt"), None);
+    }
+
+    #[test]
+    fn test_reader_merges_symbols_in_time_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = 1_700_000_000_000u64;
+        let mut store = SnapshotStore::new(dir.path()).unwrap();
+
+        let written = vec![
+            snapshot("BTC", base),
+            snapshot("ETH", base + 50),
+            snapshot("BTC", base + 100),
+            // Next hour -> second BTC segment
+            snapshot("BTC", base + SEGMENT_MS),
+            snapshot("PURR/USDC", base + 75),
+        ];
+        for s in &written {
+            store.append(s).unwrap();
+        }
+        store.flush().unwrap();
+
+        assert_eq!(store.segments("BTC", 0, u64::MAX).unwrap().len(), 2);
+
+        let read: Vec<L2Snapshot> = store
+            .reader(&[], 0, u64::MAX)
+            .unwrap()
+            .collect::<DataResult<_>>()
+            .unwrap();
+        let times: Vec<u64> = read.iter().map(|s| s.time).collect();
+        assert_eq!(times, vec![base, base + 50, base + 75, base + 100, base + SEGMENT_MS]);
+        assert_eq!(read[0], written[0]);
+        assert_eq!(read[2].coin, "PURR/USDC");
+
+        let btc_window: Vec<u64> = store
+            .reader(&["BTC".to_string()], base + 1, base + 100)
+            .unwrap()
+            .map(|s| s.unwrap().time)
+            .collect();
+        assert_eq!(btc_window, vec![base + 100]);
+    }
+
+    #[test]
+    fn test_torn_tail_is_repaired_on_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = 1_700_000_000_000u64;
+        {
+            let mut store = SnapshotStore::new(dir.path()).unwrap();
+            store.append(&snapshot("SOL", base)).unwrap();
+        }
+
+        // Simulate a crash in the middle of the next record
+        let segment = &SnapshotStore::new(dir.path()).unwrap().segments("SOL", 0, u64::MAX).unwrap()[0];
+        let mut file = OpenOptions::new().append(true).open(&segment.path).unwrap();
+        file.write_all(&[1, 2, 3, 4, 5]).unwrap();
+        drop(file);
+
+        let mut store = SnapshotStore::new(dir.path()).unwrap();
+        store.append(&snapshot("SOL", base + 1)).unwrap();
+        store.flush().unwrap();
+
+        let times: Vec<u64> = store
+            .reader(&[], 0, u64::MAX)
+            .unwrap()
+            .map(|s| s.unwrap().time)
+            .collect();
+        assert_eq!(times, vec![base, base + 1]);
+    }
+}
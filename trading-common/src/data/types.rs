@@ -1,6 +1,6 @@
 use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
 use rust_decimal::Decimal;
-use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -146,6 +146,9 @@ pub enum DataError {
 
     #[error("Configuration error: {0}")]
     Config(String),
+
+    #[error("Storage error: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 pub type DataResult<T> = Result<T, DataError>;
@@ -409,6 +412,33 @@ impl OHLCData {
     }
 }
 
+// =================================================================
+// Order Book Types (Hyperliquid l2Book wire format)
+// =================================================================
+
+/// Full L2 order book snapshot of one coin
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct L2Snapshot {
+    pub coin: String,
+    /// Exchange timestamp (Unix millis)
+    pub time: u64,
+    pub levels: L2Levels,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct L2Levels {
+    pub bids: Vec<Level>,
+    pub asks: Vec<Level>,
+}
+
+/// Price level, kept as the exchange strings so snapshots round-trip exactly
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Level {
+    pub px: String,
+    pub sz: String,
+}
+
 // =================================================================
 // Thermodynamic Data Types
 // =================================================================
@@ -431,6 +461,44 @@ pub struct MarketState {
 }
 
 impl MarketState {
+    /// Builds the Decimal storage form from f64 fast-path values.
+    /// Non-finite values (NaN, inf) are stored as zero.
+    pub fn from_f64(
+        symbol: impl Into<String>,
+        temperature: f64,
+        pressure: f64,
+        volume_spread: f64,
+        entropy_level: Option<f64>,
+        timestamp: i64,
+    ) -> Self {
+        let dec = |v: f64| Decimal::from_f64(v).unwrap_or(Decimal::ZERO);
+        Self {
+            symbol: symbol.into(),
+            temperature: dec(temperature),
+            pressure: dec(pressure),
+            volume_spread: dec(volume_spread),
+            entropy_level: entropy_level.map(dec),
+            timestamp,
+            regime: None,
+        }
+    }
+
+    pub fn temperature_f64(&self) -> f64 {
+        self.temperature.to_f64().unwrap_or(0.0)
+    }
+
+    pub fn pressure_f64(&self) -> f64 {
+        self.pressure.to_f64().unwrap_or(0.0)
+    }
+
+    pub fn volume_spread_f64(&self) -> f64 {
+        self.volume_spread.to_f64().unwrap_or(0.0)
+    }
+
+    pub fn entropy_f64(&self) -> Option<f64> {
+        self.entropy_level.and_then(|e| e.to_f64())
+    }
+
     /// Berechnet die potenzielle Energie der "gespannten Feder"
     pub fn spring_tension(&self) -> Decimal {
         if self.volume_spread == Decimal::ZERO { 
@@ -476,3 +544,19 @@ impl MarketState {
     }
 }
 
+/// Aggregated market states of one symbol over a fixed time bucket
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MarketStateAggregate {
+    pub symbol: String,
+    /// Bucket start (Unix millis, aligned to bucket_ms)
+    pub bucket_start: i64,
+    pub bucket_ms: i64,
+    pub sample_count: u64,
+    pub mean_temperature: f64,
+    pub mean_pressure: f64,
+    pub mean_volume_spread: f64,
+    /// None if no state in the bucket carried an entropy level
+    pub mean_entropy: Option<f64>,
+    /// Highest spring tension in the bucket, None if every spread was zero
+    pub max_tension: Option<f64>,
+}
@@ -0,0 +1,76 @@
+// E:\MBCT\trading-core\src\bin\account_tool.rs
+// THE ALLIANCE - Account-Werkzeug (Agent-Freigabe & Transfers ohne Web-UI)
+//
+// Nutzung (HL_PRIVATE_KEY = Master-Key, IS_TESTNET=true|false):
+//   account_tool approve-agent <agent_address> [name]
+//   account_tool to-perp <amount>
+//   account_tool to-spot <amount>
+//   account_tool usd-send <destination> <amount>
+//   account_tool withdraw <destination> <amount>
+
+use anyhow::{anyhow, Result};
+use dotenvy::dotenv;
+use rust_decimal::Decimal;
+use std::env;
+use trading_core::exchange::connector::HyperliquidConnector;
+
+fn arg(args: &[String], idx: usize, name: &str) -> Result<String> {
+    args.get(idx)
+        .cloned()
+        .ok_or_else(|| anyhow!("Argument <{}> fehlt", name))
+}
+
+fn amount(args: &[String], idx: usize) -> Result<Decimal> {
+    arg(args, idx, "amount")?
+        .parse::<Decimal>()
+        .map_err(|e| anyhow!("Ungültiger Betrag: {}", e))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = arg(&args, 0, "command")?;
+
+    let is_testnet = env::var("IS_TESTNET").unwrap_or("true".to_string()) == "true";
+    let pk = env::var("HL_PRIVATE_KEY").map_err(|_| anyhow!("HL_PRIVATE_KEY missing"))?;
+    let connector = HyperliquidConnector::new(&pk, is_testnet)?;
+
+    println!(
+        "🛰️ Account {} ({})",
+        connector.address(),
+        if is_testnet { "Testnet" } else { "Mainnet" }
+    );
+
+    let response = match command.as_str() {
+        "approve-agent" => {
+            let agent = arg(&args, 1, "agent_address")?;
+            connector
+                .approve_agent(&agent, args.get(2).map(String::as_str))
+                .await?
+        }
+        "to-perp" => {
+            connector
+                .usd_class_transfer(amount(&args, 1)?, true)
+                .await?
+        }
+        "to-spot" => {
+            connector
+                .usd_class_transfer(amount(&args, 1)?, false)
+                .await?
+        }
+        "usd-send" => {
+            let destination = arg(&args, 1, "destination")?;
+            connector.usd_send(&destination, amount(&args, 2)?).await?
+        }
+        "withdraw" => {
+            let destination = arg(&args, 1, "destination")?;
+            connector.withdraw(&destination, amount(&args, 2)?).await?
+        }
+        other => return Err(anyhow!("Unbekannter Befehl: {}", other)),
+    };
+
+    println!("✅ Antwort: {}", response);
+    Ok(())
+}
@@ -21,6 +21,8 @@ use trading_common::data::repository::Repository;
 use trading_common::data::types::MarketState;
 use trading_core::exchange::envelope_detection::EnvelopeDetector;
 use trading_core::exchange::market_data::HyperliquidMarketData;
+use trading_core::exchange::orderbook::OrderBook;
+use trading_core::physics::FeatureSet;
 use trading_core::exchange::types::L2Snapshot;
 use trading_core::exchange::ws::HyperliquidWs;
 
@@ -46,23 +48,18 @@ static CSV_WRITES_COUNT: AtomicUsize = AtomicUsize::new(0);
 // ============================================================================
 
 fn extract_mid_price_from_snapshot(snapshot: &L2Snapshot) -> Option<f64> {
-    // Hyperliquid L2: levels[0] sind Bids, levels[1] sind Asks
-    if snapshot.levels.len() < 2 {
-        return None;
-    }
-
     let mut best_bid = f64::MIN;
     let mut best_ask = f64::MAX;
 
     // Bids verarbeiten
-    for level in &snapshot.levels[0] {
+    for level in &snapshot.levels.bids {
         if let Ok(price) = level.px.parse::<f64>() {
             best_bid = best_bid.max(price);
         }
     }
 
     // Asks verarbeiten
-    for level in &snapshot.levels[1] {
+    for level in &snapshot.levels.asks {
         if let Ok(price) = level.px.parse::<f64>() {
             best_ask = best_ask.min(price);
         }
@@ -77,31 +74,25 @@ fn extract_mid_price_from_snapshot(snapshot: &L2Snapshot) -> Option<f64> {
 
 fn extract_total_volume_from_snapshot(snapshot: &L2Snapshot) -> f64 {
     let mut total = 0.0;
-    for level_vec in &snapshot.levels {
-        for level in level_vec {
-            if let Ok(volume) = level.sz.parse::<f64>() {
-                total += volume;
-            }
+    for level in snapshot.levels.bids.iter().chain(&snapshot.levels.asks) {
+        if let Ok(volume) = level.sz.parse::<f64>() {
+            total += volume;
         }
     }
     total
 }
 
 fn extract_spread_from_snapshot(snapshot: &L2Snapshot) -> Option<f64> {
-    if snapshot.levels.len() < 2 {
-        return None;
-    }
-
     let mut best_bid = f64::MIN;
     let mut best_ask = f64::MAX;
 
-    for level in &snapshot.levels[0] {
+    for level in &snapshot.levels.bids {
         if let Ok(price) = level.px.parse::<f64>() {
             best_bid = best_bid.max(price);
         }
     }
 
-    for level in &snapshot.levels[1] {
+    for level in &snapshot.levels.asks {
         if let Ok(price) = level.px.parse::<f64>() {
             best_ask = best_ask.min(price);
         }
@@ -118,16 +109,14 @@ fn extract_bid_ask_volumes(snapshot: &L2Snapshot) -> (f64, f64) {
     let mut bid_volume = 0.0;
     let mut ask_volume = 0.0;
 
-    if snapshot.levels.len() >= 2 {
-        for level in &snapshot.levels[0] {
-            if let Ok(volume) = level.sz.parse::<f64>() {
-                bid_volume += volume;
-            }
+    for level in &snapshot.levels.bids {
+        if let Ok(volume) = level.sz.parse::<f64>() {
+            bid_volume += volume;
         }
-        for level in &snapshot.levels[1] {
-            if let Ok(volume) = level.sz.parse::<f64>() {
-                ask_volume += volume;
-            }
+    }
+    for level in &snapshot.levels.asks {
+        if let Ok(volume) = level.sz.parse::<f64>() {
+            ask_volume += volume;
         }
     }
 
@@ -698,9 +687,9 @@ async fn main() -> anyhow::Result<()> {
         .map_err(|e| anyhow::anyhow!("Database connection failed: {}", e))?;
 
     let repo = Arc::new(Repository::from_pool(pool));
-    repo.ensure_market_states_table()
+    repo.migrate()
         .await
-        .map_err(|e| anyhow::anyhow!("Failed to ensure tables: {}", e))?;
+        .map_err(|e| anyhow::anyhow!("Failed to migrate schema: {}", e))?;
 
     let physicist = Arc::new(ThermodynamicPhysicist::new().await?);
     let detector = EnvelopeDetector::new(20);
@@ -746,7 +735,11 @@ async fn main() -> anyhow::Result<()> {
         .await
         .map_err(|e| anyhow::anyhow!("WebSocket connection failed: {}", e))?;
 
-    let market_data = HyperliquidMarketData::new();
+    // Feature-Definitionen aus FEATURE_SET, sonst die bisherige Markttiefe (market_data_v1)
+    let features = FeatureSet::from_env_or(*HyperliquidMarketData::new().features())
+        .map_err(|e| anyhow::anyhow!(e))?;
+    println!("🧪 FeatureSet: {}", features);
+    let market_data = HyperliquidMarketData::with_features(features);
 
     println!("📡 Subscribing to symbols...");
     for symbol in &symbols {
@@ -759,6 +752,7 @@ async fn main() -> anyhow::Result<()> {
     println!("✅ Subscriptions complete");
 
     let history_map: Arc<DashMap<String, Vec<MarketState>>> = Arc::new(DashMap::new());
+    let mut books: HashMap<String, OrderBook> = HashMap::new();
 
     println!("{}", "=".repeat(80));
     println!("🔄 Starting live validation with CSV writing...");
@@ -777,7 +771,20 @@ async fn main() -> anyhow::Result<()> {
                     Some(l2_snapshot) => {
                         consecutive_errors = 0;
 
-                        let state = market_data.derive_market_state(&l2_snapshot);
+                        // Only books that pass the integrity checks reach the physics
+                        let applied = match books.get_mut(&l2_snapshot.coin) {
+                            Some(book) => book.apply(&l2_snapshot),
+                            None => OrderBook::from_snapshot(&l2_snapshot).map(|book| {
+                                books.insert(l2_snapshot.coin.clone(), book);
+                            }),
+                        };
+                        if let Err(e) = applied {
+                            ERROR_COUNT.fetch_add(1, Ordering::Relaxed);
+                            eprintln!("⚠️  Book rejected for {}: {}", l2_snapshot.coin, e);
+                            continue;
+                        }
+
+                        let state = market_data.derive_market_state(&books[&l2_snapshot.coin]);
                         let symbol = state.symbol.clone();
 
                         let mut history = history_map.entry(symbol.clone()).or_insert_with(Vec::new);
@@ -840,7 +847,7 @@ async fn main() -> anyhow::Result<()> {
                             eprintln!("⚠️  Multiple connection errors, attempting reconnect...");
                             time::sleep(Duration::from_secs(5)).await;
 
-                            match HyperliquidWs::new().await {
+                            match HyperliquidWs::new(false).await {
                                 Ok(new_ws) => {
                                     ws = new_ws;
                                     for symbol in &symbols {
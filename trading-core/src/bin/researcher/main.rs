@@ -1,18 +1,17 @@
 // E:\MBCT\trading-core\src\bin\researcher\main.rs
-mod modules;
-
-use modules::archive::Archive;
-use modules::chronos::Chronos;
-use modules::collector::Collector;
-use modules::physicist::{Physicist, PhysicsState};
-use modules::regime::RegimeClassifier;
-
 use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::signal;
 use tokio::sync::{mpsc, watch, Mutex};
+use trading_common::data::snapshot_store::SnapshotStore;
+use trading_core::clock::{SharedClock, SimulatedClock, WallClock};
+use trading_core::exchange::OrderBook;
+use trading_core::physics::{
+    Archive, Chronos, Collector, FeatureSet, Physicist, PhysicsField, RegimeTracker,
+};
+use trading_core::replay::{ReplayProvider, ReplaySpeed};
 
 fn clear_screen() {
     print!("{}[2J{}[1;1H", 27 as char, 27 as char);
@@ -36,18 +35,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     let sens_map = Arc::new(sens_map_internal);
-    let collector = Arc::new(Collector::new());
+    let collector = Arc::new(
+        Collector::new(false).with_recorder(SnapshotStore::new("e:/mbct/data/snapshots")?),
+    );
+    // Feature-Definitionen aus FEATURE_SET, sonst die Researcher-v2-Formeln (pep_v1);
+    // das Archiv speichert den Fingerprint je Record
+    let features = FeatureSet::from_env_or(FeatureSet::preset("pep_v1").expect("pep_v1 preset"))?;
     let archive = Arc::new(
         Archive::new(
             "sqlite:e:/mbct/data/researcher_v2.db",
             "e:/mbct/data/researcher_v2.csv",
         )
-        .await?,
+        .await?
+        .with_feature_set(&features),
     );
-    let chronos = Arc::new(Mutex::new(Chronos::new()));
-    let classifier = Arc::new(RegimeClassifier::new(21));
-    let histories: Arc<Mutex<HashMap<String, VecDeque<PhysicsState>>>> =
-        Arc::new(Mutex::new(HashMap::new()));
+    // Im Replay läuft die Zeit auf der Snapshot-Uhr, sonst Wall-Clock
+    let replay_dir = std::env::var("REPLAY_DIR").ok();
+    let sim_clock = Arc::new(SimulatedClock::default());
+    let clock: SharedClock = match replay_dir {
+        Some(_) => sim_clock.clone(),
+        None => WallClock::shared(),
+    };
+    let chronos = Arc::new(Mutex::new(Chronos::with_clock(clock.clone())));
+    // Rollierende Statistik je Symbol: 89 Samples Historie, Regime ab 21 Samples
+    let trackers: Arc<Mutex<HashMap<String, RegimeTracker>>> = Arc::new(Mutex::new(HashMap::new()));
     let ui_events: Arc<Mutex<VecDeque<String>>> = Arc::new(Mutex::new(VecDeque::with_capacity(10)));
 
     let (shutdown_tx, _shutdown_rx) = watch::channel(false);
@@ -94,100 +105,115 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
-    let collector_clone = collector.clone();
-    let symbols_clone = symbols.clone();
-    tokio::spawn(async move {
-        collector_clone.stream_provider(symbols_clone).await;
-    });
+    // Replay-Modus: aufgezeichnete Snapshots statt Websocket
+    let replay = match replay_dir {
+        Some(dir) => {
+            let speed: ReplaySpeed = std::env::var("REPLAY_SPEED")
+                .unwrap_or("max".to_string())
+                .parse()?;
+            let store = SnapshotStore::new(dir)?;
+            Some(
+                ReplayProvider::from_store(&store, &symbols, 0, u64::MAX, speed)?
+                    .with_clock(sim_clock.clone()),
+            )
+        }
+        None => {
+            let collector_clone = collector.clone();
+            let symbols_clone = symbols.clone();
+            tokio::spawn(async move {
+                collector_clone.stream_provider(symbols_clone).await;
+            });
+            None
+        }
+    };
 
     let tx_channel = tx.clone();
-    let histories_lock = histories.clone();
+    let trackers_lock = trackers.clone();
     let chronos_lock = chronos.clone();
-    let classifier_arc = classifier.clone();
     let sens_ref = sens_map.clone();
     let ui_event_log = ui_events.clone();
     let heart_shutdown = shutdown_tx.subscribe();
+    let heart_clock = clock.clone();
 
     let heartbeat_handle = tokio::spawn(async move {
-        collector
-            .heartbeat_loop(move |symbol, snapshot| {
-                if *heart_shutdown.borrow() {
-                    return;
-                }
+        let on_sample = move |book: OrderBook| {
+            if *heart_shutdown.borrow() {
+                return;
+            }
 
-                let s_name = symbol.clone();
-                let current_physics = Physicist::process_snapshot(&snapshot);
-                let s_config = sens_ref.get(&s_name).cloned();
-                let h_lock = histories_lock.clone();
-                let c_lock = chronos_lock.clone();
-                let tx_chan = tx_channel.clone();
-                let classifier_ref = classifier_arc.clone();
-                let ui_log_trigger = ui_event_log.clone();
-
-                tokio::spawn(async move {
-                    let mut hist = h_lock.lock().await;
-                    let entry = hist
-                        .entry(s_name.clone())
-                        .or_insert_with(|| VecDeque::with_capacity(100));
-                    entry.push_back(current_physics.clone());
-                    if entry.len() > 89 {
-                        entry.pop_front();
+            let s_name = book.coin.clone();
+            let current_physics =
+                Physicist::process_book_with(&book, heart_clock.as_ref(), &features);
+            let s_config = sens_ref.get(&s_name).cloned();
+            let t_lock = trackers_lock.clone();
+            let c_lock = chronos_lock.clone();
+            let tx_chan = tx_channel.clone();
+            let ui_log_trigger = ui_event_log.clone();
+
+            tokio::spawn(async move {
+                let mut trackers = t_lock.lock().await;
+                let tracker = trackers
+                    .entry(s_name.clone())
+                    .or_insert_with(|| RegimeTracker::new(89, 21));
+                tracker.push(&current_physics);
+
+                let regime_state = tracker.regime();
+                let z_scores = (
+                    tracker.z_score(PhysicsField::Entropy, current_physics.entropy),
+                    tracker.z_score(PhysicsField::Pressure, current_physics.pressure),
+                    tracker.z_score(PhysicsField::Nrg, current_physics.nrg),
+                );
+
+                if let Some(cfg) = s_config {
+                    let l_floor = cfg["sens_long_trigger"].as_f64().unwrap_or(0.40);
+                    let s_ceiling = cfg["sens_short_trigger"].as_f64().unwrap_or(0.60);
+
+                    let mut c_guard = c_lock.lock().await;
+                    if c_guard.observe_potential_hit(
+                        &s_name,
+                        &current_physics,
+                        &regime_state,
+                        l_floor,
+                        s_ceiling,
+                    ) {
+                        let log_line = format!(
+                            "{:<11} | {:<9.3} | {:<14.4} | {:<12?} | LOCKED ✅",
+                            s_name,
+                            regime_state.symmetry_score,
+                            current_physics.price,
+                            regime_state.regime
+                        );
+                        let mut ui_guard = ui_log_trigger.lock().await;
+                        ui_guard.push_front(log_line);
+                        if ui_guard.len() > 10 {
+                            ui_guard.pop_back();
+                        }
                     }
 
-                    let regime_state = classifier_ref.classify(entry);
-                    let z_scores = (
-                        RegimeClassifier::calculate_z_score(
-                            current_physics.entropy,
-                            entry,
-                            "entropy",
-                        ),
-                        RegimeClassifier::calculate_z_score(
-                            current_physics.pressure,
-                            entry,
-                            "pressure",
-                        ),
-                        RegimeClassifier::calculate_z_score(current_physics.nrg, entry, "nrg"),
+                    let completed_records = c_guard.update_and_flush(
+                        &s_name,
+                        current_physics.price,
+                        z_scores,
+                        z_scores,
                     );
+                    if !completed_records.is_empty() {
+                        let _ = tx_chan.send(completed_records).await;
+                    }
+                }
+            });
+        };
 
-                    if let Some(cfg) = s_config {
-                        let l_floor = cfg["sens_long_trigger"].as_f64().unwrap_or(0.40);
-                        let s_ceiling = cfg["sens_short_trigger"].as_f64().unwrap_or(0.60);
-
-                        let mut c_guard = c_lock.lock().await;
-                        if c_guard.observe_potential_hit(
-                            &s_name,
-                            &current_physics,
-                            &regime_state,
-                            l_floor,
-                            s_ceiling,
-                        ) {
-                            let log_line = format!(
-                                "{:<11} | {:<9.3} | {:<14.4} | {:<12?} | LOCKED ✅",
-                                s_name,
-                                regime_state.symmetry_score,
-                                current_physics.price,
-                                regime_state.regime
-                            );
-                            let mut ui_guard = ui_log_trigger.lock().await;
-                            ui_guard.push_front(log_line);
-                            if ui_guard.len() > 10 {
-                                ui_guard.pop_back();
-                            }
-                        }
+        let on_tick = move |updates: Vec<OrderBook>| {
+            for book in updates {
+                on_sample(book);
+            }
+            std::future::ready(())
+        };
 
-                        let completed_records = c_guard.update_and_flush(
-                            &s_name,
-                            current_physics.price,
-                            z_scores,
-                            z_scores,
-                        );
-                        if !completed_records.is_empty() {
-                            let _ = tx_chan.send(completed_records).await;
-                        }
-                    }
-                });
-            })
-            .await;
+        match replay {
+            Some(replay) => collector.replay_loop(replay, on_tick).await,
+            None => collector.heartbeat_loop(on_tick).await,
+        }
     });
 
     signal::ctrl_c().await?;
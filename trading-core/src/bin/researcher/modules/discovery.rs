@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tokio::time::{self, Duration};
 use reqwest::Client;
+use trading_core::exchange::rate_limit::{info_weight, Priority, RateLimiter};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -44,6 +45,12 @@ impl Discovery {
         loop {
             interval.tick().await;
 
+            // Niedrige Priorität: bei knappem IP-Budget fällt dieser Durchlauf aus
+            if let Err(e) = RateLimiter::shared().acquire(info_weight("metaAndAssetCtxs"), Priority::Low).await {
+                eprintln!("[DISCOVERY] Übersprungen: {}", e);
+                continue;
+            }
+
             let request_body = InfoRequest::MetaAndAssetCtxs;
             let response = self.client
                 .post(&self.api_url)
@@ -1,5 +0,0 @@
-pub mod archive;
-pub mod chronos;
-pub mod collector;
-pub mod physicist;
-pub mod regime;
@@ -3,6 +3,8 @@ use sqlx::{Pool, Sqlite, Row};
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use chrono::Utc;
+use trading_common::data::migrations;
+use trading_common::data::types::DataError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradingParams {
@@ -23,18 +25,9 @@ impl ParamManager {
         Self { pool }
     }
 
-    /// Erstellt die Steuerungstabelle mit erweiterten Metriken für die Allianz
-    pub async fn initialize_table(&self) -> Result<(), sqlx::Error> {
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS active_trading_params (
-                symbol TEXT PRIMARY KEY,
-                l_floor REAL DEFAULT 0.35,
-                s_ceiling REAL DEFAULT 0.65,
-                is_active INTEGER DEFAULT 1,
-                sample_count INTEGER DEFAULT 0,
-                last_updated INTEGER
-            )"
-        ).execute(&self.pool).await?;
+    /// Stellt die Steuerungstabelle über die versionierten Migrationen sicher
+    pub async fn initialize_table(&self) -> Result<(), DataError> {
+        migrations::run_migrations(&self.pool).await?;
         Ok(())
     }
 
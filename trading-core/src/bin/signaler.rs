@@ -4,18 +4,17 @@
 
 use chrono::Local;
 use dashmap::DashMap;
-use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
-use rust_decimal::Decimal;
 use serde::Deserialize;
 use std::fs;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 // Interne MBCT Module
-use trading_common::data::types::MarketState;
 use trading_core::exchange::envelope_detection::{EnvelopeDetector, MarketRegime};
-use trading_core::exchange::types::L2Snapshot;
+use trading_core::exchange::orderbook::OrderBook;
+use trading_core::exchange::types::{L2Snapshot, MarketState};
 use trading_core::exchange::ws::HyperliquidWs;
+use trading_core::physics::FeatureSet;
 
 // ============================================================================
 // KONFIGURATION
@@ -40,38 +39,55 @@ struct HLMeta {
 // ============================================================================
 struct SignalerPhysicist {
     history: DashMap<String, Vec<MarketState>>,
+    /// Geprüftes Orderbuch je Symbol
+    books: DashMap<String, OrderBook>,
     detector: EnvelopeDetector,
+    features: FeatureSet,
 }
 
 impl SignalerPhysicist {
-    fn new() -> Self {
+    fn new(features: FeatureSet) -> Self {
         Self {
             history: DashMap::new(),
+            books: DashMap::new(),
             detector: EnvelopeDetector::new(HISTORY_SIZE),
+            features,
         }
     }
 
     fn process_snapshot(&self, snapshot: &L2Snapshot) -> Option<(String, MarketRegime, f64)> {
         let symbol = snapshot.coin.clone();
 
-        let (bid_vol, ask_vol) = self.extract_volumes(snapshot);
+        // Gekreuzte, veraltete oder kaputte Bücher erreichen die Physik nicht
+        let applied = match self.books.get_mut(&symbol) {
+            Some(mut book) => book.apply(snapshot),
+            None => OrderBook::from_snapshot(snapshot).map(|book| {
+                self.books.insert(symbol.clone(), book);
+            }),
+        };
+        if let Err(e) = applied {
+            eprintln!("⚠️ {} verworfen: {}", symbol, e);
+            return None;
+        }
+        let book = self.books.get(&symbol)?;
+
+        let (bid_vol, ask_vol) = self.features.volumes(&book);
         let total_vol = bid_vol + ask_vol;
         if total_vol == 0.0 {
             return None;
         }
 
-        let pressure = (bid_vol - ask_vol).abs() / total_vol;
-        let entropy = self.calculate_entropy(snapshot);
-
-        let state = MarketState {
-            timestamp: Local::now().timestamp_millis(),
-            symbol: symbol.clone(),
-            pressure: Decimal::from_f64(pressure).unwrap_or_else(|| Decimal::new(0, 0)),
-            entropy_level: Some(Decimal::from_f64(entropy).unwrap_or_else(|| Decimal::new(0, 0))),
-            regime: None,
-            temperature: Decimal::from_f64(pressure * 100.0).unwrap_or_else(|| Decimal::new(0, 0)),
-            volume_spread: Decimal::from_f64(total_vol).unwrap_or_else(|| Decimal::new(0, 0)),
-        };
+        let pressure = self.features.pressure.compute(bid_vol, ask_vol);
+        let entropy = self.features.entropy(&book);
+
+        let state = MarketState::from_f64(
+            symbol.clone(),
+            pressure * 100.0,
+            pressure,
+            total_vol,
+            Some(entropy),
+            Local::now().timestamp_millis(),
+        );
 
         let mut hist = self.history.entry(symbol.clone()).or_insert_with(Vec::new);
         hist.push(state.clone());
@@ -85,52 +101,13 @@ impl SignalerPhysicist {
         Some((symbol, regime, nrg))
     }
 
-    fn extract_volumes(&self, snapshot: &L2Snapshot) -> (f64, f64) {
-        let mut bv = 0.0;
-        let mut av = 0.0;
-        if snapshot.levels.len() >= 2 {
-            for l in &snapshot.levels[0] {
-                bv += l.sz.parse::<f64>().unwrap_or(0.0);
-            }
-            for l in &snapshot.levels[1] {
-                av += l.sz.parse::<f64>().unwrap_or(0.0);
-            }
-        }
-        (bv, av)
-    }
-
-    fn calculate_entropy(&self, snapshot: &L2Snapshot) -> f64 {
-        let mut volumes = Vec::new();
-        for side in &snapshot.levels {
-            for l in side {
-                let sz = l.sz.parse::<f64>().unwrap_or(0.0);
-                if sz > 0.0 {
-                    volumes.push(sz);
-                }
-            }
-        }
-        let total: f64 = volumes.iter().sum();
-        if total == 0.0 {
-            return 0.0;
-        }
-
-        volumes
-            .iter()
-            .map(|v| v / total)
-            .map(|p| -p * p.log2())
-            .sum()
-    }
-
     fn calculate_nrg(&self, history: &[MarketState]) -> f64 {
         if history.len() < 2 {
             return 0.0;
         }
         let last = history.last().unwrap();
         let prev = &history[history.len() - 2];
-        let dp = (last.pressure - prev.pressure)
-            .to_f64()
-            .unwrap_or(0.0)
-            .abs();
+        let dp = (last.pressure_f64() - prev.pressure_f64()).abs();
         dp * 1000.0
     }
 }
@@ -163,8 +140,11 @@ async fn main() -> anyhow::Result<()> {
         active_coins.len()
     );
 
-    let mut ws = HyperliquidWs::new().await?;
-    let physicist = Arc::new(SignalerPhysicist::new());
+    let mut ws = HyperliquidWs::new(false).await?;
+    // Feature-Definitionen aus FEATURE_SET, sonst |Imbalance| und log2-Entropie (signaler_v1)
+    let features = FeatureSet::from_env_or(FeatureSet::preset("signaler_v1").expect("signaler_v1 preset"))
+        .map_err(|e| anyhow::anyhow!(e))?;
+    let physicist = Arc::new(SignalerPhysicist::new(features));
 
     for coin in &active_coins {
         let _ = ws.subscribe_l2(coin).await;
@@ -3,30 +3,31 @@
 // Fokus: Dynamische JSON-Thresholds, Trailing-SL & Präzisions-Anzeige
 // Vollständige Datei - compilierbar und ohne Platzhalter.
 
-mod modules;
-
 use dotenvy::dotenv;
-use modules::{
-    chronos::Chronos,
-    collector::Collector,
-    physicist::{Physicist, PhysicsState},
-    regime::{RegimeClassifier, RegimeState},
-};
 use rust_decimal::prelude::*;
 use serde::Deserialize;
-use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::HashMap,
     env, fs,
     sync::Arc,
-    time::{Duration, Instant},
+    time::Duration,
 };
 use tokio::{
     sync::mpsc,
     sync::Mutex,
     time::{sleep, timeout},
 };
-use trading_core::exchange::connector::HyperliquidConnector;
+use trading_common::data::snapshot_store::SnapshotStore;
+use trading_core::clock::{elapsed_secs, SharedClock, SimulatedClock, WallClock};
+use trading_core::exchange::connector::{HyperliquidConnector, DEFAULT_SLIPPAGE};
+use trading_core::exchange::market_data::HyperliquidMarketData;
+use trading_core::exchange::mock_exchange::MockConfig;
+use trading_core::exchange::simulated::SimulatedExchange;
+use trading_core::exchange::ws::ConnectionState;
+use trading_core::exchange::{ExchangeError, MarketDataStream, OrderBook, OrderExecution};
+use trading_core::physics::{Chronos, Collector, FeatureSet, PhysicsField, Physicist, PhysicsState, RegimeState, RegimeTracker};
+use trading_core::replay::{ReplayProvider, ReplaySpeed};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 enum TradeState {
@@ -67,28 +68,34 @@ struct ShlongMachine {
     _symbol: String,
     entry_price: Option<f64>,
     is_long: bool,
-    opened_at: Option<Instant>,
-    last_action: Instant,
+    opened_at: Option<u64>,
+    last_action: u64,
     is_executing: bool,
-    executing_since: Option<Instant>,
+    executing_since: Option<u64>,
     highest_pnl: f64,
+    clock: SharedClock,
 }
 
 impl ShlongMachine {
-    fn new(symbol: String) -> Self {
+    fn new(symbol: String, clock: SharedClock) -> Self {
         Self {
             state: TradeState::Flat,
             _symbol: symbol,
             entry_price: None,
             is_long: true,
             opened_at: None,
-            last_action: Instant::now(),
+            last_action: clock.now_ms(),
             is_executing: false,
             executing_since: None,
             highest_pnl: 0.0,
+            clock,
         }
     }
 
+    fn secs_since(&self, since_ms: u64) -> u64 {
+        elapsed_secs(self.clock.as_ref(), since_ms)
+    }
+
     fn get_pnl(&self, current_price: f64) -> f64 {
         if let Some(entry) = self.entry_price {
             if entry == 0.0 { return 0.0; }
@@ -98,6 +105,25 @@ impl ShlongMachine {
         0.0
     }
 
+    /// Übernimmt das Ergebnis einer Order (live per Channel, im Replay sofort)
+    fn apply_order_result(&mut self, error: Option<&ExchangeError>, price: f64, entry: bool) {
+        self.is_executing = false;
+        if error.is_none() {
+            self.state = if entry { TradeState::InPosition } else { TradeState::Cooldown };
+            if entry {
+                self.entry_price = Some(price);
+                self.opened_at = Some(self.clock.now_ms());
+                self.highest_pnl = 0.0;
+            }
+        } else if entry && error.is_some_and(|e| e.blocks_reentry()) {
+            // Margin-/Filter-Reject: kein sofortiger Re-Entry, erst Cooldown abwarten
+            self.state = TradeState::Cooldown;
+        } else {
+            self.state = if entry { TradeState::Observing } else { TradeState::InPosition };
+        }
+        self.last_action = self.clock.now_ms();
+    }
+
     fn update(
         &mut self,
         physics: &PhysicsState,
@@ -109,7 +135,7 @@ impl ShlongMachine {
     ) {
         if self.is_executing {
             if let Some(start) = self.executing_since {
-                if start.elapsed() > Duration::from_secs(10) {
+                if self.clock.now_ms().saturating_sub(start) > 10_000 {
                     self.is_executing = false;
                     self.executing_since = None;
                 }
@@ -139,12 +165,12 @@ impl ShlongMachine {
             if pnl > 0.70 { should_exit = true; }
 
             // 5. Zeit-Limit
-            let elapsed = self.opened_at.map(|t| t.elapsed().as_secs()).unwrap_or(0);
+            let elapsed = self.opened_at.map(|t| self.secs_since(t)).unwrap_or(0);
             if elapsed > profile.max_duration_seconds { should_exit = true; }
 
             if should_exit {
                 self.state = TradeState::Exiting;
-                self.last_action = Instant::now();
+                self.last_action = self.clock.now_ms();
             }
         }
 
@@ -163,18 +189,18 @@ impl ShlongMachine {
                 if buffer_ready && active_count < 3 && nrg_valid && slope_valid && entropy_valid {
                     if chronos_hit {
                         self.state = TradeState::SetupDetected;
-                        self.last_action = Instant::now();
+                        self.last_action = self.clock.now_ms();
                     }
                 }
             }
             TradeState::SetupDetected => {
-                if self.last_action.elapsed().as_secs() > 1 { // Kurze Bestätigung
+                if self.secs_since(self.last_action) > 1 { // Kurze Bestätigung
                     self.state = TradeState::PendingEntry;
-                    self.last_action = Instant::now();
+                    self.last_action = self.clock.now_ms();
                 }
             }
             TradeState::Cooldown => {
-                if self.last_action.elapsed().as_secs() > profile.cooldown_seconds {
+                if self.secs_since(self.last_action) > profile.cooldown_seconds {
                     self.state = TradeState::Flat;
                     self.highest_pnl = 0.0;
                 }
@@ -184,17 +210,76 @@ impl ShlongMachine {
     }
 }
 
+/// Konto der simulierten Venue (EXECUTION_VENUE=sim)
+const SIM_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Entry als Market-Order; Exit schließt die offene Position (Gegenseite, reduce-only)
+async fn execute_order(venue: Arc<dyn OrderExecution>, symbol: &str, is_entry: bool, is_long: bool, size: Decimal) -> Option<ExchangeError> {
+    let res = if is_entry {
+        timeout(Duration::from_secs(6), venue.market_open(symbol, is_long, size, DEFAULT_SLIPPAGE)).await
+    } else {
+        timeout(Duration::from_secs(6), venue.market_close(symbol, None, DEFAULT_SLIPPAGE)).await
+    };
+    match res {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => Some(e.downcast_ref::<ExchangeError>().cloned().unwrap_or_else(|| ExchangeError::Rejected(e.to_string()))),
+        Err(_) => Some(ExchangeError::NetworkError("Order-Timeout".to_string())),
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
 
-    let pk = env::var("HL_PRIVATE_KEY").expect("HL_PRIVATE_KEY missing");
-    let main_addr = env::var("HL_MAIN_ADDRESS").expect("HL_MAIN_ADDRESS missing");
     let is_testnet = env::var("IS_TESTNET").unwrap_or("true".to_string()) == "true";
 
-    let conn = Arc::new(HyperliquidConnector::new(&pk, is_testnet)?);
-    let collector = Arc::new(Collector::new(is_testnet));
-    let chronos_arc = Arc::new(Mutex::new(Chronos::new()));
+    // Replay-Modus: aufgezeichnete Snapshots statt Websocket, Orders werden zum Snapshot-Preis simuliert gefüllt
+    let replay_dir = env::var("REPLAY_DIR").ok();
+
+    // Im Replay läuft die Zeit auf der Snapshot-Uhr, sonst Wall-Clock
+    let sim_clock = Arc::new(SimulatedClock::default());
+    let clock: SharedClock = match replay_dir {
+        Some(_) => sim_clock.clone(),
+        None => WallClock::shared(),
+    };
+
+    // Feature-Definitionen (Preset oder JSON) aus FEATURE_SET, sonst Trader-Default
+    let features = FeatureSet::from_env()?;
+    println!("🧪 Features: {}", features);
+
+    // Ausführung: EXECUTION_VENUE=sim füllt gegen die empfangenen Bücher (live und im Replay),
+    // sonst Hyperliquid live bzw. sofortiger Fill zum Snapshot-Preis im Replay
+    let sim_venue = match env::var("EXECUTION_VENUE").as_deref() {
+        Ok("sim") => {
+            let capital = env::var("SIM_CAPITAL").ok().and_then(|v| v.parse().ok()).unwrap_or(Decimal::from(1000));
+            println!("🧪 Simulierte Ausführung mit {} USDC", capital);
+            Some(Arc::new(SimulatedExchange::new(MockConfig::default(), SIM_ADDRESS, capital)))
+        }
+        _ => None,
+    };
+    let conn: Option<Arc<dyn OrderExecution>> = match (&sim_venue, &replay_dir) {
+        (Some(sim), _) => Some(sim.clone()),
+        (None, Some(_)) => None,
+        (None, None) => {
+            // Agent-Key signiert, gehandelt (Positionen, market_close) wird der Master-Account
+            let pk = env::var("HL_PRIVATE_KEY").expect("HL_PRIVATE_KEY missing");
+            let main_addr = env::var("HL_MAIN_ADDRESS").expect("HL_MAIN_ADDRESS missing");
+            Some(Arc::new(HyperliquidConnector::new(&pk, is_testnet)?.with_account(&main_addr)))
+        }
+    };
+    // Marktdaten (live): MARKET_DATA_VENUE wählt die Quelle, unabhängig von der Ausführung
+    let market_data: Arc<dyn MarketDataStream> = match env::var("MARKET_DATA_VENUE").as_deref() {
+        Ok("hyperliquid") | Err(_) => Arc::new(HyperliquidMarketData::new().with_testnet(is_testnet)),
+        Ok(other) => return Err(format!("Unbekannte MARKET_DATA_VENUE: {}", other).into()),
+    };
+    let replay_mode = replay_dir.is_some();
+    let mut collector = Collector::new(is_testnet);
+    if let Ok(dir) = env::var("SNAPSHOT_DIR") {
+        println!("💾 Roh-Snapshots werden nach {} geschrieben", dir);
+        collector = collector.with_recorder(SnapshotStore::new(dir)?);
+    }
+    let collector = Arc::new(collector);
+    let chronos_arc = Arc::new(Mutex::new(Chronos::with_clock(clock.clone())));
     let account_value = Arc::new(AtomicI64::new(0));
 
     let profiles_raw = fs::read_to_string("E:/MBCT/data/coin_profiles.json")?;
@@ -202,31 +287,50 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let profile_map: HashMap<String, CoinProfile> = profiles.iter().map(|p| (p.symbol.clone(), p.clone())).collect();
 
     let machines_map = Arc::new(Mutex::new(
-        profiles.iter().map(|p| (p.symbol.clone(), ShlongMachine::new(p.symbol.clone()))).collect::<HashMap<String, ShlongMachine>>()
+        profiles.iter().map(|p| (p.symbol.clone(), ShlongMachine::new(p.symbol.clone(), clock.clone()))).collect::<HashMap<String, ShlongMachine>>()
     ));
-    let histories_map = Arc::new(Mutex::new(HashMap::<String, VecDeque<PhysicsState>>::new()));
+    // Rollierende Statistik je Symbol: 90 Samples Historie, Regime erst bei voller Historie
+    let histories_map = Arc::new(Mutex::new(HashMap::<String, RegimeTracker>::new()));
 
-    let (tx_order_res, mut rx_order_res) = mpsc::channel::<(String, bool, f64, bool)>(100);
+    let (tx_order_res, mut rx_order_res) = mpsc::channel::<(String, Option<ExchangeError>, f64, bool)>(100);
 
     // Account Watcher
-    let conn_acc = conn.clone();
-    let acc_val = account_value.clone();
-    let addr_acc = main_addr.clone();
-    tokio::spawn(async move {
-        loop {
-            if let Ok(info) = conn_acc.get_user_state(&addr_acc).await {
-                let val = info.withdrawable_equity.to_f64().unwrap_or(0.0);
-                acc_val.store((val * 100.0) as i64, Ordering::Relaxed);
+    if let Some(conn_acc) = conn.clone() {
+        let main_addr = match sim_venue {
+            Some(_) => SIM_ADDRESS.to_string(),
+            None => env::var("HL_MAIN_ADDRESS").expect("HL_MAIN_ADDRESS missing"),
+        };
+        let acc_val = account_value.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Ok(value) = conn_acc.account_value(&main_addr).await {
+                    let val = value.to_f64().unwrap_or(0.0);
+                    acc_val.store((val * 100.0) as i64, Ordering::Relaxed);
+                }
+                sleep(Duration::from_secs(10)).await;
             }
-            sleep(Duration::from_secs(10)).await;
-        }
-    });
+        });
+    }
 
-    let c_listen = collector.clone();
     let symbols: Vec<String> = profiles.iter().map(|p| p.symbol.clone()).collect();
-    tokio::spawn(async move {
-        c_listen.stream_provider(symbols).await;
-    });
+    let replay = match &replay_dir {
+        Some(dir) => {
+            let speed: ReplaySpeed = env::var("REPLAY_SPEED").unwrap_or("max".to_string()).parse()?;
+            let start_ms = env::var("REPLAY_START_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(0);
+            let end_ms = env::var("REPLAY_END_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(u64::MAX);
+            println!("⏪ Replay aus {} ({:?})", dir, speed);
+            let store = SnapshotStore::new(dir.as_str())?;
+            Some(ReplayProvider::from_store(&store, &symbols, start_ms, end_ms, speed)?.with_clock(sim_clock.clone()))
+        }
+        None => {
+            let c_listen = collector.clone();
+            tokio::spawn(async move {
+                c_listen.book_feed(market_data, symbols).await;
+            });
+            None
+        }
+    };
+    let replay_done = Arc::new(AtomicBool::new(false));
 
     let c_heart = collector.clone();
     let h_arc = histories_map.clone();
@@ -235,15 +339,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let tx_res = tx_order_res.clone();
     let chr_arc = chronos_arc.clone();
     let p_map_heart = profile_map.clone();
+    let done_flag = replay_done.clone();
+    let clk_heart = clock.clone();
+    let c_live = collector.clone();
+    let sim_feed = sim_venue.clone();
 
     tokio::spawn(async move {
-        c_heart.heartbeat_loop(move |updates| {
+        let on_tick = move |updates: Vec<OrderBook>| {
             let h_lock = h_arc.clone();
             let m_lock = m_arc.clone();
             let p_map = p_map_heart.clone();
             let co_call = co_arc.clone();
             let tx_call = tx_res.clone();
             let chr_lock = chr_arc.clone();
+            let clk = clk_heart.clone();
+            // Keine neuen Entries auf Daten einer stummen oder getrennten Verbindung
+            let live = c_live.is_live();
+            // Simulierte Venue sieht dieselben (vom Collector geprüften) Bücher wie die Physik
+            if let Some(sim) = &sim_feed {
+                for book in &updates {
+                    let _ = sim.feed_book(book);
+                }
+            }
 
             async move {
                 let mut h_map = h_lock.lock().await;
@@ -252,76 +369,95 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 
                 let active_trades = m_map.values().filter(|m| m.state == TradeState::InPosition).count();
 
-                for (symbol, snapshot) in updates {
-                    let physics = Physicist::process_snapshot(&snapshot);
+                for book in updates {
+                    let symbol = book.coin.clone();
+                    let physics = Physicist::process_book_with(&book, clk.as_ref(), &features);
                     
-                    let hist = h_map.entry(symbol.clone()).or_insert_with(|| VecDeque::with_capacity(90));
-                    hist.push_back(physics.clone());
-                    if hist.len() > 90 { hist.pop_front(); }
+                    let tracker = h_map.entry(symbol.clone()).or_insert_with(|| RegimeTracker::new(90, 90));
+                    tracker.push(&physics);
 
-                    let classifier = RegimeClassifier::new(90);
-                    let regime = classifier.classify(hist);
-                    let ready = hist.len() >= 90;
+                    let regime = tracker.regime();
+                    let ready = tracker.is_ready();
 
                     let hit = chr_map.observe_potential_hit(&symbol, &physics, &regime, 0.15, 0.85);
 
                     if let (Some(m), Some(profile)) = (m_map.get_mut(&symbol), p_map.get(&symbol)) {
-                        m.update(&physics, &regime, profile, active_trades, ready, hit);
+                        m.update(&physics, &regime, profile, active_trades, ready && live, hit);
 
                         if (m.state == TradeState::PendingEntry || m.state == TradeState::Exiting) && !m.is_executing {
                             m.is_executing = true;
-                            m.executing_since = Some(Instant::now());
+                            m.executing_since = Some(m.clock.now_ms());
                             let is_entry = m.state == TradeState::PendingEntry;
                             let is_long = if is_entry { regime.symmetry_score < 0.5 } else { m.is_long };
                             
-                            // Quantisierte Size-Berechnung
-                            let size = Decimal::from_f64((12.0 / physics.price.max(0.000001)) * profile.allocation_weight).unwrap_or(Decimal::ZERO).round_dp(2);
+                            // Rohe Size - Rundung auf szDecimals übernimmt die Venue (ExchangeFilters)
+                            let size = Decimal::from_f64((12.0 / physics.price.max(0.000001)) * profile.allocation_weight).unwrap_or(Decimal::ZERO);
+
+                            if is_entry { m.is_long = is_long; }
 
                             let s_order = symbol.clone();
-                            let p_now = physics.price;
-                            let co_call_inner = co_call.clone();
-                            let tx_call_inner = tx_call.clone();
-                            tokio::spawn(async move {
-                                let res = timeout(Duration::from_secs(6), co_call_inner.place_market_order(&s_order, is_long, size, None)).await;
-                                let success = matches!(res, Ok(Ok(_)));
-                                let _ = tx_call_inner.send((s_order, success, p_now, is_entry)).await;
-                            });
+                            // Erwarteter Taker-Preis der Size im Buch; zu dünnes Buch: Mid-Price
+                            let is_buy = if is_entry { is_long } else { !m.is_long };
+                            let p_now = book.vwap(is_buy, size).and_then(|p| p.to_f64()).unwrap_or(physics.price);
+                            match co_call.clone() {
+                                // Replay mit simulierter Venue: synchron, damit der Fill zum aktuellen Buch gehört
+                                Some(venue) if replay_mode => {
+                                    let error = execute_order(venue, &s_order, is_entry, is_long, size).await;
+                                    m.apply_order_result(error.as_ref(), p_now, is_entry);
+                                }
+                                Some(venue) => {
+                                    let tx_call_inner = tx_call.clone();
+                                    tokio::spawn(async move {
+                                        let error = execute_order(venue, &s_order, is_entry, is_long, size).await;
+                                        let _ = tx_call_inner.send((s_order, error, p_now, is_entry)).await;
+                                    });
+                                }
+                                None => {
+                                    // Replay: sofortiger Fill, damit das Ergebnis nicht vom Wall-Clock-Takt abhängt
+                                    m.apply_order_result(None, p_now, is_entry);
+                                }
+                            }
                         }
                     }
                 }
             }
-        }).await;
+        };
+
+        match replay {
+            Some(replay) => {
+                c_heart.replay_loop(replay, on_tick).await;
+                done_flag.store(true, Ordering::Relaxed);
+            }
+            None => c_heart.heartbeat_loop(on_tick).await,
+        }
     });
 
     loop {
-        while let Ok((sym, ok, price, entry)) = rx_order_res.try_recv() {
+        while let Ok((sym, error, price, entry)) = rx_order_res.try_recv() {
             let mut m_map = machines_map.lock().await;
             if let Some(m) = m_map.get_mut(&sym) {
-                m.is_executing = false;
-                if ok {
-                    m.state = if entry { TradeState::InPosition } else { TradeState::Cooldown };
-                    if entry {
-                        m.entry_price = Some(price);
-                        m.opened_at = Some(Instant::now());
-                        m.highest_pnl = 0.0;
-                    }
-                } else {
-                    m.state = if entry { TradeState::Observing } else { TradeState::InPosition };
-                }
-                m.last_action = Instant::now();
+                m.apply_order_result(error.as_ref(), price, entry);
             }
         }
 
+        let finished = replay_done.load(Ordering::Relaxed);
+
         {
             let m_map = machines_map.lock().await;
             let h_map = histories_map.lock().await;
             let stats = collector.get_stats();
             let rec = stats.0;
+            let link = match collector.connection_state() {
+                Some(ConnectionState::Connected) => "LIVE".to_string(),
+                Some(ConnectionState::Stale { silent_ms }) => format!("STALE {}s", silent_ms / 1000),
+                Some(ConnectionState::Reconnecting { attempt, .. }) => format!("RECON #{}", attempt),
+                None => "INIT".to_string(),
+            };
             let equity = account_value.load(Ordering::Relaxed) as f64 / 100.0;
 
             print!("{}[H", 27 as char);
             println!("╔══════════════════════════════════════════════════════════════════════════════════════════╗");
-            println!("║ 🛡️  THE ALLIANCE v7.7 | WS-RCV: {:<10} | EQUITY: {:>10.2} USD                ║", rec, equity);
+            println!("║ 🛡️  THE ALLIANCE v7.7 | WS-RCV: {:<10} | WS: {:<8} | EQUITY: {:>10.2} USD ║", rec, link, equity);
             println!("╠══════════════════════════════════════════════════════════════════════════════════════════╣");
             println!("║ SYMBOL   | PRICE        | SYM   | Z-NRG  | PnL %   | MAX % | STATE                     ║");
             println!("╟──────────┼────────────┼───────┼────────┼─────────┼───────┼───────────────────────────╢");
@@ -330,10 +466,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             keys.sort();
             for k in keys {
                 if let (Some(m), Some(h), Some(profile)) = (m_map.get(k), h_map.get(k), profile_map.get(k)) {
-                    let last_p = h.back().cloned().unwrap_or_default();
-                    let z_nrg = RegimeClassifier::calculate_z_score(last_p.nrg, h, "nrg");
-                    let classifier = RegimeClassifier::new(90);
-                    let reg = classifier.classify(h);
+                    let last_p = h.last().cloned().unwrap_or_default();
+                    let z_nrg = h.z_score(PhysicsField::Nrg, last_p.nrg);
+                    let reg = h.regime();
                     let pnl = if m.state == TradeState::InPosition { format!("{:>+7.2}%", m.get_pnl(last_p.price)) } else { "---".to_string() };
                     let max_pnl = if m.state == TradeState::InPosition { format!("{:>+5.2}%", m.highest_pnl) } else { "---".to_string() };
 
@@ -347,6 +482,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
             println!("╚══════════════════════════════════════════════════════════════════════════════════════════╝");
         }
+
+        if finished {
+            println!("⏹️  Replay abgeschlossen.");
+            return Ok(());
+        }
         sleep(Duration::from_millis(600)).await;
     }
 }
\ No newline at end of file
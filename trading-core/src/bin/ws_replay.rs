@@ -0,0 +1,43 @@
+// E:\MBCT\trading-core\src\bin\ws_replay.rs
+// THE ALLIANCE - Websocket-Replay-Server
+//
+// Spielt eine mit HL_WS_RECORD (Collector-Stream von Trader/Researcher) aufgezeichnete Datei
+// über einen lokalen Websocket ab.
+// Trader, Researcher und research_engine verbinden sich mit HL_WS_URL=ws://127.0.0.1:<port>.
+//
+// Nutzung:
+//   ws_replay <datei.jsonl> [port=8765] [speed=realtime|max|<N>x]
+
+use anyhow::{anyhow, Result};
+use std::env;
+use trading_core::exchange::ws_replay::{load_frames, WsReplayServer};
+use trading_core::replay::ReplaySpeed;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let path = args
+        .first()
+        .ok_or_else(|| anyhow!("Nutzung: ws_replay <datei.jsonl> [port] [speed]"))?;
+    let port: u16 = args.get(1).map(|p| p.parse()).transpose()?.unwrap_or(8765);
+    let speed: ReplaySpeed = args
+        .get(2)
+        .map(|s| s.parse())
+        .transpose()
+        .map_err(|e: String| anyhow!(e))?
+        .unwrap_or(ReplaySpeed::Realtime);
+
+    let frames = load_frames(path)?;
+    println!("📼 {} Frames aus {} geladen", frames.len(), path);
+
+    let server = WsReplayServer::bind(&format!("127.0.0.1:{}", port), frames, speed).await?;
+    println!("📡 Replay-Server: HL_WS_URL={}", server.url());
+
+    tokio::signal::ctrl_c().await?;
+    println!(
+        "🛑 Beendet: {} Verbindungen, {} Frames abgespielt",
+        server.connections(),
+        server.frames_sent()
+    );
+    Ok(())
+}
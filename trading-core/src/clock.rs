@@ -0,0 +1,84 @@
+// E:\MBCT\trading-core\src\clock.rs
+// THE ALLIANCE - Zeitquelle
+// Alle zeitabhängigen Entscheidungen (Cooldowns, Peak-Timeout, Forward-Returns) lesen die Zeit
+// über eine injizierte Clock. Live: Wall-Clock. Replay/Tests: simulierte Uhr.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub trait Clock: Send + Sync {
+    /// Aktuelle Zeit in Unix-Millisekunden
+    fn now_ms(&self) -> u64;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Systemzeit
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WallClock;
+
+impl WallClock {
+    pub fn shared() -> SharedClock {
+        Arc::new(WallClock)
+    }
+}
+
+impl Clock for WallClock {
+    fn now_ms(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// Manuell gesteuerte Uhr, wird vom ReplayProvider auf die Snapshot-Zeit gesetzt
+#[derive(Debug, Default)]
+pub struct SimulatedClock {
+    now_ms: AtomicU64,
+}
+
+impl SimulatedClock {
+    pub fn new(start_ms: u64) -> Self {
+        Self {
+            now_ms: AtomicU64::new(start_ms),
+        }
+    }
+
+    /// Setzt die Zeit; Rückwärtssprünge werden ignoriert, die Uhr ist monoton
+    pub fn set_ms(&self, now_ms: u64) {
+        self.now_ms.fetch_max(now_ms, Ordering::SeqCst);
+    }
+
+    pub fn advance_ms(&self, delta_ms: u64) {
+        self.now_ms.fetch_add(delta_ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now_ms(&self) -> u64 {
+        self.now_ms.load(Ordering::SeqCst)
+    }
+}
+
+/// Vergangene Sekunden seit `since_ms` (abgerundet wie Duration::as_secs)
+pub fn elapsed_secs(clock: &dyn Clock, since_ms: u64) -> u64 {
+    clock.now_ms().saturating_sub(since_ms) / 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulated_clock_is_monotonic() {
+        let clock = SimulatedClock::new(10_000);
+        clock.advance_ms(2_500);
+        assert_eq!(clock.now_ms(), 12_500);
+
+        clock.set_ms(5_000);
+        assert_eq!(clock.now_ms(), 12_500);
+        assert_eq!(elapsed_secs(&clock, 1_000), 11);
+    }
+}
@@ -0,0 +1,283 @@
+// exchange/account.rs
+// Typed account state: clearinghouseState (perp margin) and spotClearinghouseState
+//
+// Numbers arrive as exchange strings and are parsed into Decimal on
+// deserialization (serde_decimal); a malformed field fails the response.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::serde_decimal;
+
+/// Response of `clearinghouseState`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearinghouseState {
+    #[serde(default)]
+    pub asset_positions: Vec<Position>,
+    /// Cross and isolated margin combined
+    pub margin_summary: MarginSummary,
+    pub cross_margin_summary: MarginSummary,
+    #[serde(default, with = "serde_decimal")]
+    pub cross_maintenance_margin_used: Decimal,
+    /// USDC that can be withdrawn right now
+    #[serde(with = "serde_decimal")]
+    pub withdrawable: Decimal,
+    #[serde(default)]
+    pub time: u64,
+}
+
+impl ClearinghouseState {
+    pub fn account_value(&self) -> Decimal {
+        self.margin_summary.account_value
+    }
+
+    pub fn withdrawable(&self) -> Decimal {
+        self.withdrawable
+    }
+
+    pub fn total_margin_used(&self) -> Decimal {
+        self.margin_summary.total_margin_used
+    }
+
+    /// Maintenance margin used / cross account value; liquidation at 1.0
+    pub fn cross_margin_ratio(&self) -> Decimal {
+        let account_value = self.cross_margin_summary.account_value;
+        if account_value <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        self.cross_maintenance_margin_used / account_value
+    }
+
+    pub fn position(&self, coin: &str) -> Option<&PositionData> {
+        self.asset_positions
+            .iter()
+            .map(|p| &p.position)
+            .find(|p| p.coin == coin)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarginSummary {
+    #[serde(with = "serde_decimal")]
+    pub account_value: Decimal,
+    #[serde(with = "serde_decimal")]
+    pub total_ntl_pos: Decimal,
+    #[serde(with = "serde_decimal")]
+    pub total_raw_usd: Decimal,
+    #[serde(with = "serde_decimal")]
+    pub total_margin_used: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub position: PositionData,
+    /// Position mode, "oneWay"
+    #[serde(rename = "type", default)]
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionData {
+    pub coin: String,
+    /// Signed size: positive long, negative short
+    #[serde(with = "serde_decimal")]
+    pub szi: Decimal,
+    #[serde(default, with = "serde_decimal::option")]
+    pub entry_px: Option<Decimal>,
+    pub leverage: Leverage,
+    #[serde(default, with = "serde_decimal::option")]
+    pub liquidation_px: Option<Decimal>,
+    #[serde(with = "serde_decimal")]
+    pub margin_used: Decimal,
+    #[serde(default)]
+    pub max_leverage: u32,
+    #[serde(with = "serde_decimal")]
+    pub position_value: Decimal,
+    #[serde(with = "serde_decimal")]
+    pub return_on_equity: Decimal,
+    #[serde(with = "serde_decimal")]
+    pub unrealized_pnl: Decimal,
+    #[serde(default)]
+    pub cum_funding: CumFunding,
+}
+
+impl PositionData {
+    pub fn size(&self) -> Decimal {
+        self.szi
+    }
+
+    pub fn is_long(&self) -> bool {
+        self.size() > Decimal::ZERO
+    }
+
+    pub fn entry_price(&self) -> Option<Decimal> {
+        self.entry_px
+    }
+
+    pub fn liquidation_price(&self) -> Option<Decimal> {
+        self.liquidation_px
+    }
+
+    pub fn margin_used(&self) -> Decimal {
+        self.margin_used
+    }
+
+    pub fn unrealized_pnl(&self) -> Decimal {
+        self.unrealized_pnl
+    }
+
+    pub fn return_on_equity(&self) -> Decimal {
+        self.return_on_equity
+    }
+}
+
+/// Leverage setting of a position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Leverage {
+    Cross {
+        value: u32,
+    },
+    Isolated {
+        value: u32,
+        #[serde(rename = "rawUsd", with = "serde_decimal")]
+        raw_usd: Decimal,
+    },
+}
+
+impl Leverage {
+    pub fn value(&self) -> u32 {
+        match self {
+            Leverage::Cross { value } | Leverage::Isolated { value, .. } => *value,
+        }
+    }
+
+    pub fn is_cross(&self) -> bool {
+        matches!(self, Leverage::Cross { .. })
+    }
+}
+
+/// Cumulative funding paid by the position (positive = paid)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CumFunding {
+    #[serde(with = "serde_decimal")]
+    pub all_time: Decimal,
+    #[serde(with = "serde_decimal")]
+    pub since_open: Decimal,
+    #[serde(with = "serde_decimal")]
+    pub since_change: Decimal,
+}
+
+/// Response of `spotClearinghouseState`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpotClearinghouseState {
+    #[serde(default)]
+    pub balances: Vec<Balance>,
+}
+
+impl SpotClearinghouseState {
+    pub fn balance(&self, coin: &str) -> Decimal {
+        self.balances
+            .iter()
+            .find(|b| b.coin == coin)
+            .map(|b| b.total())
+            .unwrap_or(Decimal::ZERO)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Balance {
+    pub coin: String,
+    #[serde(default)]
+    pub token: u32,
+    #[serde(with = "serde_decimal")]
+    pub total: Decimal,
+    /// Reserved by open orders
+    #[serde(default, with = "serde_decimal")]
+    pub hold: Decimal,
+    #[serde(default, with = "serde_decimal")]
+    pub entry_ntl: Decimal,
+}
+
+impl Balance {
+    pub fn total(&self) -> Decimal {
+        self.total
+    }
+
+    /// Total minus the part held by open orders
+    pub fn available(&self) -> Decimal {
+        self.total - self.hold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_clearinghouse_state() {
+        let json = r#"{
+            "assetPositions": [{
+                "position": {
+                    "coin": "ETH",
+                    "cumFunding": { "allTime": "514.085417", "sinceChange": "0.0", "sinceOpen": "0.0" },
+                    "entryPx": "2986.3",
+                    "leverage": { "rawUsd": "-95.059824", "type": "isolated", "value": 20 },
+                    "liquidationPx": "2866.26936529",
+                    "marginUsed": "4.967826",
+                    "maxLeverage": 50,
+                    "positionValue": "100.02765",
+                    "returnOnEquity": "-0.0026789",
+                    "szi": "0.0335",
+                    "unrealizedPnl": "-0.0134"
+                },
+                "type": "oneWay"
+            }, {
+                "position": {
+                    "coin": "BTC", "entryPx": "60000.0", "leverage": { "type": "cross", "value": 10 },
+                    "liquidationPx": null, "marginUsed": "60.0", "maxLeverage": 40,
+                    "positionValue": "600.0", "returnOnEquity": "0.0", "szi": "-0.01",
+                    "unrealizedPnl": "0.0"
+                },
+                "type": "oneWay"
+            }],
+            "crossMaintenanceMarginUsed": "150.0",
+            "crossMarginSummary": { "accountValue": "1000.0", "totalMarginUsed": "60.0", "totalNtlPos": "600.0", "totalRawUsd": "1600.0" },
+            "marginSummary": { "accountValue": "13104.514502", "totalMarginUsed": "64.967826", "totalNtlPos": "700.02765", "totalRawUsd": "13104.514502" },
+            "time": 1708622398623,
+            "withdrawable": "13104.514502"
+        }"#;
+        let state: ClearinghouseState = serde_json::from_str(json).unwrap();
+        assert_eq!(state.account_value(), dec!(13104.514502));
+        assert_eq!(state.cross_margin_ratio(), dec!(0.15));
+
+        let eth = state.position("ETH").unwrap();
+        assert_eq!(eth.leverage.value(), 20);
+        assert!(!eth.leverage.is_cross());
+        assert_eq!(eth.liquidation_price(), Some(dec!(2866.26936529)));
+        assert_eq!(eth.cum_funding.all_time, dec!(514.085417));
+
+        let btc = state.position("BTC").unwrap();
+        assert!(!btc.is_long());
+        assert!(btc.leverage.is_cross());
+        assert_eq!(btc.liquidation_price(), None);
+    }
+
+    #[test]
+    fn test_parse_spot_state() {
+        let json = r#"{"balances": [
+            {"coin": "USDC", "token": 0, "hold": "2.5", "total": "14.625485", "entryNtl": "0.0"},
+            {"coin": "PURR", "token": 1, "hold": "0.0", "total": "2000", "entryNtl": "1234.56"}
+        ]}"#;
+        let state: SpotClearinghouseState = serde_json::from_str(json).unwrap();
+        assert_eq!(state.balance("USDC"), dec!(14.625485));
+        assert_eq!(state.balances[0].available(), dec!(12.125485));
+        assert_eq!(state.balance("HFUN"), Decimal::ZERO);
+    }
+}
@@ -0,0 +1,552 @@
+// exchange/actions.rs
+// Typed Hyperliquid L1 actions
+//
+// The action hash is computed over the msgpack encoding, so field order and
+// names must match the Hyperliquid Python SDK exactly. serde_json::Value sorts
+// keys alphabetically and can therefore not be used for signed actions.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Time in force of a limit order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tif {
+    /// Add liquidity only (post-only)
+    Alo,
+    /// Immediate or cancel
+    Ioc,
+    /// Good til cancelled
+    Gtc,
+}
+
+/// Trigger kind: take profit or stop loss
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tpsl {
+    Tp,
+    Sl,
+}
+
+/// Order type, encoded as `{"limit": {"tif": "Gtc"}}` or
+/// `{"trigger": {"isMarket": true, "triggerPx": "...", "tpsl": "sl"}}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderTypeWire {
+    Limit {
+        tif: Tif,
+    },
+    Trigger {
+        #[serde(rename = "isMarket")]
+        is_market: bool,
+        #[serde(rename = "triggerPx")]
+        trigger_px: String,
+        tpsl: Tpsl,
+    },
+}
+
+/// Client order id: 16 bytes as 0x-prefixed hex
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Cloid(String);
+
+impl Cloid {
+    pub fn from_u128(id: u128) -> Self {
+        Cloid(format!("0x{:032x}", id))
+    }
+
+    /// Parse a 0x-prefixed 32 character hex string
+    pub fn parse(raw: &str) -> anyhow::Result<Self> {
+        let digits = raw
+            .strip_prefix("0x")
+            .ok_or_else(|| anyhow::anyhow!("Cloid must start with 0x: {}", raw))?;
+        if digits.len() != 32 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow::anyhow!("Cloid must be 16 bytes hex: {}", raw));
+        }
+        Ok(Cloid(raw.to_lowercase()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for Cloid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Single order as sent on the wire
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderWire {
+    /// Asset index
+    pub a: u32,
+    /// Is buy
+    pub b: bool,
+    /// Limit price
+    pub p: String,
+    /// Size
+    pub s: String,
+    /// Reduce only
+    pub r: bool,
+    /// Order type
+    pub t: OrderTypeWire,
+    /// Client order id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub c: Option<Cloid>,
+}
+
+/// How the orders of one bulk request relate to each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Grouping {
+    /// Independent orders
+    Na,
+    /// Entry order followed by its TP/SL triggers, sized like the entry
+    NormalTpsl,
+    /// TP/SL triggers attached to the whole position, resized with it
+    PositionTpsl,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CancelWire {
+    /// Asset index
+    pub a: u32,
+    /// Order id
+    pub o: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CancelByCloidWire {
+    pub asset: u32,
+    pub cloid: String,
+}
+
+/// Reference to an existing order, by exchange id or client order id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OrderRef {
+    Oid(u64),
+    Cloid(Cloid),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModifyWire {
+    pub oid: OrderRef,
+    pub order: OrderWire,
+}
+
+/// L1 action, signed via the phantom agent (see wallet::action_hash)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Action {
+    Order {
+        orders: Vec<OrderWire>,
+        grouping: Grouping,
+    },
+    Cancel {
+        cancels: Vec<CancelWire>,
+    },
+    CancelByCloid {
+        cancels: Vec<CancelByCloidWire>,
+    },
+    Modify {
+        oid: OrderRef,
+        order: OrderWire,
+    },
+    BatchModify {
+        modifies: Vec<ModifyWire>,
+    },
+    UpdateLeverage {
+        asset: u32,
+        #[serde(rename = "isCross")]
+        is_cross: bool,
+        leverage: u32,
+    },
+}
+
+impl Action {
+    /// Orders, cancels or modifies carried by the action (drives the request weight)
+    pub fn batch_len(&self) -> usize {
+        match self {
+            Action::Order { orders, .. } => orders.len(),
+            Action::Cancel { cancels } => cancels.len(),
+            Action::CancelByCloid { cancels } => cancels.len(),
+            Action::BatchModify { modifies } => modifies.len(),
+            Action::Modify { .. } | Action::UpdateLeverage { .. } => 1,
+        }
+    }
+}
+
+/// Wire format of prices and sizes: no trailing zeros, no exponent
+pub fn decimal_to_wire(value: Decimal) -> String {
+    let normalized = value.normalize();
+    if normalized.is_zero() {
+        "0".to_string()
+    } else {
+        normalized.to_string()
+    }
+}
+
+// ====
+// Order Requests
+// ====
+
+/// Order kind of an [`OrderRequest`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderKind {
+    Limit(Tif),
+    /// Trigger order; `is_market` executes with the exchange's 10% slippage
+    /// at trigger, otherwise as limit at `limit_px`
+    Trigger {
+        trigger_px: Decimal,
+        is_market: bool,
+        tpsl: Tpsl,
+    },
+}
+
+/// Order in human units, converted to an [`OrderWire`] once the asset is known
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderRequest {
+    pub coin: String,
+    pub is_buy: bool,
+    pub size: Decimal,
+    pub limit_px: Decimal,
+    pub kind: OrderKind,
+    pub reduce_only: bool,
+    pub cloid: Option<Cloid>,
+}
+
+impl OrderRequest {
+    pub fn limit(coin: &str, is_buy: bool, size: Decimal, limit_px: Decimal, tif: Tif) -> Self {
+        Self {
+            coin: coin.to_string(),
+            is_buy,
+            size,
+            limit_px,
+            kind: OrderKind::Limit(tif),
+            reduce_only: false,
+            cloid: None,
+        }
+    }
+
+    /// Stop loss / take profit; always reduce only. `limit_px` bounds the fill
+    /// of a market trigger and is the resting price of a limit trigger.
+    pub fn trigger(
+        coin: &str,
+        is_buy: bool,
+        size: Decimal,
+        trigger_px: Decimal,
+        limit_px: Decimal,
+        tpsl: Tpsl,
+        is_market: bool,
+    ) -> Self {
+        Self {
+            coin: coin.to_string(),
+            is_buy,
+            size,
+            limit_px,
+            kind: OrderKind::Trigger {
+                trigger_px,
+                is_market,
+                tpsl,
+            },
+            reduce_only: true,
+            cloid: None,
+        }
+    }
+
+    pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = reduce_only;
+        self
+    }
+
+    pub fn with_cloid(mut self, cloid: Cloid) -> Self {
+        self.cloid = Some(cloid);
+        self
+    }
+
+    /// Wire representation; prices and sizes must already be rounded
+    pub fn to_wire(&self, asset: u32) -> OrderWire {
+        let t = match self.kind {
+            OrderKind::Limit(tif) => OrderTypeWire::Limit { tif },
+            OrderKind::Trigger {
+                trigger_px,
+                is_market,
+                tpsl,
+            } => OrderTypeWire::Trigger {
+                is_market,
+                trigger_px: decimal_to_wire(trigger_px),
+                tpsl,
+            },
+        };
+        OrderWire {
+            a: asset,
+            b: self.is_buy,
+            p: decimal_to_wire(self.limit_px),
+            s: decimal_to_wire(self.size),
+            r: self.reduce_only,
+            t,
+            c: self.cloid.clone(),
+        }
+    }
+}
+
+// ====
+// User-Signed Actions (EIP-712 domain "HyperliquidSignTransaction")
+// ====
+
+/// Chain id carried in user-signed actions (Arbitrum Sepolia, used by the official SDK for both networks)
+pub const SIGNATURE_CHAIN_ID: &str = "0x66eee";
+
+/// Actions signed directly by the user wallet instead of via phantom agent.
+/// Build with the constructors, which fill `signatureChainId` and `hyperliquidChain`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum UserSignedAction {
+    /// USDC transfer to another address on Hyperliquid
+    UsdSend {
+        signature_chain_id: String,
+        hyperliquid_chain: String,
+        destination: String,
+        amount: String,
+        time: u64,
+    },
+    /// Withdrawal to Arbitrum via the bridge
+    Withdraw3 {
+        signature_chain_id: String,
+        hyperliquid_chain: String,
+        destination: String,
+        amount: String,
+        time: u64,
+    },
+    /// Move USDC between spot and perp balance
+    UsdClassTransfer {
+        signature_chain_id: String,
+        hyperliquid_chain: String,
+        amount: String,
+        to_perp: bool,
+        nonce: u64,
+    },
+    /// Authorize an API (agent) wallet to trade for this account
+    ApproveAgent {
+        signature_chain_id: String,
+        hyperliquid_chain: String,
+        agent_address: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        agent_name: Option<String>,
+        nonce: u64,
+    },
+}
+
+fn hyperliquid_chain(is_mainnet: bool) -> String {
+    if is_mainnet { "Mainnet" } else { "Testnet" }.to_string()
+}
+
+impl UserSignedAction {
+    pub fn usd_send(destination: &str, amount: &str, time: u64, is_mainnet: bool) -> Self {
+        UserSignedAction::UsdSend {
+            signature_chain_id: SIGNATURE_CHAIN_ID.to_string(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            destination: destination.to_string(),
+            amount: amount.to_string(),
+            time,
+        }
+    }
+
+    pub fn withdraw(destination: &str, amount: &str, time: u64, is_mainnet: bool) -> Self {
+        UserSignedAction::Withdraw3 {
+            signature_chain_id: SIGNATURE_CHAIN_ID.to_string(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            destination: destination.to_string(),
+            amount: amount.to_string(),
+            time,
+        }
+    }
+
+    pub fn usd_class_transfer(amount: &str, to_perp: bool, nonce: u64, is_mainnet: bool) -> Self {
+        UserSignedAction::UsdClassTransfer {
+            signature_chain_id: SIGNATURE_CHAIN_ID.to_string(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            amount: amount.to_string(),
+            to_perp,
+            nonce,
+        }
+    }
+
+    pub fn approve_agent(
+        agent_address: &str,
+        agent_name: Option<&str>,
+        nonce: u64,
+        is_mainnet: bool,
+    ) -> Self {
+        UserSignedAction::ApproveAgent {
+            signature_chain_id: SIGNATURE_CHAIN_ID.to_string(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            agent_address: agent_address.to_string(),
+            agent_name: agent_name.map(str::to_string),
+            nonce,
+        }
+    }
+
+    /// Nonce of the request (`time` or `nonce` field)
+    pub fn nonce(&self) -> u64 {
+        match self {
+            UserSignedAction::UsdSend { time, .. } | UserSignedAction::Withdraw3 { time, .. } => {
+                *time
+            }
+            UserSignedAction::UsdClassTransfer { nonce, .. }
+            | UserSignedAction::ApproveAgent { nonce, .. } => *nonce,
+        }
+    }
+
+    pub fn signature_chain_id(&self) -> &str {
+        match self {
+            UserSignedAction::UsdSend {
+                signature_chain_id, ..
+            }
+            | UserSignedAction::Withdraw3 {
+                signature_chain_id, ..
+            }
+            | UserSignedAction::UsdClassTransfer {
+                signature_chain_id, ..
+            }
+            | UserSignedAction::ApproveAgent {
+                signature_chain_id, ..
+            } => signature_chain_id,
+        }
+    }
+
+    /// EIP-712 primary type
+    pub fn primary_type(&self) -> &'static str {
+        match self {
+            UserSignedAction::UsdSend { .. } => "HyperliquidTransaction:UsdSend",
+            UserSignedAction::Withdraw3 { .. } => "HyperliquidTransaction:Withdraw",
+            UserSignedAction::UsdClassTransfer { .. } => "HyperliquidTransaction:UsdClassTransfer",
+            UserSignedAction::ApproveAgent { .. } => "HyperliquidTransaction:ApproveAgent",
+        }
+    }
+
+    /// EIP-712 fields of the primary type, in signing order
+    pub fn sign_fields(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            UserSignedAction::UsdSend { .. } | UserSignedAction::Withdraw3 { .. } => &[
+                ("hyperliquidChain", "string"),
+                ("destination", "string"),
+                ("amount", "string"),
+                ("time", "uint64"),
+            ],
+            UserSignedAction::UsdClassTransfer { .. } => &[
+                ("hyperliquidChain", "string"),
+                ("amount", "string"),
+                ("toPerp", "bool"),
+                ("nonce", "uint64"),
+            ],
+            UserSignedAction::ApproveAgent { .. } => &[
+                ("hyperliquidChain", "string"),
+                ("agentAddress", "address"),
+                ("agentName", "string"),
+                ("nonce", "uint64"),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_json_matches_api() {
+        let action = Action::Order {
+            orders: vec![OrderWire {
+                a: 4,
+                b: true,
+                p: "1670.1".into(),
+                s: "0.0147".into(),
+                r: false,
+                t: OrderTypeWire::Limit { tif: Tif::Ioc },
+                c: None,
+            }],
+            grouping: Grouping::Na,
+        };
+        assert_eq!(
+            serde_json::to_string(&action).unwrap(),
+            r#"{"type":"order","orders":[{"a":4,"b":true,"p":"1670.1","s":"0.0147","r":false,"t":{"limit":{"tif":"Ioc"}}}],"grouping":"na"}"#
+        );
+
+        let leverage = Action::UpdateLeverage {
+            asset: 1,
+            is_cross: true,
+            leverage: 5,
+        };
+        assert_eq!(
+            serde_json::to_string(&leverage).unwrap(),
+            r#"{"type":"updateLeverage","asset":1,"isCross":true,"leverage":5}"#
+        );
+    }
+
+    #[test]
+    fn test_user_signed_action_json() {
+        let action = UserSignedAction::usd_class_transfer("12.5", true, 1700000000000, false);
+        assert_eq!(
+            serde_json::to_string(&action).unwrap(),
+            r#"{"type":"usdClassTransfer","signatureChainId":"0x66eee","hyperliquidChain":"Testnet","amount":"12.5","toPerp":true,"nonce":1700000000000}"#
+        );
+
+        let agent = UserSignedAction::approve_agent("0xabc", None, 1, true);
+        let json = serde_json::to_value(&agent).unwrap();
+        assert_eq!(json["type"], "approveAgent");
+        assert!(json.get("agentName").is_none());
+        assert_eq!(agent.nonce(), 1);
+    }
+
+    #[test]
+    fn test_trigger_and_modify_json() {
+        let cloid = Cloid::from_u128(7);
+        let sl = OrderRequest::trigger(
+            "ETH",
+            false,
+            Decimal::new(150, 2),
+            Decimal::new(18000, 1),
+            Decimal::new(17500, 1),
+            Tpsl::Sl,
+            true,
+        )
+        .with_cloid(cloid.clone());
+        let wire = sl.to_wire(1);
+        assert_eq!(
+            serde_json::to_string(&wire).unwrap(),
+            r#"{"a":1,"b":false,"p":"1750","s":"1.5","r":true,"t":{"trigger":{"isMarket":true,"triggerPx":"1800","tpsl":"sl"}},"c":"0x00000000000000000000000000000007"}"#
+        );
+
+        let modify = Action::BatchModify {
+            modifies: vec![
+                ModifyWire {
+                    oid: OrderRef::Oid(42),
+                    order: wire.clone(),
+                },
+                ModifyWire {
+                    oid: OrderRef::Cloid(cloid),
+                    order: wire,
+                },
+            ],
+        };
+        let json = serde_json::to_value(&modify).unwrap();
+        assert_eq!(json["type"], "batchModify");
+        assert_eq!(json["modifies"][0]["oid"], 42);
+        assert_eq!(
+            json["modifies"][1]["oid"],
+            "0x00000000000000000000000000000007"
+        );
+
+        let grouping = serde_json::to_string(&Grouping::PositionTpsl).unwrap();
+        assert_eq!(grouping, r#""positionTpsl""#);
+        assert!(Cloid::parse("0x1234").is_err());
+        assert_eq!(decimal_to_wire(Decimal::new(-0, 3)), "0");
+    }
+}
@@ -8,75 +8,127 @@
 // ====
 
 use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
 use reqwest::Client;
-use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, RwLock};
 
+use super::account::*;
+use super::actions::*;
+use super::errors::ExchangeError;
+use super::filters::ExchangeFilters;
+use super::info::*;
+use super::nonce::NonceManager;
+use super::orderbook::OrderBook;
+use super::rate_limit::{
+    exchange_weight, info_weight, response_weight, Priority, RateLimitStats, RateLimiter,
+};
+use super::traits::{AccountQueries, MarketDataStream, OrderExecution};
+use super::types::L2Snapshot;
 use super::wallet::*;
+use super::ws::HyperliquidWs;
 
 /// Hyperliquid API Endpoints
 const MAINNET_API: &str = "https://api.hyperliquid.xyz";
 const TESTNET_API: &str = "https://api.hyperliquid-testnet.xyz";
 
+/// Unbekannte Symbole lösen höchstens einmal pro Minute einen meta-Abruf aus
+const META_REFRESH: Duration = Duration::from_secs(60);
+
 /// Hyperliquid Connector
 pub struct HyperliquidConnector {
     /// HTTP client
     client: Client,
     /// Wallet for signing
     wallet: HyperliquidWallet,
+    /// Traded account; differs from the signer when an agent wallet signs for a master account
+    account: String,
     /// API base URL
     base_url: String,
     /// Is testnet?
     pub is_testnet: bool,
     /// Asset info cache
     asset_info: Arc<RwLock<HashMap<String, AssetInfo>>>,
+    /// Monotonic nonces of this signer (shared across tasks and connectors)
+    nonces: Arc<NonceManager>,
+    /// Request weight budget (per IP, shared across connectors)
+    limiter: Arc<RateLimiter>,
+    /// Last full meta + spotMeta reload after a cache miss
+    universe_loaded: RwLock<Option<Instant>>,
 }
 
 impl HyperliquidConnector {
+    /// Mainnet/Testnet-API; HL_API_URL überschreibt die URL (z.B. MockExchange)
     pub fn new(private_key: &str, is_testnet: bool) -> Result<Self> {
+        Self::with_base_url(private_key, is_testnet, &api_url(is_testnet))
+    }
+
+    /// Beliebige API-URL; `is_testnet` bestimmt weiterhin die Signatur-Quelle
+    pub fn with_base_url(private_key: &str, is_testnet: bool, base_url: &str) -> Result<Self> {
         let wallet = HyperliquidWallet::from_private_key(private_key)?;
-        let base_url = if is_testnet {
-            TESTNET_API.to_string()
-        } else {
-            MAINNET_API.to_string()
-        };
+        let base_url = base_url.trim_end_matches('/').to_string();
 
         let client = Client::builder()
             .timeout(std::time::Duration::from_secs(30))
             .build()?;
 
+        let nonces = NonceManager::for_signer(&wallet.address);
+
         Ok(Self {
             client,
+            account: wallet.address.clone(),
             wallet,
             base_url,
             is_testnet,
             asset_info: Arc::new(RwLock::new(HashMap::new())),
+            nonces,
+            limiter: RateLimiter::shared(),
+            universe_loaded: RwLock::new(None),
         })
     }
 
+    /// Account, für den der Signer handelt (Agent-Wallet: Master-Adresse, z.B. HL_MAIN_ADDRESS).
+    /// Positionen, Balances und market_close beziehen sich auf diese Adresse.
+    pub fn with_account(mut self, address: &str) -> Self {
+        self.account = address.to_lowercase();
+        self
+    }
+
+    /// Eigenes Budget statt des prozessweiten (Tests, getrennte IPs)
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Verbleibendes Request-Gewicht, Wartende und verworfene Requests
+    pub fn rate_limit_stats(&self) -> RateLimitStats {
+        self.limiter.stats()
+    }
+
+    /// Signer-Adresse (bei Agent-Wallets nicht die Account-Adresse)
     pub fn address(&self) -> &str {
         &self.wallet.address
     }
 
+    /// Gehandelter Account; ohne with_account die Signer-Adresse
+    pub fn account_address(&self) -> &str {
+        &self.account
+    }
+
     // ====================================================================
     // MARKET DATA
     // ====================================================================
 
     /// Holt alle Assets und korrigiert das Index-Mapping dynamisch
     pub async fn get_all_assets(&self) -> Result<Vec<AssetInfo>> {
-        let url = format!("{}/info", self.base_url);
-        let response_value: Value = self
-            .client
-            .post(&url)
-            .json(&json!({ "type": "meta" }))
-            .send()
-            .await?
-            .json()
+        let response_value = self
+            .info_value(Priority::Normal, json!({ "type": "meta" }))
             .await?;
 
         // Allianz-Sicherung: Wir parsen das "universe" manuell,
@@ -96,6 +148,7 @@ impl HyperliquidConnector {
                 name: name.clone(),
                 index: idx as u32, // Wir nutzen die Array-Position als verlässlichen Index
                 sz_decimals,
+                is_spot: false,
             };
 
             assets.push(asset.clone());
@@ -112,35 +165,58 @@ impl HyperliquidConnector {
                 return Ok(info.clone());
             }
         }
-        let assets = self.get_all_assets().await?;
-        assets
+        let loaded = *self.universe_loaded.read().await;
+        if loaded.is_some_and(|t| t.elapsed() < META_REFRESH) {
+            return Err(ExchangeError::UnknownAsset(symbol.to_string()).into());
+        }
+        if let Some(asset) = self
+            .get_all_assets()
+            .await?
             .into_iter()
             .find(|a| a.name == symbol)
-            .ok_or_else(|| anyhow!("Asset {} nicht gefunden", symbol))
+        {
+            return Ok(asset);
+        }
+        let spot = self.get_spot_assets().await?;
+        *self.universe_loaded.write().await = Some(Instant::now());
+        spot.into_iter()
+            .find(|a| a.name == symbol || format!("@{}", a.index - SPOT_ASSET_OFFSET) == symbol)
+            .ok_or_else(|| ExchangeError::UnknownAsset(symbol.to_string()).into())
     }
 
-    pub async fn get_orderbook(&self, symbol: &str) -> Result<Orderbook> {
-        let url = format!("{}/info", self.base_url);
-        let response: OrderbookResponse = self
-            .client
-            .post(&url)
-            .json(&json!({ "type": "l2Book", "coin": symbol }))
-            .send()
-            .await?
-            .json()
+    /// Spot-Paare aus `spotMeta`; szDecimals kommen vom Base-Token
+    pub async fn get_spot_assets(&self) -> Result<Vec<AssetInfo>> {
+        let response_value = self
+            .info_value(Priority::Normal, json!({ "type": "spotMeta" }))
             .await?;
-        Ok(response.levels)
+
+        let assets = parse_spot_meta(&response_value)?;
+        let mut cache = self.asset_info.write().await;
+        for asset in &assets {
+            cache.insert(asset.name.clone(), asset.clone());
+            cache.insert(
+                format!("@{}", asset.index - SPOT_ASSET_OFFSET),
+                asset.clone(),
+            );
+        }
+        Ok(assets)
+    }
+
+    pub async fn get_orderbook(&self, symbol: &str) -> Result<L2Snapshot> {
+        self.orderbook(symbol, Priority::Normal).await
+    }
+
+    async fn orderbook(&self, symbol: &str, priority: Priority) -> Result<L2Snapshot> {
+        self.post_info(priority, json!({ "type": "l2Book", "coin": symbol }))
+            .await
     }
 
     pub async fn get_recent_trades(&self, symbol: &str) -> Result<Vec<Trade>> {
-        let url = format!("{}/info", self.base_url);
         let response: TradesResponse = self
-            .client
-            .post(&url)
-            .json(&json!({ "type": "recentTrades", "coin": symbol }))
-            .send()
-            .await?
-            .json()
+            .post_info(
+                Priority::Low,
+                json!({ "type": "recentTrades", "coin": symbol }),
+            )
             .await?;
         Ok(response.trades)
     }
@@ -149,86 +225,190 @@ impl HyperliquidConnector {
     // ACCOUNT & ALLIANZ-STABILITY
     // ====================================================================
 
-    /// Robuste Equity-Abfrage für Master-Account und Agenten
-    pub async fn get_user_state(&self, address: &str) -> Result<UserState> {
-        let url = format!("{}/info", self.base_url);
-        let response_value: Value = self
-            .client
-            .post(&url)
-            .json(&json!({ "type": "clearinghouseState", "user": address }))
-            .send()
-            .await?
-            .json()
-            .await?;
+    /// Perp-Margin-Status eines Accounts (Positionen, Hebel, Liquidationspreise)
+    pub async fn get_clearinghouse_state(&self, user: &str) -> Result<ClearinghouseState> {
+        self.clearinghouse_state(user, Priority::Normal).await
+    }
 
-        // Suche nach Equity in verschiedenen möglichen JSON-Pfaden (API-Resilienz)
-        let equity_str = response_value["withdrawableEquity"]
-            .as_str()
-            .or_else(|| response_value["marginSummary"]["withdrawableEquity"].as_str())
-            .or_else(|| response_value["marginSummary"]["accountValue"].as_str())
-            .unwrap_or("0");
+    async fn clearinghouse_state(
+        &self,
+        user: &str,
+        priority: Priority,
+    ) -> Result<ClearinghouseState> {
+        self.post_info(
+            priority,
+            json!({ "type": "clearinghouseState", "user": user }),
+        )
+        .await
+    }
 
-        let withdrawable = Decimal::from_str(equity_str).unwrap_or(Decimal::ZERO);
+    /// Spot-Balances eines Accounts
+    pub async fn get_spot_clearinghouse_state(&self, user: &str) -> Result<SpotClearinghouseState> {
+        self.post_info(
+            Priority::Normal,
+            json!({ "type": "spotClearinghouseState", "user": user }),
+        )
+        .await
+    }
 
+    /// Kurzform für Equity-Anzeigen (Master-Account und Agenten); niedrige Priorität,
+    /// bei knappem Budget wird die Abfrage verworfen (RateLimited)
+    pub async fn get_user_state(&self, address: &str) -> Result<UserState> {
+        let state = self.clearinghouse_state(address, Priority::Low).await?;
         Ok(UserState {
-            withdrawable_equity: withdrawable,
+            withdrawable_equity: state.withdrawable(),
+            account_value: state.account_value(),
         })
     }
 
-    pub async fn get_account_state(&self) -> Result<AccountState> {
-        let url = format!("{}/info", self.base_url);
-        // Wir nutzen hier Value, um flexibel auf die Antwort zu reagieren
-        let response: Value = self
-            .client
-            .post(&url)
-            .json(&json!({ "type": "clearinghouseState", "user": self.wallet.address }))
-            .send()
-            .await?
-            .json()
-            .await?;
-
-        // Mapping auf die AccountState Struktur
-        let state_val = if response["assetPositions"].is_array() {
-            response.clone()
-        } else {
-            response["state"].clone()
-        };
-
-        serde_json::from_value(state_val).context("Fehler beim Parsen des AccountState")
+    pub async fn get_account_state(&self) -> Result<ClearinghouseState> {
+        self.get_clearinghouse_state(&self.account).await
     }
 
     pub async fn get_balance(&self, asset: &str) -> Result<Decimal> {
-        let state = self.get_account_state().await?;
-        for balance in state.balances {
-            if balance.coin == asset {
-                return Decimal::from_str(&balance.total).context("Balance-Parsing fehlgeschlagen");
-            }
-        }
-        Ok(Decimal::ZERO)
+        let state = self.get_spot_clearinghouse_state(&self.account).await?;
+        Ok(state.balance(asset))
     }
 
     pub async fn get_all_balances(&self) -> Result<HashMap<String, Decimal>> {
-        let state = self.get_account_state().await?;
-        let mut balances = HashMap::new();
-        for balance in state.balances {
-            if let Ok(amount) = Decimal::from_str(&balance.total) {
-                if amount > Decimal::ZERO {
-                    balances.insert(balance.coin, amount);
-                }
-            }
-        }
-        Ok(balances)
+        let state = self.get_spot_clearinghouse_state(&self.account).await?;
+        Ok(state
+            .balances
+            .into_iter()
+            .filter(|b| b.total() > Decimal::ZERO)
+            .map(|b| (b.coin.clone(), b.total()))
+            .collect())
     }
 
+    /// Offene Perp-Positionen (Größe != 0)
     pub async fn get_open_positions(&self) -> Result<Vec<Position>> {
         let state = self.get_account_state().await?;
-        Ok(state.asset_positions)
+        Ok(state
+            .asset_positions
+            .into_iter()
+            .filter(|p| !p.position.size().is_zero())
+            .collect())
+    }
+
+    // ====================================================================
+    // ORDERS & FILLS (Info-Queries)
+    // ====================================================================
+    // `user` ist die Account-Adresse - bei Agent-Wallets die Master-Adresse (account_address()),
+    // nicht self.address()
+
+    /// Status einer Order per oid oder cloid; None, wenn die Order unbekannt ist
+    pub async fn order_status(
+        &self,
+        user: &str,
+        oid: &OrderRef,
+    ) -> Result<Option<OrderWithStatus>> {
+        let response: OrderStatusResponse = self
+            .post_info(
+                Priority::Normal,
+                json!({ "type": "orderStatus", "user": user, "oid": oid }),
+            )
+            .await?;
+        Ok(match response {
+            OrderStatusResponse::Order { order } => Some(*order),
+            OrderStatusResponse::UnknownOid => None,
+        })
+    }
+
+    pub async fn open_orders(&self, user: &str) -> Result<Vec<OpenOrder>> {
+        self.post_info(
+            Priority::Normal,
+            json!({ "type": "openOrders", "user": user }),
+        )
+        .await
+    }
+
+    /// Offene Orders inklusive Trigger-, TP/SL- und Reduce-Only-Informationen
+    pub async fn frontend_open_orders(&self, user: &str) -> Result<Vec<FrontendOrder>> {
+        self.post_info(
+            Priority::Normal,
+            json!({ "type": "frontendOpenOrders", "user": user }),
+        )
+        .await
+    }
+
+    /// Letzte Fills (max. 2000)
+    pub async fn user_fills(&self, user: &str) -> Result<Vec<Fill>> {
+        self.post_info(
+            Priority::Normal,
+            json!({ "type": "userFills", "user": user }),
+        )
+        .await
+    }
+
+    pub async fn user_fills_by_time(
+        &self,
+        user: &str,
+        start_ms: u64,
+        end_ms: Option<u64>,
+    ) -> Result<Vec<Fill>> {
+        self.post_info(
+            Priority::Normal,
+            json!({
+                "type": "userFillsByTime",
+                "user": user,
+                "startTime": start_ms,
+                "endTime": end_ms
+            }),
+        )
+        .await
+    }
+
+    pub async fn historical_orders(&self, user: &str) -> Result<Vec<OrderWithStatus>> {
+        self.post_info(
+            Priority::Low,
+            json!({ "type": "historicalOrders", "user": user }),
+        )
+        .await
+    }
+
+    pub async fn user_funding(
+        &self,
+        user: &str,
+        start_ms: u64,
+        end_ms: Option<u64>,
+    ) -> Result<Vec<FundingEvent>> {
+        self.post_info(
+            Priority::Low,
+            json!({
+                "type": "userFunding",
+                "user": user,
+                "startTime": start_ms,
+                "endTime": end_ms
+            }),
+        )
+        .await
+    }
+
+    /// Aktions-Budget der Adresse (unabhängig vom IP-Gewicht des RateLimiters)
+    pub async fn user_rate_limit(&self, user: &str) -> Result<UserRateLimit> {
+        self.post_info(
+            Priority::Low,
+            json!({ "type": "userRateLimit", "user": user }),
+        )
+        .await
+    }
+
+    /// Durchschnittlicher Fill-Preis und Größe einer Order aus den echten Fills
+    pub async fn order_fill(
+        &self,
+        user: &str,
+        oid: u64,
+        since_ms: u64,
+    ) -> Result<Option<(Decimal, Decimal)>> {
+        let fills = self.user_fills_by_time(user, since_ms, None).await?;
+        Ok(average_fill(&fills, oid))
     }
 
     // ====================================================================
     // TRADING
     // ====================================================================
 
+    /// "Market"-Order: IOC-Limit, deren Preis aus dem Orderbuch plus Slippage abgeleitet wird
     pub async fn place_market_order(
         &self,
         symbol: &str,
@@ -236,24 +416,63 @@ impl HyperliquidConnector {
         size: Decimal,
         _leverage: Option<u8>,
     ) -> Result<String> {
-        let asset_info = self.get_asset_info(symbol).await?;
-        let size_str = format_size(size, asset_info.sz_decimals);
-        let order = json!({
-            "type": "order",
-            "orders": [{
-                "a": asset_info.index, "b": is_buy, "p": "0", "s": size_str, "r": false,
-                "t": { "limit": { "tif": "Ioc" } }
-            }],
-            "grouping": "na"
-        });
-        let response = self.sign_and_send_action(order).await?;
-        let status = &response["response"]["data"]["statuses"][0];
+        self.market_open(symbol, is_buy, size, DEFAULT_SLIPPAGE)
+            .await
+    }
+
+    /// IOC-Order mit maximal `slippage` (0.01 = 1%) Abstand zum besten Gegenpreis
+    pub async fn market_open(
+        &self,
+        symbol: &str,
+        is_buy: bool,
+        size: Decimal,
+        slippage: Decimal,
+    ) -> Result<String> {
+        let limit_px = self.slippage_price(symbol, is_buy, slippage).await?;
+        let request = OrderRequest::limit(symbol, is_buy, size, limit_px, Tif::Ioc);
+        self.place_order(&request)
+            .await?
+            .order_id()
+            .ok_or_else(|| anyhow!("Market-Order nicht ausgeführt: {}", symbol))
+    }
+
+    /// Offene Position (ganz oder bis `size`) per Reduce-Only-IOC schließen
+    pub async fn market_close(
+        &self,
+        symbol: &str,
+        size: Option<Decimal>,
+        slippage: Decimal,
+    ) -> Result<String> {
+        let state = self
+            .clearinghouse_state(&self.account, Priority::Critical)
+            .await?;
+        let position = state
+            .position(symbol)
+            .filter(|p| !p.size().is_zero())
+            .ok_or_else(|| anyhow!("Keine offene Position für {}", symbol))?;
 
-        status["resting"]["oid"]
-            .as_u64()
-            .or_else(|| status["filled"]["oid"].as_u64())
-            .map(|id| id.to_string())
-            .ok_or_else(|| anyhow!("Order-ID konnte nicht extrahiert werden: {:?}", response))
+        let open = position.size().abs();
+        let size = size.map_or(open, |s| s.min(open));
+        let is_buy = !position.is_long();
+        let limit_px = self.slippage_price(symbol, is_buy, slippage).await?;
+        let request =
+            OrderRequest::limit(symbol, is_buy, size, limit_px, Tif::Ioc).reduce_only(true);
+        self.place_order(&request)
+            .await?
+            .order_id()
+            .ok_or_else(|| anyhow!("Close-Order nicht ausgeführt: {}", symbol))
+    }
+
+    /// Grenzpreis: bester Ask * (1 + slippage) bzw. bester Bid * (1 - slippage)
+    pub async fn slippage_price(
+        &self,
+        symbol: &str,
+        is_buy: bool,
+        slippage: Decimal,
+    ) -> Result<Decimal> {
+        OrderBook::from_snapshot(&self.orderbook(symbol, Priority::Critical).await?)?
+            .slippage_price(is_buy, slippage)
+            .ok_or_else(|| anyhow!("Leeres Orderbuch für {}", symbol))
     }
 
     pub async fn place_limit_order(
@@ -265,109 +484,392 @@ impl HyperliquidConnector {
         _leverage: Option<u8>,
         post_only: bool,
     ) -> Result<String> {
-        let asset_info = self.get_asset_info(symbol).await?;
-        let price_str = format_price(price, 6);
-        let size_str = format_size(size, asset_info.sz_decimals);
-        let order = json!({
-            "type": "order",
-            "orders": [{
-                "a": asset_info.index, "b": is_buy, "p": price_str, "s": size_str, "r": false,
-                "t": { "limit": { "tif": if post_only { "Alo" } else { "Gtc" } } }
-            }],
-            "grouping": "na"
-        });
-        let response = self.sign_and_send_action(order).await?;
-        let status = &response["response"]["data"]["statuses"][0];
+        let tif = if post_only { Tif::Alo } else { Tif::Gtc };
+        let request = OrderRequest::limit(symbol, is_buy, size, price, tif);
+        self.place_order(&request)
+            .await?
+            .order_id()
+            .ok_or_else(|| anyhow!("Limit-Order-ID Fehler: {}", symbol))
+    }
 
-        status["resting"]["oid"]
-            .as_u64()
-            .or_else(|| status["filled"]["oid"].as_u64())
-            .map(|id| id.to_string())
-            .ok_or_else(|| anyhow!("Limit-Order-ID Fehler: {:?}", response))
+    /// Stop-Market / Take-Profit (reduce only)
+    pub async fn place_trigger_order(
+        &self,
+        symbol: &str,
+        is_buy: bool,
+        size: Decimal,
+        trigger_px: Decimal,
+        tpsl: Tpsl,
+    ) -> Result<OrderStatus> {
+        // Grenzpreis für die Ausführung nach dem Trigger, analog zum SDK 10%
+        let limit_px = if is_buy {
+            trigger_px * (Decimal::ONE + TRIGGER_SLIPPAGE)
+        } else {
+            trigger_px * (Decimal::ONE - TRIGGER_SLIPPAGE)
+        };
+        let request = OrderRequest::trigger(symbol, is_buy, size, trigger_px, limit_px, tpsl, true);
+        self.place_order(&request).await
+    }
+
+    pub async fn place_order(&self, request: &OrderRequest) -> Result<OrderStatus> {
+        let mut statuses = self
+            .bulk_orders(std::slice::from_ref(request), Grouping::Na)
+            .await?;
+        statuses
+            .pop()
+            .ok_or_else(|| anyhow!("Keine Order-Status-Antwort"))
+    }
+
+    /// Mehrere Orders in einer Action; mit NormalTpsl/PositionTpsl als Entry + TP/SL-Gruppe
+    pub async fn bulk_orders(
+        &self,
+        requests: &[OrderRequest],
+        grouping: Grouping,
+    ) -> Result<Vec<OrderStatus>> {
+        let mut orders = Vec::with_capacity(requests.len());
+        for request in requests {
+            orders.push(self.order_wire(request).await?);
+        }
+        let response = self
+            .sign_and_send_action(Action::Order { orders, grouping })
+            .await?;
+        parse_statuses(&response)
+    }
+
+    /// Bestehende Order (per oid oder cloid) ersetzen
+    pub async fn modify_order(&self, oid: OrderRef, request: &OrderRequest) -> Result<OrderStatus> {
+        let order = self.order_wire(request).await?;
+        let response = self
+            .sign_and_send_action(Action::Modify { oid, order })
+            .await?;
+        // Einzel-Modify antwortet ohne Statusliste: {"status":"ok","response":{"type":"default"}}
+        if response["status"] == "ok" && response["response"]["type"] == "default" {
+            return Ok(OrderStatus::Success);
+        }
+        parse_statuses(&response)?
+            .pop()
+            .ok_or_else(|| anyhow!("Keine Modify-Status-Antwort"))
+    }
+
+    pub async fn batch_modify(
+        &self,
+        modifies: &[(OrderRef, OrderRequest)],
+    ) -> Result<Vec<OrderStatus>> {
+        let mut wires = Vec::with_capacity(modifies.len());
+        for (oid, request) in modifies {
+            wires.push(ModifyWire {
+                oid: oid.clone(),
+                order: self.order_wire(request).await?,
+            });
+        }
+        let response = self
+            .sign_and_send_action(Action::BatchModify { modifies: wires })
+            .await?;
+        parse_statuses(&response)
     }
 
     pub async fn cancel_order(&self, symbol: &str, order_id: &str) -> Result<()> {
         let asset_info = self.get_asset_info(symbol).await?;
-        let cancel = json!({
-            "type": "cancel",
-            "cancels": [{ "a": asset_info.index, "o": order_id.parse::<u64>().unwrap_or(0) }]
-        });
+        let cancel = Action::Cancel {
+            cancels: vec![CancelWire {
+                a: asset_info.index,
+                o: order_id.parse::<u64>().unwrap_or(0),
+            }],
+        };
         self.sign_and_send_action(cancel).await?;
         Ok(())
     }
 
-    pub async fn cancel_all_orders(&self, symbol: &str) -> Result<()> {
+    pub async fn cancel_by_cloid(&self, symbol: &str, cloid: &Cloid) -> Result<()> {
         let asset_info = self.get_asset_info(symbol).await?;
-        let cancel = json!({
-            "type": "cancelByCloid",
-            "cancels": [{ "asset": asset_info.index, "cloid": "0x0" }]
-        });
+        let cancel = Action::CancelByCloid {
+            cancels: vec![CancelByCloidWire {
+                asset: asset_info.index,
+                cloid: cloid.to_string(),
+            }],
+        };
         self.sign_and_send_action(cancel).await?;
         Ok(())
     }
 
+    /// Alle offenen Orders eines Symbols per Bulk-Cancel (oid); Anzahl der gesendeten Cancels.
+    /// Hyperliquid kennt kein "cancel all" - die Orders kommen aus openOrders des Accounts.
+    pub async fn cancel_all_orders(&self, symbol: &str) -> Result<usize> {
+        let oids: Vec<u64> = self
+            .open_orders(&self.account)
+            .await?
+            .into_iter()
+            .filter(|o| o.coin == symbol)
+            .map(|o| o.oid)
+            .collect();
+        if oids.is_empty() {
+            return Ok(0);
+        }
+
+        let asset_info = self.get_asset_info(symbol).await?;
+        let cancel = Action::Cancel {
+            cancels: oids
+                .iter()
+                .map(|&o| CancelWire {
+                    a: asset_info.index,
+                    o,
+                })
+                .collect(),
+        };
+        self.sign_and_send_action(cancel).await?;
+        Ok(oids.len())
+    }
+
     pub async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<()> {
         let asset_info = self.get_asset_info(symbol).await?;
-        let update = json!({
-            "type": "updateLeverage",
-            "asset": asset_info.index,
-            "isCross": true,
-            "leverage": leverage
-        });
+        let update = Action::UpdateLeverage {
+            asset: asset_info.index,
+            is_cross: true,
+            leverage: leverage as u32,
+        };
         self.sign_and_send_action(update).await?;
         Ok(())
     }
 
+    // ====================================================================
+    // USER-SIGNED ACTIONS (Agent, Transfers)
+    // ====================================================================
+
+    /// Agent-Wallet (API-Key) für diesen Account freigeben - muss mit dem Master-Key signiert werden
+    pub async fn approve_agent(
+        &self,
+        agent_address: &str,
+        agent_name: Option<&str>,
+    ) -> Result<Value> {
+        let action = UserSignedAction::approve_agent(
+            agent_address,
+            agent_name,
+            self.nonces.next()?,
+            !self.is_testnet,
+        );
+        self.sign_and_send_user_action(action).await
+    }
+
+    /// USDC an eine andere Hyperliquid-Adresse senden
+    pub async fn usd_send(&self, destination: &str, amount: Decimal) -> Result<Value> {
+        let action = UserSignedAction::usd_send(
+            destination,
+            &amount.normalize().to_string(),
+            self.nonces.next()?,
+            !self.is_testnet,
+        );
+        self.sign_and_send_user_action(action).await
+    }
+
+    /// USDC über die Bridge nach Arbitrum abheben
+    pub async fn withdraw(&self, destination: &str, amount: Decimal) -> Result<Value> {
+        let action = UserSignedAction::withdraw(
+            destination,
+            &amount.normalize().to_string(),
+            self.nonces.next()?,
+            !self.is_testnet,
+        );
+        self.sign_and_send_user_action(action).await
+    }
+
+    /// USDC zwischen Spot- und Perp-Konto verschieben
+    pub async fn usd_class_transfer(&self, amount: Decimal, to_perp: bool) -> Result<Value> {
+        let action = UserSignedAction::usd_class_transfer(
+            &amount.normalize().to_string(),
+            to_perp,
+            self.nonces.next()?,
+            !self.is_testnet,
+        );
+        self.sign_and_send_user_action(action).await
+    }
+
     // ====================================================================
     // INTERNAL
     // ====================================================================
 
-    async fn sign_and_send_action(&self, action: Value) -> Result<Value> {
-        let typed_data = self.build_typed_data(action.clone())?;
-        let signature = self.wallet.sign_typed_data(&typed_data)?;
-        let url = format!("{}/exchange", self.base_url);
-        let response: Value = self
-            .client
-            .post(&url)
-            .json(&json!({
+    /// Rundet Preise und Größen auf die Asset-Auflösung und baut die Wire-Order
+    async fn order_wire(&self, request: &OrderRequest) -> Result<OrderWire> {
+        let asset_info = self.get_asset_info(&request.coin).await?;
+        // Reduce-Only darf Restpositionen unter dem Mindestwert schließen
+        let normalized = asset_info
+            .filters()
+            .normalize_order(request)
+            .map_err(ExchangeError::from)?;
+        Ok(normalized.to_wire(asset_info.index))
+    }
+
+    async fn sign_and_send_action(&self, action: Action) -> Result<Value> {
+        // Derselbe Nonce geht in den Hash und in den Request
+        let nonce = self.nonces.next()?;
+        let signature = self
+            .wallet
+            .sign_l1_action(&action, None, nonce, None, !self.is_testnet)?;
+        let weight = exchange_weight(action.batch_len());
+        self.post_exchange(
+            json!({
                 "action": action,
-                "nonce": chrono::Utc::now().timestamp_millis(),
+                "nonce": nonce,
                 "signature": signature,
                 "vaultAddress": null
-            }))
-            .send()
+            }),
+            weight,
+        )
+        .await
+    }
+
+    async fn sign_and_send_user_action(&self, action: UserSignedAction) -> Result<Value> {
+        // Nonce steckt bei user-signed Actions in der Action selbst (time / nonce)
+        let signature = self.wallet.sign_user_signed_action(&action)?;
+        self.post_exchange(
+            json!({
+                "action": action,
+                "nonce": action.nonce(),
+                "signature": signature
+            }),
+            exchange_weight(1),
+        )
+        .await
+    }
+
+    async fn post_info<T: DeserializeOwned>(&self, priority: Priority, body: Value) -> Result<T> {
+        let kind = body["type"].clone();
+        let response = self.info_value(priority, body).await?;
+        serde_json::from_value(response)
+            .map_err(|e| ExchangeError::ParseError(format!("{}: {}", kind, e)).into())
+    }
+
+    /// POST /info nach Abzug des Request-Gewichts; lange Listen kosten nachträglich extra
+    async fn info_value(&self, priority: Priority, body: Value) -> Result<Value> {
+        let kind = body["type"].as_str().unwrap_or_default().to_string();
+        self.limiter.acquire(info_weight(&kind), priority).await?;
+
+        let url = format!("{}/info", self.base_url);
+        let response: Value = self
+            .send_checked(&url, &body)
             .await?
             .json()
-            .await?;
+            .await
+            .map_err(ExchangeError::from)?;
+        if let Some(items) = response.as_array() {
+            self.limiter.charge(response_weight(&kind, items.len()));
+        }
+        Ok(response)
+    }
 
+    /// POST mit typisiertem HTTP-Fehler; ein 429 leert zusätzlich den lokalen Bucket
+    async fn send_checked(
+        &self,
+        url: &str,
+        body: &Value,
+    ) -> Result<reqwest::Response, ExchangeError> {
+        let response = self.client.post(url).json(body).send().await?;
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            self.limiter.throttle();
+        }
+        Ok(response.error_for_status()?)
+    }
+
+    /// Actions laufen immer mit höchster Priorität - Info-Polling kann sie nicht verdrängen
+    async fn post_exchange(&self, payload: Value, weight: u32) -> Result<Value> {
+        self.limiter.acquire(weight, Priority::Critical).await?;
+
+        let url = format!("{}/exchange", self.base_url);
+        let response: Value = self
+            .send_checked(&url, &payload)
+            .await?
+            .json()
+            .await
+            .map_err(ExchangeError::from)?;
+
+        // Ablehnung der gesamten Action: {"status": "err", "response": "..."}
+        if response["status"] == "err" {
+            let message = response["response"].as_str().unwrap_or_default();
+            return Err(ExchangeError::from_reject(message).into());
+        }
         if let Some(error) = response.get("error") {
-            return Err(anyhow!("API Fehler: {}", error));
+            return Err(ExchangeError::from_reject(&error.to_string()).into());
         }
         Ok(response)
     }
+}
 
-    fn build_typed_data(&self, action: Value) -> Result<TypedData> {
-        let domain = EIP712Domain {
-            name: "Exchange".to_string(),
-            version: "1".to_string(),
-            chain_id: if self.is_testnet { 421614 } else { 42161 },
-            verifying_contract: "0x0000000000000000000000000000000000000000".to_string(),
-        };
-        let types = json!({
-            "EIP712Domain": [
-                { "name": "name", "type": "string" }, { "name": "version", "type": "string" },
-                { "name": "chainId", "type": "uint256" }, { "name": "verifyingContract", "type": "address" }
-            ],
-            "Agent": [
-                { "name": "source", "type": "string" }, { "name": "connectionId", "type": "bytes32" }
-            ]
-        });
-        Ok(TypedData {
-            domain,
-            primary_type: "Agent".to_string(),
-            types,
-            message: action,
-        })
+// ====================================================================
+// VENUE-TRAITS
+// ====================================================================
+
+#[async_trait]
+impl MarketDataStream for HyperliquidConnector {
+    async fn orderbook(&self, symbol: &str) -> Result<OrderBook> {
+        Ok(OrderBook::from_snapshot(
+            &self.get_orderbook(symbol).await?,
+        )?)
+    }
+
+    /// Eigener Websocket (Reconnect und Abo-Replay inklusive) je Aufruf
+    async fn subscribe_books(&self, symbols: &[String]) -> Result<mpsc::Receiver<OrderBook>> {
+        let ws = HyperliquidWs::new(self.is_testnet).await?;
+        for symbol in symbols {
+            ws.subscribe_l2(symbol).await?;
+        }
+        Ok(ws.into_books())
+    }
+}
+
+#[async_trait]
+impl AccountQueries for HyperliquidConnector {
+    fn address(&self) -> &str {
+        &self.wallet.address
+    }
+
+    fn account_address(&self) -> &str {
+        &self.account
+    }
+
+    async fn account_state(&self, user: &str) -> Result<ClearinghouseState> {
+        self.get_clearinghouse_state(user).await
+    }
+
+    async fn open_orders(&self, user: &str) -> Result<Vec<OpenOrder>> {
+        HyperliquidConnector::open_orders(self, user).await
+    }
+
+    async fn user_fills(&self, user: &str) -> Result<Vec<Fill>> {
+        HyperliquidConnector::user_fills(self, user).await
+    }
+
+    async fn account_value(&self, user: &str) -> Result<Decimal> {
+        Ok(self.get_user_state(user).await?.account_value)
+    }
+}
+
+#[async_trait]
+impl OrderExecution for HyperliquidConnector {
+    async fn place_order(&self, request: &OrderRequest) -> Result<OrderStatus> {
+        HyperliquidConnector::place_order(self, request).await
+    }
+
+    async fn cancel_order(&self, symbol: &str, oid: u64) -> Result<()> {
+        HyperliquidConnector::cancel_order(self, symbol, &oid.to_string()).await
+    }
+
+    // Buch und Position mit Critical-Priorität statt über die Trait-Defaults
+    async fn market_open(
+        &self,
+        symbol: &str,
+        is_buy: bool,
+        size: Decimal,
+        slippage: Decimal,
+    ) -> Result<String> {
+        HyperliquidConnector::market_open(self, symbol, is_buy, size, slippage).await
+    }
+
+    async fn market_close(
+        &self,
+        symbol: &str,
+        size: Option<Decimal>,
+        slippage: Decimal,
+    ) -> Result<String> {
+        HyperliquidConnector::market_close(self, symbol, size, slippage).await
     }
 }
 
@@ -378,25 +880,28 @@ impl HyperliquidConnector {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserState {
     pub withdrawable_equity: Decimal,
+    pub account_value: Decimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssetInfo {
     pub name: String,
+    /// Asset-ID für Orders (Perp: Position im Universe, Spot: 10000 + Index)
     pub index: u32,
     #[serde(rename = "szDecimals")]
     pub sz_decimals: u8,
+    #[serde(default)]
+    pub is_spot: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Orderbook {
-    pub bids: Vec<[String; 2]>,
-    pub asks: Vec<[String; 2]>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct OrderbookResponse {
-    levels: Orderbook,
+impl AssetInfo {
+    pub fn filters(&self) -> ExchangeFilters {
+        if self.is_spot {
+            ExchangeFilters::spot(self.sz_decimals as u32)
+        } else {
+            ExchangeFilters::perp(self.sz_decimals as u32)
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -412,44 +917,167 @@ struct TradesResponse {
     trades: Vec<Trade>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AccountState {
-    pub balances: Vec<Balance>,
-    #[serde(rename = "withdrawableEquity", default)]
-    pub withdrawable_equity: String,
-    #[serde(rename = "assetPositions", default)]
-    pub asset_positions: Vec<Position>,
+/// Status einer einzelnen Order aus `response.data.statuses`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderStatus {
+    Resting(RestingOrder),
+    Filled(FilledOrder),
+    Error(String),
+    Success,
+    WaitingForFill,
+    WaitingForTrigger,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct AccountStateResponse {
-    state: AccountState,
+impl OrderStatus {
+    /// Ablehnung als typisierter Fehler
+    pub fn error(&self) -> Option<ExchangeError> {
+        match self {
+            OrderStatus::Error(message) => Some(ExchangeError::from_reject(message)),
+            _ => None,
+        }
+    }
+
+    pub fn order_id(&self) -> Option<String> {
+        match self {
+            OrderStatus::Resting(r) => Some(r.oid.to_string()),
+            OrderStatus::Filled(f) => Some(f.oid.to_string()),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Balance {
-    pub coin: String,
-    pub total: String,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestingOrder {
+    pub oid: u64,
+    #[serde(default)]
+    pub cloid: Option<Cloid>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Position {
-    pub position: PositionData,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilledOrder {
+    pub oid: u64,
+    pub total_sz: String,
+    pub avg_px: String,
+    #[serde(default)]
+    pub cloid: Option<Cloid>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PositionData {
-    pub coin: String,
-    pub szi: String,
-    #[serde(rename = "entryPx")]
-    pub entry_px: String,
-    #[serde(rename = "unrealizedPnl")]
-    pub unrealized_pnl: String,
+/// REST-Basis-URL des Netzes; HL_API_URL überschreibt sie (z.B. MockExchange)
+pub(crate) fn api_url(is_testnet: bool) -> String {
+    match std::env::var("HL_API_URL") {
+        Ok(url) if !url.is_empty() => url,
+        _ if is_testnet => TESTNET_API.to_string(),
+        _ => MAINNET_API.to_string(),
+    }
 }
 
-fn format_price(price: Decimal, decimals: u8) -> String {
-    format!("{:.1$}", price, decimals as usize)
+/// Spot-Asset-IDs beginnen bei 10000
+const SPOT_ASSET_OFFSET: u32 = 10_000;
+
+fn parse_spot_meta(meta: &Value) -> Result<Vec<AssetInfo>> {
+    let tokens = meta["tokens"]
+        .as_array()
+        .ok_or_else(|| anyhow!("Tokens-Feld in spotMeta fehlt"))?;
+    let universe = meta["universe"]
+        .as_array()
+        .ok_or_else(|| anyhow!("Universe-Feld in spotMeta fehlt"))?;
+
+    let sz_decimals_of = |token_idx: u64| {
+        tokens
+            .iter()
+            .find(|t| t["index"].as_u64() == Some(token_idx))
+            .and_then(|t| t["szDecimals"].as_u64())
+    };
+
+    let mut assets = Vec::new();
+    for pair in universe {
+        let (Some(index), Some(base)) = (pair["index"].as_u64(), pair["tokens"][0].as_u64()) else {
+            continue;
+        };
+        assets.push(AssetInfo {
+            name: pair["name"].as_str().unwrap_or("UNKNOWN").to_string(),
+            index: SPOT_ASSET_OFFSET + index as u32,
+            sz_decimals: sz_decimals_of(base).unwrap_or(0) as u8,
+            is_spot: true,
+        });
+    }
+    Ok(assets)
 }
-fn format_size(size: Decimal, decimals: u8) -> String {
-    format!("{:.1$}", size, decimals as usize)
+
+/// Standard-Slippage der Market-Order (5%, wie im Python SDK)
+pub const DEFAULT_SLIPPAGE: Decimal = Decimal::from_parts(5, 0, 0, false, 2);
+/// Slippage-Grenze der Trigger-Market-Orders (10%)
+const TRIGGER_SLIPPAGE: Decimal = Decimal::from_parts(10, 0, 0, false, 2);
+
+fn parse_statuses(response: &Value) -> Result<Vec<OrderStatus>> {
+    let statuses = &response["response"]["data"]["statuses"];
+    if statuses.is_null() {
+        return Err(anyhow!("Unerwartete Order-Antwort: {}", response));
+    }
+    let statuses: Vec<OrderStatus> =
+        serde_json::from_value(statuses.clone()).context("Order-Status nicht lesbar")?;
+    // Einzelorder: Ablehnung als typisierter Fehler (per downcast_ref::<ExchangeError>)
+    if let [status @ OrderStatus::Error(_)] = statuses.as_slice() {
+        return Err(status
+            .error()
+            .unwrap_or_else(|| ExchangeError::Rejected(String::new()))
+            .into());
+    }
+    Ok(statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_statuses() {
+        let response = json!({
+            "status": "ok",
+            "response": { "type": "order", "data": { "statuses": [
+                { "resting": { "oid": 77738308 } },
+                { "filled": { "totalSz": "0.02", "avgPx": "1891.4", "oid": 77747314 } },
+                "waitingForTrigger"
+            ] } }
+        });
+        let statuses = parse_statuses(&response).unwrap();
+        assert_eq!(statuses[0].order_id().as_deref(), Some("77738308"));
+        assert_eq!(statuses[1].order_id().as_deref(), Some("77747314"));
+        assert_eq!(statuses[2], OrderStatus::WaitingForTrigger);
+
+        let rejected = json!({
+            "status": "ok",
+            "response": { "type": "order", "data": { "statuses": [
+                { "error": "Order must have minimum value of $10." }
+            ] } }
+        });
+        let err = parse_statuses(&rejected).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ExchangeError>(),
+            Some(ExchangeError::BelowMinNotional(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_spot_meta() {
+        let meta = json!({
+            "tokens": [
+                { "name": "USDC", "szDecimals": 8, "weiDecimals": 8, "index": 0 },
+                { "name": "PURR", "szDecimals": 0, "weiDecimals": 5, "index": 1 },
+                { "name": "HFUN", "szDecimals": 2, "weiDecimals": 8, "index": 2 }
+            ],
+            "universe": [
+                { "name": "PURR/USDC", "tokens": [1, 0], "index": 0 },
+                { "name": "@1", "tokens": [2, 0], "index": 1 }
+            ]
+        });
+        let assets = parse_spot_meta(&meta).unwrap();
+        assert_eq!(assets[0].name, "PURR/USDC");
+        assert_eq!(assets[0].index, 10_000);
+        assert_eq!(assets[1].index, 10_001);
+        assert_eq!(assets[1].sz_decimals, 2);
+        assert_eq!(assets[1].filters().price_decimals(), 6);
+    }
 }
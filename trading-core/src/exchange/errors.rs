@@ -2,8 +2,10 @@
 
 use thiserror::Error;
 
+use super::filters::FilterError;
+
 /// Error types for exchange operations
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq)]
 pub enum ExchangeError {
     #[error("Network error: {0}")]
     NetworkError(String),
@@ -16,6 +18,107 @@ pub enum ExchangeError {
 
     #[error("Data parsing error: {0}")]
     ParseError(String),
+
+    // ====
+    // API rejects (parsed from `statuses[].error` / `{"status": "err"}`)
+    // ====
+    #[error("Insufficient margin: {0}")]
+    InsufficientMargin(String),
+
+    #[error("Invalid tick or lot size: {0}")]
+    InvalidTickOrLot(String),
+
+    #[error("Below minimum order value: {0}")]
+    BelowMinNotional(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Unknown asset: {0}")]
+    UnknownAsset(String),
+
+    #[error("Post-only order would cross: {0}")]
+    PostOnlyWouldCross(String),
+
+    #[error("Reduce-only rejected: {0}")]
+    ReduceOnlyRejected(String),
+
+    #[error("IOC order not filled: {0}")]
+    IocNotFilled(String),
+
+    #[error("Invalid nonce: {0}")]
+    InvalidNonce(String),
+
+    #[error("Order not found: {0}")]
+    OrderNotFound(String),
+
+    #[error("Order rejected: {0}")]
+    Rejected(String),
+
+    /// Rejected locally before sending (see exchange::filters)
+    #[error("Order filter: {0}")]
+    Filter(#[from] FilterError),
+}
+
+impl ExchangeError {
+    /// Classify a reject message of the /exchange endpoint
+    pub fn from_reject(message: &str) -> Self {
+        let msg = message.to_string();
+        let lower = message.to_lowercase();
+        let has = |needle: &str| lower.contains(needle);
+
+        if has("insufficient margin") || has("insufficient spot balance") {
+            ExchangeError::InsufficientMargin(msg)
+        } else if has("post only") {
+            ExchangeError::PostOnlyWouldCross(msg)
+        } else if has("reduce only") {
+            ExchangeError::ReduceOnlyRejected(msg)
+        } else if has("minimum value") {
+            ExchangeError::BelowMinNotional(msg)
+        } else if has("tick size")
+            || has("invalid price")
+            || has("invalid size")
+            || has("zero size")
+        {
+            ExchangeError::InvalidTickOrLot(msg)
+        } else if has("could not immediately match") {
+            ExchangeError::IocNotFilled(msg)
+        } else if has("rate limit") || has("too many") {
+            ExchangeError::RateLimited(msg)
+        } else if has("nonce") {
+            ExchangeError::InvalidNonce(msg)
+        } else if has("unknown asset") || has("asset not found") || has("invalid asset") {
+            ExchangeError::UnknownAsset(msg)
+        } else if has("never placed") || has("canceled or filled") {
+            ExchangeError::OrderNotFound(msg)
+        } else {
+            ExchangeError::Rejected(msg)
+        }
+    }
+
+    /// Sending the same request again may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ExchangeError::NetworkError(_)
+                | ExchangeError::RateLimited(_)
+                | ExchangeError::InvalidNonce(_)
+                | ExchangeError::IocNotFilled(_)
+        )
+    }
+
+    /// A new entry on this asset will be rejected again until the account
+    /// or the order sizing changes
+    pub fn blocks_reentry(&self) -> bool {
+        matches!(
+            self,
+            ExchangeError::InsufficientMargin(_)
+                | ExchangeError::UnknownAsset(_)
+                | ExchangeError::BelowMinNotional(_)
+                | ExchangeError::InvalidTickOrLot(_)
+                | ExchangeError::Filter(_)
+        )
+    }
 }
 
 // Convert from common error types
@@ -30,3 +133,71 @@ impl From<tokio_tungstenite::tungstenite::Error> for ExchangeError {
         ExchangeError::WebSocketError(err.to_string())
     }
 }
+
+impl From<reqwest::Error> for ExchangeError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+            ExchangeError::RateLimited(err.to_string())
+        } else {
+            ExchangeError::NetworkError(err.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reject_classification() {
+        let cases = [
+            (
+                "Insufficient margin to place order. asset=4",
+                ExchangeError::InsufficientMargin(String::new()),
+            ),
+            (
+                "Post only order would have immediately matched, bbo was 1891.4@1891.5. asset=4",
+                ExchangeError::PostOnlyWouldCross(String::new()),
+            ),
+            (
+                "Reduce only order would increase position. asset=4",
+                ExchangeError::ReduceOnlyRejected(String::new()),
+            ),
+            (
+                "Order must have minimum value of $10. asset=4",
+                ExchangeError::BelowMinNotional(String::new()),
+            ),
+            (
+                "Price must be divisible by tick size. asset=4",
+                ExchangeError::InvalidTickOrLot(String::new()),
+            ),
+            (
+                "Order could not immediately match against any resting orders. asset=4",
+                ExchangeError::IocNotFilled(String::new()),
+            ),
+            (
+                "Invalid nonce: duplicate nonce",
+                ExchangeError::InvalidNonce(String::new()),
+            ),
+            (
+                "Order was never placed, already canceled, or filled. asset=4",
+                ExchangeError::OrderNotFound(String::new()),
+            ),
+            ("Something new", ExchangeError::Rejected(String::new())),
+        ];
+
+        for (message, expected) in cases {
+            let parsed = ExchangeError::from_reject(message);
+            assert_eq!(
+                std::mem::discriminant(&parsed),
+                std::mem::discriminant(&expected),
+                "{}",
+                message
+            );
+            assert!(parsed.to_string().contains(message));
+        }
+
+        assert!(ExchangeError::from_reject("Insufficient margin to place order.").blocks_reentry());
+        assert!(ExchangeError::from_reject("Too many cumulative requests sent").is_retryable());
+    }
+}
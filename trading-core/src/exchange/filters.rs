@@ -1,28 +1,195 @@
 // File: src/exchange/filters.rs
-// Placeholder for exchange-specific filters (e.g., price filters, lot size filters)
-// To be implemented when order execution is needed
+// Hyperliquid order filters: tick and lot size rules per asset
+//
+// Prices: at most 5 significant figures and MAX_DECIMALS - szDecimals decimals
+// (integer prices are always valid). Sizes: szDecimals decimals.
+// Orders below the minimum notional are rejected by the exchange.
 
 use rust_decimal::Decimal;
+use thiserror::Error;
 
-#[derive(Debug, Clone)]
+use super::actions::{OrderKind, OrderRequest};
+
+/// Max price decimals of perp assets before subtracting szDecimals
+pub const MAX_DECIMALS_PERP: u32 = 6;
+/// Max price decimals of spot assets before subtracting szDecimals
+pub const MAX_DECIMALS_SPOT: u32 = 8;
+/// Max significant figures of a non-integer price
+pub const MAX_SIG_FIGS: u32 = 5;
+/// Minimum order value in USDC
+pub const MIN_NOTIONAL: Decimal = Decimal::from_parts(10, 0, 0, false, 0);
+
+/// Order that cannot be represented on the exchange
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum FilterError {
+    #[error("Price must be positive: {0}")]
+    NonPositivePrice(Decimal),
+
+    #[error("Price {price} rounds to zero with {decimals} price decimals")]
+    PriceRoundsToZero { price: Decimal, decimals: u32 },
+
+    #[error("Size must be positive: {0}")]
+    NonPositiveSize(Decimal),
+
+    #[error("Size {size} rounds to zero with {sz_decimals} size decimals")]
+    SizeRoundsToZero { size: Decimal, sz_decimals: u32 },
+
+    #[error("Order value {notional} below minimum of {min}")]
+    BelowMinNotional { notional: Decimal, min: Decimal },
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct ExchangeFilters {
-    pub min_price: Option<Decimal>,
-    pub max_price: Option<Decimal>,
-    pub tick_size: Option<Decimal>,
-    pub min_qty: Option<Decimal>,
-    pub max_qty: Option<Decimal>,
-    pub step_size: Option<Decimal>,
+    /// Size decimals of the asset (`szDecimals` from meta / spotMeta)
+    pub sz_decimals: u32,
+    /// MAX_DECIMALS_PERP or MAX_DECIMALS_SPOT
+    pub max_decimals: u32,
+    /// Minimum order value in quote currency
+    pub min_notional: Decimal,
 }
 
 impl Default for ExchangeFilters {
     fn default() -> Self {
+        Self::perp(0)
+    }
+}
+
+impl ExchangeFilters {
+    pub fn perp(sz_decimals: u32) -> Self {
         Self {
-            min_price: None,
-            max_price: None,
-            tick_size: None,
-            min_qty: None,
-            max_qty: None,
-            step_size: None,
+            sz_decimals,
+            max_decimals: MAX_DECIMALS_PERP,
+            min_notional: MIN_NOTIONAL,
+        }
+    }
+
+    pub fn spot(sz_decimals: u32) -> Self {
+        Self {
+            sz_decimals,
+            max_decimals: MAX_DECIMALS_SPOT,
+            min_notional: MIN_NOTIONAL,
+        }
+    }
+
+    /// Allowed price decimals: max_decimals - szDecimals
+    pub fn price_decimals(&self) -> u32 {
+        self.max_decimals.saturating_sub(self.sz_decimals)
+    }
+
+    /// Round a price to 5 significant figures and the allowed decimals
+    pub fn normalize_price(&self, price: Decimal) -> Result<Decimal, FilterError> {
+        if price <= Decimal::ZERO {
+            return Err(FilterError::NonPositivePrice(price));
+        }
+
+        let rounded = if price.fract().is_zero() {
+            price
+        } else {
+            // Rounding to significant figures can only fail on overflow
+            price.round_sf(MAX_SIG_FIGS).unwrap_or(price)
+        };
+        let rounded = rounded.round_dp(self.price_decimals()).normalize();
+
+        if rounded.is_zero() {
+            return Err(FilterError::PriceRoundsToZero {
+                price,
+                decimals: self.price_decimals(),
+            });
         }
+        Ok(rounded)
+    }
+
+    /// Round a size to szDecimals
+    pub fn normalize_size(&self, size: Decimal) -> Result<Decimal, FilterError> {
+        if size <= Decimal::ZERO {
+            return Err(FilterError::NonPositiveSize(size));
+        }
+
+        let rounded = size.round_dp(self.sz_decimals).normalize();
+        if rounded.is_zero() {
+            return Err(FilterError::SizeRoundsToZero {
+                size,
+                sz_decimals: self.sz_decimals,
+            });
+        }
+        Ok(rounded)
+    }
+
+    /// Check the minimum order value of already normalized price and size
+    pub fn check_notional(&self, price: Decimal, size: Decimal) -> Result<(), FilterError> {
+        let notional = price * size;
+        if notional < self.min_notional {
+            return Err(FilterError::BelowMinNotional {
+                notional,
+                min: self.min_notional,
+            });
+        }
+        Ok(())
+    }
+
+    /// Round price, trigger price and size of an order; the minimum value is
+    /// only enforced for orders that can open a position
+    pub fn normalize_order(&self, request: &OrderRequest) -> Result<OrderRequest, FilterError> {
+        let mut normalized = request.clone();
+        normalized.size = self.normalize_size(request.size)?;
+        normalized.limit_px = self.normalize_price(request.limit_px)?;
+        if let OrderKind::Trigger { trigger_px, .. } = &mut normalized.kind {
+            *trigger_px = self.normalize_price(*trigger_px)?;
+        }
+        if !normalized.reduce_only {
+            self.check_notional(normalized.limit_px, normalized.size)?;
+        }
+        Ok(normalized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_normalize_price() {
+        let btc = ExchangeFilters::perp(5);
+        assert_eq!(btc.normalize_price(dec!(97123.456)).unwrap(), dec!(97123));
+        // Integer prices are valid regardless of significant figures
+        assert_eq!(btc.normalize_price(dec!(123456)).unwrap(), dec!(123456));
+
+        let meme = ExchangeFilters::perp(0);
+        assert_eq!(
+            meme.normalize_price(dec!(0.000123456)).unwrap(),
+            dec!(0.000123)
+        );
+
+        let spot = ExchangeFilters::spot(0);
+        assert_eq!(
+            spot.normalize_price(dec!(0.000123456)).unwrap(),
+            dec!(0.00012346)
+        );
+
+        assert_eq!(
+            meme.normalize_price(dec!(0.0000001)),
+            Err(FilterError::PriceRoundsToZero {
+                price: dec!(0.0000001),
+                decimals: 6
+            })
+        );
+        assert!(meme.normalize_price(dec!(-1)).is_err());
+    }
+
+    #[test]
+    fn test_normalize_size_and_notional() {
+        let eth = ExchangeFilters::perp(4);
+        assert_eq!(eth.normalize_size(dec!(0.123456)).unwrap(), dec!(0.1235));
+        assert!(matches!(
+            eth.normalize_size(dec!(0.00001)),
+            Err(FilterError::SizeRoundsToZero { .. })
+        ));
+
+        assert!(eth.check_notional(dec!(2000), dec!(0.005)).is_ok());
+        assert!(matches!(
+            eth.check_notional(dec!(2000), dec!(0.004)),
+            Err(FilterError::BelowMinNotional { .. })
+        ));
     }
 }
@@ -0,0 +1,294 @@
+// exchange/info.rs
+// Typed responses of the /info order and fill queries
+//
+// Numbers are parsed into Decimal on deserialization (serde_decimal, like
+// account::PositionData); a malformed field fails the response.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::actions::Cloid;
+use super::serde_decimal;
+
+/// Order side as reported by the API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    /// Buy
+    #[serde(rename = "B")]
+    Bid,
+    /// Sell
+    #[serde(rename = "A")]
+    Ask,
+}
+
+impl Side {
+    pub fn is_buy(&self) -> bool {
+        matches!(self, Side::Bid)
+    }
+}
+
+/// Entry of `openOrders`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenOrder {
+    pub coin: String,
+    pub side: Side,
+    #[serde(with = "serde_decimal")]
+    pub limit_px: Decimal,
+    #[serde(with = "serde_decimal")]
+    pub sz: Decimal,
+    pub oid: u64,
+    pub timestamp: u64,
+    #[serde(default, with = "serde_decimal::option")]
+    pub orig_sz: Option<Decimal>,
+    #[serde(default)]
+    pub cloid: Option<Cloid>,
+}
+
+/// Entry of `frontendOpenOrders`, also the order part of `orderStatus` / `historicalOrders`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontendOrder {
+    pub coin: String,
+    pub side: Side,
+    #[serde(with = "serde_decimal")]
+    pub limit_px: Decimal,
+    #[serde(with = "serde_decimal")]
+    pub sz: Decimal,
+    pub oid: u64,
+    pub timestamp: u64,
+    #[serde(with = "serde_decimal")]
+    pub orig_sz: Decimal,
+    #[serde(default)]
+    pub order_type: String,
+    #[serde(default)]
+    pub tif: Option<String>,
+    #[serde(default)]
+    pub reduce_only: bool,
+    #[serde(default)]
+    pub is_trigger: bool,
+    #[serde(default, with = "serde_decimal::option")]
+    pub trigger_px: Option<Decimal>,
+    #[serde(default)]
+    pub trigger_condition: Option<String>,
+    #[serde(default)]
+    pub is_position_tpsl: bool,
+    #[serde(default)]
+    pub cloid: Option<Cloid>,
+}
+
+impl FrontendOrder {
+    pub fn limit_price(&self) -> Decimal {
+        self.limit_px
+    }
+
+    /// Already filled part of the original size
+    pub fn filled_size(&self) -> Decimal {
+        self.orig_sz - self.sz
+    }
+}
+
+/// Order together with its lifecycle status
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderWithStatus {
+    pub order: FrontendOrder,
+    /// open, filled, canceled, triggered, rejected, marginCanceled, ...
+    pub status: String,
+    pub status_timestamp: u64,
+}
+
+impl OrderWithStatus {
+    pub fn is_open(&self) -> bool {
+        self.status == "open"
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.status == "filled"
+    }
+}
+
+/// Response of `orderStatus`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum OrderStatusResponse {
+    Order { order: Box<OrderWithStatus> },
+    UnknownOid,
+}
+
+/// Entry of `userFills` / `userFillsByTime`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fill {
+    pub coin: String,
+    #[serde(with = "serde_decimal")]
+    pub px: Decimal,
+    #[serde(with = "serde_decimal")]
+    pub sz: Decimal,
+    pub side: Side,
+    pub time: u64,
+    #[serde(with = "serde_decimal")]
+    pub start_position: Decimal,
+    /// e.g. "Open Long", "Close Short"
+    pub dir: String,
+    #[serde(with = "serde_decimal")]
+    pub closed_pnl: Decimal,
+    pub hash: String,
+    pub oid: u64,
+    pub crossed: bool,
+    #[serde(with = "serde_decimal")]
+    pub fee: Decimal,
+    #[serde(default)]
+    pub fee_token: Option<String>,
+    pub tid: u64,
+    #[serde(default)]
+    pub cloid: Option<Cloid>,
+}
+
+impl Fill {
+    pub fn price(&self) -> Decimal {
+        self.px
+    }
+
+    pub fn size(&self) -> Decimal {
+        self.sz
+    }
+
+    pub fn closed_pnl(&self) -> Decimal {
+        self.closed_pnl
+    }
+
+    pub fn fee(&self) -> Decimal {
+        self.fee
+    }
+}
+
+/// Size-weighted average price and total size of the fills of one order
+pub fn average_fill(fills: &[Fill], oid: u64) -> Option<(Decimal, Decimal)> {
+    let (notional, size) = fills
+        .iter()
+        .filter(|f| f.oid == oid)
+        .fold((Decimal::ZERO, Decimal::ZERO), |(n, s), f| {
+            (n + f.price() * f.size(), s + f.size())
+        });
+    if size.is_zero() {
+        None
+    } else {
+        Some((notional / size, size))
+    }
+}
+
+/// Entry of `userFunding`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingEvent {
+    pub time: u64,
+    pub hash: String,
+    pub delta: FundingDelta,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingDelta {
+    pub coin: String,
+    #[serde(with = "serde_decimal")]
+    pub funding_rate: Decimal,
+    /// Signed position size at funding time
+    #[serde(with = "serde_decimal")]
+    pub szi: Decimal,
+    /// Paid (negative) or received (positive) USDC
+    #[serde(with = "serde_decimal")]
+    pub usdc: Decimal,
+    #[serde(default)]
+    pub n_samples: Option<u64>,
+}
+
+impl FundingDelta {
+    pub fn usdc(&self) -> Decimal {
+        self.usdc
+    }
+}
+
+/// Response of `userRateLimit`: the per-address action budget
+/// (10000 requests to start, plus one per USDC traded)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserRateLimit {
+    #[serde(with = "serde_decimal")]
+    pub cum_vlm: Decimal,
+    pub n_requests_used: u64,
+    pub n_requests_cap: u64,
+}
+
+impl UserRateLimit {
+    pub fn remaining(&self) -> u64 {
+        self.n_requests_cap.saturating_sub(self.n_requests_used)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_order_status() {
+        let json = r#"{
+            "status": "order",
+            "order": {
+                "order": {
+                    "coin": "ETH", "side": "A", "limitPx": "2412.7", "sz": "0.0", "oid": 1,
+                    "timestamp": 1724361546645, "triggerCondition": "N/A", "isTrigger": false,
+                    "triggerPx": "0.0", "children": [], "isPositionTpsl": false,
+                    "reduceOnly": true, "orderType": "Market", "origSz": "0.0076",
+                    "tif": "FrontendMarket", "cloid": null
+                },
+                "status": "filled",
+                "statusTimestamp": 1724361546645
+            }
+        }"#;
+        let parsed: OrderStatusResponse = serde_json::from_str(json).unwrap();
+        let OrderStatusResponse::Order { order } = parsed else {
+            panic!("expected order");
+        };
+        assert!(order.is_filled());
+        assert_eq!(order.order.side, Side::Ask);
+        assert_eq!(order.order.filled_size(), dec!(0.0076));
+
+        let unknown: OrderStatusResponse =
+            serde_json::from_str(r#"{"status":"unknownOid"}"#).unwrap();
+        assert_eq!(unknown, OrderStatusResponse::UnknownOid);
+    }
+
+    #[test]
+    fn test_parse_fills_and_average() {
+        let json = r#"[
+            {"closedPnl": "0.0", "coin": "AVAX", "crossed": false, "dir": "Open Long",
+             "hash": "0xa166e3fa63c25663024b03f2e0da011a00307e4017465df020210d3d432e7cb8",
+             "oid": 90542681, "px": "18.435", "side": "B", "startPosition": "26.86",
+             "sz": "93.53", "time": 1681222254710, "fee": "0.01", "feeToken": "USDC",
+             "tid": 118906512037719},
+            {"closedPnl": "0.0", "coin": "AVAX", "crossed": true, "dir": "Open Long",
+             "hash": "0x01", "oid": 90542681, "px": "18.445", "side": "B",
+             "startPosition": "120.39", "sz": "6.47", "time": 1681222254711, "fee": "0.01",
+             "tid": 118906512037720}
+        ]"#;
+        let fills: Vec<Fill> = serde_json::from_str(json).unwrap();
+        assert_eq!(fills.len(), 2);
+        assert!(fills[0].side.is_buy());
+
+        let (avg, size) = average_fill(&fills, 90542681).unwrap();
+        assert_eq!(size, dec!(100));
+        assert_eq!(avg, dec!(18.435647));
+        assert!(average_fill(&fills, 1).is_none());
+    }
+
+    #[test]
+    fn test_parse_funding() {
+        let json = r#"[{"delta": {"coin": "ETH", "fundingRate": "0.0000125", "szi": "49.1477",
+            "type": "funding", "usdc": "-3.625312", "nSamples": null},
+            "hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "time": 1681923600000}]"#;
+        let events: Vec<FundingEvent> = serde_json::from_str(json).unwrap();
+        assert_eq!(events[0].delta.usdc(), dec!(-3.625312));
+    }
+}
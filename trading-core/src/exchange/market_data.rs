@@ -3,91 +3,142 @@
 // THE ALLIANCE - Market Data Analysis v4.1
 // ====
 
+use super::connector::api_url;
+use super::errors::ExchangeError;
+use super::orderbook::OrderBook;
+use super::rate_limit::{info_weight, Priority, RateLimiter};
+use super::traits::MarketDataStream;
 use super::types::{L2Snapshot, MarketState};
-use rust_decimal::prelude::FromPrimitive; // Ermöglicht Decimal::from_f64()
-use rust_decimal::Decimal;
-use std::str::FromStr;
+use super::ws::HyperliquidWs;
+use crate::physics::{FeatureSet, Physicist};
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
 
-pub struct HyperliquidMarketData {}
+/// Öffentliche Hyperliquid-Marktdaten ohne Schlüssel (MarketDataStream) und deren
+/// Ableitung in thermodynamische Zustände
+pub struct HyperliquidMarketData {
+    features: FeatureSet,
+    is_testnet: bool,
+    base_url: String,
+    client: Client,
+    limiter: Arc<RateLimiter>,
+}
 
 impl HyperliquidMarketData {
+    /// Gewichtete Top-5-Tiefe als Druck (Preset market_data_v1), Mainnet
     pub fn new() -> Self {
-        Self {}
+        Self::with_features(FeatureSet::preset("market_data_v1").expect("market_data_v1 preset"))
     }
 
-    /// Adaptive Physics: Wandelt Orderbuch-Snapshots in thermodynamische Zustände um
-    pub fn derive_market_state(&self, snapshot: &L2Snapshot) -> MarketState {
-        let symbol = snapshot.coin.clone();
+    /// Eigene Feature-Definitionen, z.B. aus FEATURE_SET
+    pub fn with_features(features: FeatureSet) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .unwrap_or_default();
+        Self {
+            features,
+            is_testnet: false,
+            base_url: api_url(false),
+            client,
+            limiter: RateLimiter::shared(),
+        }
+    }
 
-        // 1. Temperatur (T) = Mid-Price
-        let best_bid = snapshot
-            .levels
-            .bids
-            .first()
-            .and_then(|l| Decimal::from_str(&l.px).ok())
-            .unwrap_or(Decimal::ZERO);
+    /// Netz für REST und Websocket; HL_API_URL/HL_WS_URL überschreiben wie beim Connector
+    pub fn with_testnet(mut self, is_testnet: bool) -> Self {
+        self.is_testnet = is_testnet;
+        self.base_url = api_url(is_testnet);
+        self
+    }
 
-        let best_ask = snapshot
-            .levels
-            .asks
-            .first()
-            .and_then(|l| Decimal::from_str(&l.px).ok())
-            .unwrap_or(Decimal::ZERO);
+    /// Beliebige REST-URL, z.B. eine MockExchange
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
 
-        let temperature = if best_bid > Decimal::ZERO && best_ask > Decimal::ZERO {
-            (best_bid + best_ask) / Decimal::from(2)
-        } else {
-            Decimal::ZERO
-        };
+    pub fn features(&self) -> &FeatureSet {
+        &self.features
+    }
 
-        // 2. Volumen/Spread (V)
-        let volume_spread = if temperature > Decimal::ZERO {
-            (best_ask - best_bid) / temperature
-        } else {
-            Decimal::ZERO
-        };
+    /// Adaptive Physics: Wandelt geprüfte Orderbücher in thermodynamische Zustände um.
+    /// Rechnet über den Physicist, Zeitstempel ist die Exchange-Zeit des Buchs.
+    pub fn derive_market_state(&self, book: &OrderBook) -> MarketState {
+        Physicist::process_book_at(book, book.time, &self.features).to_market_state(&book.coin)
+    }
+}
 
-        // 3. Druck (P) = Gewichtete Liquidität der ersten 5 Ebenen
-        let mut pressure = Decimal::ZERO;
+impl Default for HyperliquidMarketData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        // Bids gewichten
-        for (j, level) in snapshot.levels.bids.iter().take(5).enumerate() {
-            if let Ok(sz) = Decimal::from_str(&level.sz) {
-                let weight = Decimal::from_f64(1.0 - (j as f64 * 0.1)).unwrap_or(Decimal::ONE);
-                pressure += sz * weight;
-            }
+#[async_trait]
+impl MarketDataStream for HyperliquidMarketData {
+    async fn orderbook(&self, symbol: &str) -> Result<OrderBook> {
+        self.limiter
+            .acquire(info_weight("l2Book"), Priority::Normal)
+            .await?;
+        let response = self
+            .client
+            .post(format!("{}/info", self.base_url))
+            .json(&json!({ "type": "l2Book", "coin": symbol }))
+            .send()
+            .await
+            .map_err(ExchangeError::from)?;
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            self.limiter.throttle();
         }
+        let snapshot: L2Snapshot = response
+            .error_for_status()
+            .map_err(ExchangeError::from)?
+            .json()
+            .await
+            .map_err(ExchangeError::from)?;
+        Ok(OrderBook::from_snapshot(&snapshot)?)
+    }
 
-        // Asks gewichten
-        for (j, level) in snapshot.levels.asks.iter().take(5).enumerate() {
-            if let Ok(sz) = Decimal::from_str(&level.sz) {
-                let weight = Decimal::from_f64(1.0 - (j as f64 * 0.1)).unwrap_or(Decimal::ONE);
-                pressure += sz * weight;
-            }
+    /// Eigener Websocket (Reconnect und Abo-Replay inklusive) je Aufruf
+    async fn subscribe_books(&self, symbols: &[String]) -> Result<mpsc::Receiver<OrderBook>> {
+        let ws = HyperliquidWs::new(self.is_testnet).await?;
+        for symbol in symbols {
+            ws.subscribe_l2(symbol).await?;
         }
+        Ok(ws.into_books())
+    }
+}
 
-        // 4. Entropie (S) berechnen
-        let mut all_levels = Vec::new();
-        for level in snapshot
-            .levels
-            .bids
-            .iter()
-            .chain(snapshot.levels.asks.iter())
-        {
-            if let (Ok(px), Ok(sz)) = (Decimal::from_str(&level.px), Decimal::from_str(&level.sz)) {
-                all_levels.push((px, sz));
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::orderbook::test_books::snapshot;
 
-        let entropy_raw = MarketState::calculate_entropy(&all_levels);
-        let entropy_dec = Decimal::from_f64(entropy_raw);
+    #[test]
+    fn test_feature_set_drives_derived_state() {
+        let book = OrderBook::from_snapshot(&snapshot(
+            "BTC",
+            5,
+            &[("99", "3"), ("98", "1")],
+            &[("101", "1"), ("102", "1")],
+        ))
+        .unwrap();
 
-        MarketState {
-            symbol,
-            temperature,
-            pressure,
-            volume_spread,
-            entropy: entropy_dec,
-        }
+        // market_data_v1: 3*1.0 + 1*0.9 + 1*1.0 + 1*0.9
+        let depth = HyperliquidMarketData::new().derive_market_state(&book);
+        assert!((depth.pressure_f64() - 5.8).abs() < 1e-9);
+        assert_eq!(depth.volume_spread_f64(), 0.02);
+        assert_eq!(depth.timestamp, 5);
+
+        let imbalance =
+            HyperliquidMarketData::with_features(FeatureSet::default()).derive_market_state(&book);
+        assert!((imbalance.pressure_f64() - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(imbalance.volume_spread_f64(), 2.0);
     }
 }
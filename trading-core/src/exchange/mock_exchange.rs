@@ -0,0 +1,1277 @@
+// exchange/mock_exchange.rs
+// In-process mock of the Hyperliquid /info and /exchange endpoints
+//
+// Keeps simulated USDC balances, perp positions and resting orders per user.
+// L1 actions are signature-checked (the recovered signer must be a funded
+// account or an agent approved by one), validated with ExchangeFilters and
+// matched against the books set via `set_book`, e.g. from a replay. Trigger
+// orders fire on the mid price; normalTpsl children wait for their entry's
+// fill. A modify replaces the order under a new oid. Nonces
+// follow the exchange rule: unused, above the lowest of the signer's 100
+// highest, and within (now - 2d, now + 1d). Rejects use the exchange's
+// wording, so ExchangeError::from_reject classifies them like live ones.
+
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use super::account::{
+    ClearinghouseState, CumFunding, Leverage, MarginSummary, Position, PositionData,
+};
+use super::actions::{
+    decimal_to_wire, Action, Cloid, Grouping, OrderRef, OrderTypeWire, OrderWire, Tif, Tpsl,
+    UserSignedAction,
+};
+use super::filters::ExchangeFilters;
+use super::info::{Fill, OpenOrder, Side};
+use super::orderbook::{BookError, OrderBook};
+use super::types::L2Snapshot;
+use super::wallet::{
+    action_hash, l1_typed_data, recover_address, user_signed_typed_data, ActionSignature,
+};
+
+#[derive(Debug, Clone)]
+pub struct MockAsset {
+    pub name: String,
+    pub sz_decimals: u32,
+    pub max_leverage: u32,
+}
+
+impl MockAsset {
+    pub fn new(name: &str, sz_decimals: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            sz_decimals,
+            max_leverage: 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    /// Perp universe; the position is the asset index
+    pub assets: Vec<MockAsset>,
+    /// Signing source the mock expects: "a" (mainnet) or "b" (testnet)
+    pub is_mainnet: bool,
+    pub taker_fee: Decimal,
+    pub maker_fee: Decimal,
+    /// Cross leverage until the user sends updateLeverage
+    pub default_leverage: u32,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            assets: Vec::new(),
+            is_mainnet: false,
+            taker_fee: Decimal::new(45, 5),
+            maker_fee: Decimal::new(15, 5),
+            default_leverage: 20,
+        }
+    }
+}
+
+/// Used nonces kept per signer
+const NONCE_SET_SIZE: usize = 100;
+/// Accepted nonce window around the current time
+const NONCE_MAX_AGE_MS: u64 = 2 * 24 * 60 * 60 * 1000;
+const NONCE_MAX_AHEAD_MS: u64 = 24 * 60 * 60 * 1000;
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Exchange precision of reported amounts
+fn amount(value: Decimal) -> Decimal {
+    value.round_dp(8)
+}
+
+/// Exchange-style number string
+fn wire(value: Decimal) -> String {
+    decimal_to_wire(amount(value))
+}
+
+fn err_response(message: impl Into<String>) -> Value {
+    json!({ "status": "err", "response": message.into() })
+}
+
+fn error_status(message: impl Into<String>) -> Value {
+    json!({ "error": message.into() })
+}
+
+// ====
+// Simulated state
+// ====
+
+#[derive(Debug, Clone, Copy, Default)]
+struct MockPosition {
+    szi: Decimal,
+    entry_px: Decimal,
+}
+
+#[derive(Debug, Default)]
+struct MockAccount {
+    /// Cash: deposits plus realized PnL minus fees
+    usdc: Decimal,
+    positions: HashMap<String, MockPosition>,
+    leverage: HashMap<String, u32>,
+    fills: Vec<Fill>,
+    /// Accepted /exchange actions, for userRateLimit
+    n_requests: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Trigger {
+    px: Decimal,
+    tpsl: Tpsl,
+    is_market: bool,
+}
+
+impl Trigger {
+    /// A sell take profit and a buy stop loss fire once the mark is at or above the trigger
+    fn reached(&self, is_buy: bool, mark: Decimal) -> bool {
+        match (self.tpsl, is_buy) {
+            (Tpsl::Tp, false) | (Tpsl::Sl, true) => mark >= self.px,
+            (Tpsl::Tp, true) | (Tpsl::Sl, false) => mark <= self.px,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    user: String,
+    coin: String,
+    asset: u32,
+    is_buy: bool,
+    px: Decimal,
+    sz: Decimal,
+    orig_sz: Decimal,
+    oid: u64,
+    cloid: Option<Cloid>,
+    timestamp: u64,
+    reduce_only: bool,
+    /// Not yet triggered TP/SL
+    trigger: Option<Trigger>,
+    /// normalTpsl child waiting for this entry order to fill
+    parent: Option<u64>,
+}
+
+impl RestingOrder {
+    /// Plain limit order that can be matched against the book
+    fn is_live(&self) -> bool {
+        self.trigger.is_none() && self.parent.is_none()
+    }
+
+    fn is(&self, oid: &OrderRef) -> bool {
+        match oid {
+            OrderRef::Oid(id) => self.oid == *id,
+            OrderRef::Cloid(cloid) => self.cloid.as_ref() == Some(cloid),
+        }
+    }
+}
+
+/// Matching and account state, shared with exchange::simulated
+pub(crate) struct MockState {
+    config: MockConfig,
+    books: HashMap<String, OrderBook>,
+    accounts: HashMap<String, MockAccount>,
+    /// Approved agent wallet -> master account
+    agents: HashMap<String, String>,
+    /// Highest used nonces per signer (agent or account)
+    nonces: HashMap<String, BTreeSet<u64>>,
+    resting: Vec<RestingOrder>,
+    next_oid: u64,
+    next_tid: u64,
+}
+
+impl MockState {
+    pub(crate) fn new(config: MockConfig) -> Self {
+        Self {
+            config,
+            books: HashMap::new(),
+            accounts: HashMap::new(),
+            agents: HashMap::new(),
+            nonces: HashMap::new(),
+            resting: Vec::new(),
+            next_oid: 1,
+            next_tid: 1,
+        }
+    }
+
+    pub(crate) fn book(&self, coin: &str) -> Option<&OrderBook> {
+        self.books.get(coin)
+    }
+
+    /// Asset index and definition of a coin
+    pub(crate) fn asset(&self, coin: &str) -> Option<(u32, &MockAsset)> {
+        self.config
+            .assets
+            .iter()
+            .enumerate()
+            .find(|(_, a)| a.name == coin)
+            .map(|(i, a)| (i as u32, a))
+    }
+
+    /// Append a coin to the universe (no-op if it is listed already)
+    pub(crate) fn register(&mut self, asset: MockAsset) {
+        if self.asset(&asset.name).is_none() {
+            self.config.assets.push(asset);
+        }
+    }
+
+    pub(crate) fn fund(&mut self, user: &str, usdc: Decimal) {
+        self.accounts.entry(user.to_lowercase()).or_default().usdc += usdc;
+    }
+
+    /// Let `agent` sign L1 actions for the (existing) account `master`
+    pub(crate) fn approve_agent(&mut self, master: &str, agent: &str) {
+        self.agents
+            .insert(agent.to_lowercase(), master.to_lowercase());
+    }
+
+    /// Account an L1 signer trades for: the master of an approved agent, else the signer
+    fn account_of(&self, signer: &str) -> String {
+        self.agents
+            .get(signer)
+            .cloned()
+            .unwrap_or_else(|| signer.to_string())
+    }
+
+    /// Record a nonce of `signer` or explain why the exchange would reject it
+    fn use_nonce(&mut self, signer: &str, nonce: u64, now: u64) -> Result<(), String> {
+        if nonce <= now.saturating_sub(NONCE_MAX_AGE_MS) || nonce >= now + NONCE_MAX_AHEAD_MS {
+            return Err(format!(
+                "Invalid nonce: {} is outside the time window",
+                nonce
+            ));
+        }
+        let used = self.nonces.entry(signer.to_string()).or_default();
+        if used.contains(&nonce) {
+            return Err(format!("Invalid nonce: duplicate nonce {}", nonce));
+        }
+        if used.len() >= NONCE_SET_SIZE && used.first().is_some_and(|&lowest| nonce < lowest) {
+            return Err(format!(
+                "Invalid nonce: {} is lower than the 100 highest",
+                nonce
+            ));
+        }
+        used.insert(nonce);
+        if used.len() > NONCE_SET_SIZE {
+            used.pop_first();
+        }
+        Ok(())
+    }
+
+    fn position(&self, user: &str, coin: &str) -> Decimal {
+        self.accounts
+            .get(user)
+            .and_then(|a| a.positions.get(coin))
+            .map_or(Decimal::ZERO, |p| p.szi)
+    }
+
+    fn mark(&self, coin: &str) -> Option<Decimal> {
+        self.books.get(coin).and_then(|b| b.mid())
+    }
+
+    fn leverage(&self, user: &str, coin: &str) -> u32 {
+        self.accounts
+            .get(user)
+            .and_then(|a| a.leverage.get(coin).copied())
+            .unwrap_or(self.config.default_leverage)
+    }
+
+    pub(crate) fn clearinghouse_state(&self, user: &str) -> ClearinghouseState {
+        let mut positions = Vec::new();
+        let mut upnl_total = Decimal::ZERO;
+        let mut margin_total = Decimal::ZERO;
+        let mut ntl_total = Decimal::ZERO;
+        let usdc = self.accounts.get(user).map_or(Decimal::ZERO, |a| a.usdc);
+
+        if let Some(account) = self.accounts.get(user) {
+            let mut coins: Vec<&String> = account.positions.keys().collect();
+            coins.sort();
+            for coin in coins {
+                let pos = account.positions[coin];
+                if pos.szi.is_zero() {
+                    continue;
+                }
+                let leverage = self.leverage(user, coin);
+                let mark = self.mark(coin).unwrap_or(pos.entry_px);
+                let upnl = pos.szi * (mark - pos.entry_px);
+                let value = pos.szi.abs() * mark;
+                let margin = value / Decimal::from(leverage);
+                let entry_margin = pos.szi.abs() * pos.entry_px / Decimal::from(leverage);
+                let roe = if entry_margin.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    upnl / entry_margin
+                };
+                upnl_total += upnl;
+                margin_total += margin;
+                ntl_total += value;
+
+                positions.push(Position {
+                    position: PositionData {
+                        coin: coin.clone(),
+                        szi: amount(pos.szi),
+                        entry_px: Some(amount(pos.entry_px)),
+                        leverage: Leverage::Cross { value: leverage },
+                        liquidation_px: None,
+                        margin_used: amount(margin),
+                        max_leverage: self
+                            .config
+                            .assets
+                            .iter()
+                            .find(|a| &a.name == coin)
+                            .map_or(50, |a| a.max_leverage),
+                        position_value: amount(value),
+                        return_on_equity: amount(roe),
+                        unrealized_pnl: amount(upnl),
+                        cum_funding: CumFunding::default(),
+                    },
+                    kind: "oneWay".to_string(),
+                });
+            }
+        }
+
+        let account_value = usdc + upnl_total;
+        let summary = MarginSummary {
+            account_value: amount(account_value),
+            total_ntl_pos: amount(ntl_total),
+            total_raw_usd: amount(usdc),
+            total_margin_used: amount(margin_total),
+        };
+        ClearinghouseState {
+            asset_positions: positions,
+            margin_summary: summary.clone(),
+            cross_margin_summary: summary,
+            // Maintenance margin: half of the initial margin
+            cross_maintenance_margin_used: amount(margin_total / Decimal::TWO),
+            withdrawable: amount((account_value - margin_total).max(Decimal::ZERO)),
+            time: now_ms(),
+        }
+    }
+
+    fn free_margin(&self, user: &str) -> Decimal {
+        let state = self.clearinghouse_state(user);
+        state.account_value() - state.total_margin_used()
+    }
+
+    pub(crate) fn open_orders(&self, user: &str) -> Vec<OpenOrder> {
+        self.resting
+            .iter()
+            .filter(|o| o.user == user)
+            .map(|o| OpenOrder {
+                coin: o.coin.clone(),
+                side: if o.is_buy { Side::Bid } else { Side::Ask },
+                limit_px: amount(o.px),
+                sz: amount(o.sz),
+                oid: o.oid,
+                timestamp: o.timestamp,
+                orig_sz: Some(amount(o.orig_sz)),
+                cloid: o.cloid.clone(),
+            })
+            .collect()
+    }
+
+    /// Newest first, like the API
+    pub(crate) fn user_fills(&self, user: &str) -> Vec<Fill> {
+        self.accounts
+            .get(user)
+            .map(|a| a.fills.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    // ====
+    // Matching
+    // ====
+
+    /// Consume book liquidity up to `limit`; returns the (price, size) per level
+    fn take(
+        &mut self,
+        coin: &str,
+        is_buy: bool,
+        limit: Decimal,
+        size: Decimal,
+    ) -> Vec<(Decimal, Decimal)> {
+        let Some(book) = self.books.get_mut(coin) else {
+            return Vec::new();
+        };
+        let levels = if is_buy {
+            &mut book.asks
+        } else {
+            &mut book.bids
+        };
+        let mut fills = Vec::new();
+        let mut remaining = size;
+        for level in levels.iter_mut() {
+            let crosses = if is_buy {
+                level.px <= limit
+            } else {
+                level.px >= limit
+            };
+            if remaining.is_zero() || !crosses {
+                break;
+            }
+            let qty = remaining.min(level.sz);
+            level.sz -= qty;
+            remaining -= qty;
+            fills.push((level.px, qty));
+        }
+        levels.retain(|l| !l.sz.is_zero());
+        fills
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn fill(
+        &mut self,
+        user: &str,
+        coin: &str,
+        is_buy: bool,
+        px: Decimal,
+        sz: Decimal,
+        oid: u64,
+        cloid: Option<Cloid>,
+        crossed: bool,
+    ) {
+        let tid = self.next_tid;
+        self.next_tid += 1;
+        let fee_rate = if crossed {
+            self.config.taker_fee
+        } else {
+            self.config.maker_fee
+        };
+        let fee = (px * sz * fee_rate).round_dp(6);
+
+        let account = self.accounts.entry(user.to_string()).or_default();
+        let pos = account.positions.entry(coin.to_string()).or_default();
+        let start = pos.szi;
+        let signed = if is_buy { sz } else { -sz };
+        let mut closed_pnl = Decimal::ZERO;
+
+        if start.is_zero() || (start > Decimal::ZERO) == is_buy {
+            let new = start + signed;
+            pos.entry_px = (pos.entry_px * start.abs() + px * sz) / new.abs();
+            pos.szi = new;
+        } else {
+            let closing = sz.min(start.abs());
+            closed_pnl = if start > Decimal::ZERO {
+                closing * (px - pos.entry_px)
+            } else {
+                closing * (pos.entry_px - px)
+            };
+            let new = start + signed;
+            if new.is_zero() {
+                pos.entry_px = Decimal::ZERO;
+            } else if (new > Decimal::ZERO) != (start > Decimal::ZERO) {
+                // Flipped: the remainder opens at the fill price
+                pos.entry_px = px;
+            }
+            pos.szi = new;
+        }
+        account.usdc += closed_pnl - fee;
+
+        let end = start + signed;
+        let dir = if start > Decimal::ZERO && end < Decimal::ZERO {
+            "Long > Short"
+        } else if start < Decimal::ZERO && end > Decimal::ZERO {
+            "Short > Long"
+        } else if start.is_zero() || (start > Decimal::ZERO) == is_buy {
+            if is_buy {
+                "Open Long"
+            } else {
+                "Open Short"
+            }
+        } else if is_buy {
+            "Close Short"
+        } else {
+            "Close Long"
+        };
+        account.fills.push(Fill {
+            coin: coin.to_string(),
+            px: amount(px),
+            sz: amount(sz),
+            side: if is_buy { Side::Bid } else { Side::Ask },
+            time: now_ms(),
+            start_position: amount(start),
+            dir: dir.to_string(),
+            closed_pnl: amount(closed_pnl),
+            hash: format!("0x{:064x}", tid),
+            oid,
+            crossed,
+            fee: amount(fee),
+            fee_token: Some("USDC".to_string()),
+            tid,
+            cloid,
+        });
+    }
+
+    /// Fill resting orders crossed by the current book at their limit price,
+    /// then fire the triggers the new mark has reached
+    fn match_resting(&mut self, coin: &str) {
+        let orders: Vec<RestingOrder> = self
+            .resting
+            .iter()
+            .filter(|o| o.coin == coin && o.is_live())
+            .cloned()
+            .collect();
+        for order in orders {
+            let taken = self.take(coin, order.is_buy, order.px, order.sz);
+            let qty: Decimal = taken.iter().map(|(_, q)| *q).sum();
+            if qty.is_zero() {
+                continue;
+            }
+            self.fill(
+                &order.user,
+                coin,
+                order.is_buy,
+                order.px,
+                qty,
+                order.oid,
+                order.cloid.clone(),
+                false,
+            );
+            if let Some(resting) = self.resting.iter_mut().find(|o| o.oid == order.oid) {
+                resting.sz -= qty;
+            }
+            if qty == order.sz {
+                // Entry filled: its TP/SL children become active triggers
+                for child in self.resting.iter_mut() {
+                    if child.parent == Some(order.oid) {
+                        child.parent = None;
+                    }
+                }
+            }
+        }
+        self.resting.retain(|o| !o.sz.is_zero());
+        self.fire_triggers(coin);
+    }
+
+    /// Execute triggered orders as taker; a limit trigger rests with its remainder
+    fn fire_triggers(&mut self, coin: &str) {
+        let Some(mark) = self.mark(coin) else {
+            return;
+        };
+        let fired: Vec<(RestingOrder, Trigger)> = self
+            .resting
+            .iter()
+            .filter(|o| o.coin == coin && o.parent.is_none())
+            .filter_map(|o| o.trigger.map(|t| (o.clone(), t)))
+            .filter(|(o, t)| t.reached(o.is_buy, mark))
+            .collect();
+        for (order, trigger) in fired {
+            self.resting.retain(|o| o.oid != order.oid);
+            let mut sz = order.sz;
+            if order.reduce_only {
+                let position = self.position(&order.user, coin);
+                if position.is_zero() || (position > Decimal::ZERO) == order.is_buy {
+                    continue;
+                }
+                sz = sz.min(position.abs());
+            }
+            let mut filled = Decimal::ZERO;
+            for (level_px, qty) in self.take(coin, order.is_buy, order.px, sz) {
+                self.fill(
+                    &order.user,
+                    coin,
+                    order.is_buy,
+                    level_px,
+                    qty,
+                    order.oid,
+                    order.cloid.clone(),
+                    true,
+                );
+                filled += qty;
+            }
+            if !trigger.is_market && filled < sz {
+                self.resting.push(RestingOrder {
+                    sz: sz - filled,
+                    trigger: None,
+                    ..order
+                });
+            }
+        }
+    }
+
+    pub(crate) fn set_book(&mut self, snapshot: &L2Snapshot) -> Result<(), BookError> {
+        match self.books.get_mut(&snapshot.coin) {
+            Some(book) => book.apply(snapshot)?,
+            None => {
+                let book = OrderBook::from_snapshot(snapshot)?;
+                self.books.insert(snapshot.coin.clone(), book);
+            }
+        }
+        self.match_resting(&snapshot.coin);
+        Ok(())
+    }
+
+    /// Replace the book of a coin with an already checked one; time must not go back
+    pub(crate) fn set_order_book(&mut self, book: OrderBook) -> Result<(), BookError> {
+        if let Some(last) = self.books.get(&book.coin) {
+            if book.time < last.time {
+                return Err(BookError::OutOfOrder {
+                    last: last.time,
+                    got: book.time,
+                });
+            }
+        }
+        let coin = book.coin.clone();
+        self.books.insert(coin.clone(), book);
+        self.match_resting(&coin);
+        Ok(())
+    }
+
+    // ====
+    // /exchange
+    // ====
+
+    pub(crate) fn place(&mut self, user: &str, order: &OrderWire) -> Value {
+        self.place_child(user, order, None)
+    }
+
+    /// Place an order; with `parent` it waits for that entry order to fill
+    /// and is neither position- nor margin-checked before
+    fn place_child(&mut self, user: &str, order: &OrderWire, parent: Option<u64>) -> Value {
+        let tag = format!("asset={}", order.a);
+        let Some(asset) = self.config.assets.get(order.a as usize).cloned() else {
+            return error_status(format!("Order has unknown asset. {}", tag));
+        };
+        let (Ok(px), Ok(mut sz)) = (Decimal::from_str(&order.p), Decimal::from_str(&order.s))
+        else {
+            return error_status(format!("Order has invalid price. {}", tag));
+        };
+        let (tif, trigger) = match &order.t {
+            OrderTypeWire::Limit { tif } => (*tif, None),
+            OrderTypeWire::Trigger {
+                is_market,
+                trigger_px,
+                tpsl,
+            } => {
+                let Ok(trigger_px) = Decimal::from_str(trigger_px) else {
+                    return error_status(format!("Order has invalid trigger price. {}", tag));
+                };
+                let trigger = Trigger {
+                    px: trigger_px,
+                    tpsl: *tpsl,
+                    is_market: *is_market,
+                };
+                (Tif::Gtc, Some(trigger))
+            }
+        };
+
+        let filters = ExchangeFilters::perp(asset.sz_decimals);
+        if filters.normalize_size(sz) != Ok(sz) {
+            return error_status(format!("Order has invalid size. {}", tag));
+        }
+        if filters.normalize_price(px) != Ok(px) {
+            return error_status(format!("Order has invalid price. {}", tag));
+        }
+
+        let coin = asset.name.clone();
+        let position = self.position(user, &coin);
+
+        if parent.is_some() || trigger.is_some() {
+            if parent.is_none()
+                && order.r
+                && (position.is_zero() || (position > Decimal::ZERO) == order.b)
+            {
+                return error_status(format!(
+                    "Reduce only order would increase position. {}",
+                    tag
+                ));
+            }
+            let oid = self.next_oid;
+            self.next_oid += 1;
+            self.resting.push(RestingOrder {
+                user: user.to_string(),
+                coin,
+                asset: order.a,
+                is_buy: order.b,
+                px,
+                sz,
+                orig_sz: sz,
+                oid,
+                cloid: order.c.clone(),
+                timestamp: now_ms(),
+                reduce_only: order.r,
+                trigger,
+                parent,
+            });
+            if parent.is_some() {
+                return json!("waitingForFill");
+            }
+            let mut status = json!({ "resting": { "oid": oid } });
+            if let Some(cloid) = &order.c {
+                status["resting"]["cloid"] = json!(cloid);
+            }
+            return status;
+        }
+
+        if order.r {
+            if position.is_zero() || (position > Decimal::ZERO) == order.b {
+                return error_status(format!(
+                    "Reduce only order would increase position. {}",
+                    tag
+                ));
+            }
+            sz = sz.min(position.abs());
+        } else {
+            if filters.check_notional(px, sz).is_err() {
+                return error_status(format!("Order must have minimum value of $10. {}", tag));
+            }
+            let signed = if order.b { sz } else { -sz };
+            let increase = ((position + signed).abs() - position.abs()).max(Decimal::ZERO);
+            let required = increase * px / Decimal::from(self.leverage(user, &coin));
+            if required > self.free_margin(user) {
+                return error_status(format!("Insufficient margin to place order. {}", tag));
+            }
+        }
+
+        if tif == Tif::Alo {
+            if let Some(book) = self.books.get(&coin) {
+                let crosses = if order.b {
+                    book.best_ask().is_some_and(|a| a.px <= px)
+                } else {
+                    book.best_bid().is_some_and(|b| b.px >= px)
+                };
+                if crosses {
+                    let bid = book.best_bid().map_or(Decimal::ZERO, |l| l.px);
+                    let ask = book.best_ask().map_or(Decimal::ZERO, |l| l.px);
+                    return error_status(format!(
+                        "Post only order would have immediately matched, bbo was {}@{}. {}",
+                        bid, ask, tag
+                    ));
+                }
+            }
+        }
+
+        let oid = self.next_oid;
+        self.next_oid += 1;
+
+        let taken = if tif == Tif::Alo {
+            Vec::new()
+        } else {
+            self.take(&coin, order.b, px, sz)
+        };
+        let mut filled = Decimal::ZERO;
+        let mut notional = Decimal::ZERO;
+        for (level_px, qty) in taken {
+            self.fill(
+                user,
+                &coin,
+                order.b,
+                level_px,
+                qty,
+                oid,
+                order.c.clone(),
+                true,
+            );
+            filled += qty;
+            notional += level_px * qty;
+        }
+        let remaining = sz - filled;
+
+        if tif == Tif::Ioc && filled.is_zero() {
+            return error_status(format!(
+                "Order could not immediately match against any resting orders. {}",
+                tag
+            ));
+        }
+        if tif == Tif::Ioc || remaining.is_zero() {
+            let mut status = json!({ "filled": {
+                "totalSz": wire(filled),
+                "avgPx": wire(notional / filled),
+                "oid": oid
+            }});
+            if let Some(cloid) = &order.c {
+                status["filled"]["cloid"] = json!(cloid);
+            }
+            return status;
+        }
+
+        self.resting.push(RestingOrder {
+            user: user.to_string(),
+            coin,
+            asset: order.a,
+            is_buy: order.b,
+            px,
+            sz: remaining,
+            orig_sz: sz,
+            oid,
+            cloid: order.c.clone(),
+            timestamp: now_ms(),
+            reduce_only: order.r,
+            trigger: None,
+            parent: None,
+        });
+        let mut status = json!({ "resting": { "oid": oid } });
+        if let Some(cloid) = &order.c {
+            status["resting"]["cloid"] = json!(cloid);
+        }
+        status
+    }
+
+    pub(crate) fn cancel_oid(&mut self, user: &str, asset: u32, oid: u64) -> Value {
+        self.cancel(user, asset, |o| o.oid == oid)
+    }
+
+    fn cancel(&mut self, user: &str, asset: u32, matches: impl Fn(&RestingOrder) -> bool) -> Value {
+        let canceled: Vec<u64> = self
+            .resting
+            .iter()
+            .filter(|o| o.user == user && o.asset == asset && matches(o))
+            .map(|o| o.oid)
+            .collect();
+        // Waiting TP/SL children go with their entry
+        self.resting.retain(|o| {
+            !canceled.contains(&o.oid) && !o.parent.is_some_and(|p| canceled.contains(&p))
+        });
+        if !canceled.is_empty() {
+            json!("success")
+        } else {
+            error_status(format!(
+                "Order was never placed, already canceled, or filled. asset={}",
+                asset
+            ))
+        }
+    }
+
+    /// Entry order followed by its TP/SL triggers
+    fn place_normal_tpsl(&mut self, user: &str, orders: &[OrderWire]) -> Vec<Value> {
+        let Some((entry, children)) = orders.split_first() else {
+            return Vec::new();
+        };
+        let status = self.place(user, entry);
+        let waiting_for = status["resting"]["oid"].as_u64();
+        let filled = status.get("filled").is_some();
+        let mut statuses = vec![status];
+        for child in children {
+            let status = if !matches!(child.t, OrderTypeWire::Trigger { .. }) {
+                error_status(format!(
+                    "TP/SL order must be a trigger order. asset={}",
+                    child.a
+                ))
+            } else if waiting_for.is_some() {
+                self.place_child(user, child, waiting_for)
+            } else if filled {
+                let status = self.place(user, child);
+                if status.get("resting").is_some() {
+                    json!("waitingForTrigger")
+                } else {
+                    status
+                }
+            } else {
+                error_status(format!("Entry order was rejected. asset={}", child.a))
+            };
+            statuses.push(status);
+        }
+        statuses
+    }
+
+    /// Replace a resting order; a rejected replacement keeps the original
+    fn modify(&mut self, user: &str, oid: &OrderRef, order: &OrderWire) -> Value {
+        let Some(index) = self
+            .resting
+            .iter()
+            .position(|o| o.user == user && o.is(oid))
+        else {
+            return error_status("Cannot modify canceled or filled order");
+        };
+        let original = self.resting.remove(index);
+        let status = self.place_child(user, order, original.parent);
+        if status.get("error").is_some() {
+            self.resting.insert(index, original);
+        } else {
+            // Children of a replaced entry follow the new order
+            let oid = status["resting"]["oid"].as_u64();
+            for child in self.resting.iter_mut() {
+                if child.parent == Some(original.oid) {
+                    child.parent = oid;
+                }
+            }
+        }
+        status
+    }
+
+    fn handle_exchange(&mut self, body: &Value) -> Value {
+        if body["action"]["type"] == "approveAgent" {
+            return self.handle_approve_agent(body);
+        }
+        let action: Action = match serde_json::from_value(body["action"].clone()) {
+            Ok(action) => action,
+            Err(e) => return err_response(format!("Unsupported or malformed action: {}", e)),
+        };
+        let Some(nonce) = body["nonce"].as_u64() else {
+            return err_response("Missing nonce");
+        };
+        let signature: ActionSignature = match serde_json::from_value(body["signature"].clone()) {
+            Ok(signature) => signature,
+            Err(e) => return err_response(format!("Invalid signature: {}", e)),
+        };
+        let vault = body["vaultAddress"].as_str();
+
+        let signer = action_hash(&action, vault, nonce, None)
+            .and_then(|hash| l1_typed_data(&hash, self.config.is_mainnet).encode())
+            .and_then(|digest| recover_address(&digest, &signature));
+        let signer = match signer {
+            Ok(signer) => signer,
+            Err(e) => return err_response(format!("Invalid signature: {}", e)),
+        };
+        let user = self.account_of(&signer);
+        if !self.accounts.contains_key(&user) {
+            return err_response(format!("User or API Wallet {} does not exist.", signer));
+        }
+        if let Err(e) = self.use_nonce(&signer, nonce, now_ms()) {
+            return err_response(e);
+        }
+        if let Some(account) = self.accounts.get_mut(&user) {
+            account.n_requests += 1;
+        }
+
+        match action {
+            Action::Order { orders, grouping } => {
+                let statuses: Vec<Value> = match grouping {
+                    Grouping::NormalTpsl => self.place_normal_tpsl(&user, &orders),
+                    Grouping::Na | Grouping::PositionTpsl => {
+                        orders.iter().map(|o| self.place(&user, o)).collect()
+                    }
+                };
+                json!({ "status": "ok", "response": { "type": "order", "data": { "statuses": statuses } } })
+            }
+            Action::Cancel { cancels } => {
+                let statuses: Vec<Value> = cancels
+                    .iter()
+                    .map(|c| self.cancel(&user, c.a, |o| o.oid == c.o))
+                    .collect();
+                json!({ "status": "ok", "response": { "type": "cancel", "data": { "statuses": statuses } } })
+            }
+            Action::CancelByCloid { cancels } => {
+                let statuses: Vec<Value> = cancels
+                    .iter()
+                    .map(|c| {
+                        self.cancel(&user, c.asset, |o| {
+                            o.cloid.as_ref().is_some_and(|id| id.as_str() == c.cloid)
+                        })
+                    })
+                    .collect();
+                json!({ "status": "ok", "response": { "type": "cancel", "data": { "statuses": statuses } } })
+            }
+            Action::UpdateLeverage {
+                asset, leverage, ..
+            } => match self.config.assets.get(asset as usize) {
+                Some(a) if leverage >= 1 && leverage <= a.max_leverage => {
+                    let coin = a.name.clone();
+                    self.accounts
+                        .entry(user)
+                        .or_default()
+                        .leverage
+                        .insert(coin, leverage);
+                    json!({ "status": "ok", "response": { "type": "default" } })
+                }
+                Some(_) => err_response(format!("Invalid leverage value. asset={}", asset)),
+                None => err_response(format!("Unknown asset. asset={}", asset)),
+            },
+            // A single modify answers without statuses
+            Action::Modify { oid, order } => match self.modify(&user, &oid, &order) {
+                Value::Object(status) if status.contains_key("error") => {
+                    err_response(status["error"].as_str().unwrap_or_default())
+                }
+                _ => json!({ "status": "ok", "response": { "type": "default" } }),
+            },
+            Action::BatchModify { modifies } => {
+                let statuses: Vec<Value> = modifies
+                    .iter()
+                    .map(|m| self.modify(&user, &m.oid, &m.order))
+                    .collect();
+                json!({ "status": "ok", "response": { "type": "order", "data": { "statuses": statuses } } })
+            }
+        }
+    }
+
+    /// User-signed approveAgent: the signing account authorizes an agent wallet
+    fn handle_approve_agent(&mut self, body: &Value) -> Value {
+        let action: UserSignedAction = match serde_json::from_value(body["action"].clone()) {
+            Ok(action) => action,
+            Err(e) => return err_response(format!("Unsupported or malformed action: {}", e)),
+        };
+        let signature: ActionSignature = match serde_json::from_value(body["signature"].clone()) {
+            Ok(signature) => signature,
+            Err(e) => return err_response(format!("Invalid signature: {}", e)),
+        };
+        let UserSignedAction::ApproveAgent { agent_address, .. } = &action else {
+            return err_response("Unsupported user-signed action.");
+        };
+
+        let signer = user_signed_typed_data(&action)
+            .and_then(|typed| typed.encode())
+            .and_then(|digest| recover_address(&digest, &signature));
+        let master = match signer {
+            Ok(master) => master,
+            Err(e) => return err_response(format!("Invalid signature: {}", e)),
+        };
+        if !self.accounts.contains_key(&master) {
+            return err_response(format!("User or API Wallet {} does not exist.", master));
+        }
+        if let Err(e) = self.use_nonce(&master, action.nonce(), now_ms()) {
+            return err_response(e);
+        }
+        self.approve_agent(&master, agent_address);
+        json!({ "status": "ok", "response": { "type": "default" } })
+    }
+
+    // ====
+    // /info
+    // ====
+
+    fn handle_info(&self, body: &Value) -> Result<Value, String> {
+        let user = body["user"].as_str().unwrap_or_default().to_lowercase();
+        let value = match body["type"].as_str().unwrap_or_default() {
+            "meta" => json!({ "universe": self.config.assets.iter().map(|a| json!({
+                "name": a.name,
+                "szDecimals": a.sz_decimals,
+                "maxLeverage": a.max_leverage
+            })).collect::<Vec<_>>() }),
+            "spotMeta" => json!({ "tokens": [], "universe": [] }),
+            "allMids" => {
+                let mids: HashMap<&String, String> = self
+                    .books
+                    .iter()
+                    .filter_map(|(coin, book)| Some((coin, wire(book.mid()?))))
+                    .collect();
+                json!(mids)
+            }
+            "l2Book" => {
+                let coin = body["coin"].as_str().unwrap_or_default();
+                match self.books.get(coin) {
+                    Some(book) => {
+                        let side = |levels: &[super::orderbook::BookLevel]| {
+                            levels
+                                .iter()
+                                .map(|l| json!({ "px": wire(l.px), "sz": wire(l.sz), "n": 1 }))
+                                .collect::<Vec<_>>()
+                        };
+                        json!({
+                            "coin": coin,
+                            "time": book.time,
+                            "levels": [side(&book.bids), side(&book.asks)]
+                        })
+                    }
+                    None => Value::Null,
+                }
+            }
+            "clearinghouseState" => json!(self.clearinghouse_state(&user)),
+            "spotClearinghouseState" => json!({ "balances": [] }),
+            "userRateLimit" => {
+                let account = self.accounts.get(&user);
+                let cum_vlm: Decimal = account
+                    .map(|a| a.fills.iter().map(|f| f.price() * f.size()).sum())
+                    .unwrap_or_default();
+                json!({
+                    "cumVlm": wire(cum_vlm),
+                    "nRequestsUsed": account.map_or(0, |a| a.n_requests),
+                    "nRequestsCap": 10_000 + cum_vlm.to_u64().unwrap_or(0)
+                })
+            }
+            "openOrders" => json!(self.open_orders(&user)),
+            "userFills" => json!(self.user_fills(&user)),
+            other => return Err(format!("Unsupported info type {:?}", other)),
+        };
+        Ok(value)
+    }
+}
+
+// ====
+// Server
+// ====
+
+/// Mock exchange on a free local port; point a connector at `url()`
+pub struct MockExchange {
+    url: String,
+    state: Arc<Mutex<MockState>>,
+    handle: JoinHandle<()>,
+}
+
+impl MockExchange {
+    pub async fn start(config: MockConfig) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let state = Arc::new(Mutex::new(MockState::new(config)));
+
+        let server_state = state.clone();
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    let _ = serve_connection(stream, state).await;
+                });
+            }
+        });
+
+        Ok(Self { url, state, handle })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Create the account (if needed) and credit USDC
+    pub fn fund(&self, address: &str, usdc: Decimal) {
+        self.state.lock().unwrap().fund(address, usdc);
+    }
+
+    /// Let an agent wallet sign for `master` without an approveAgent request
+    pub fn approve_agent(&self, master: &str, agent: &str) {
+        self.state.lock().unwrap().approve_agent(master, agent);
+    }
+
+    /// Replace the book of a coin and fill resting orders it crosses
+    pub fn set_book(&self, snapshot: &L2Snapshot) -> Result<(), BookError> {
+        self.state.lock().unwrap().set_book(snapshot)
+    }
+
+    pub fn clearinghouse_state(&self, user: &str) -> ClearinghouseState {
+        self.state
+            .lock()
+            .unwrap()
+            .clearinghouse_state(&user.to_lowercase())
+    }
+
+    pub fn open_orders(&self, user: &str) -> Vec<OpenOrder> {
+        self.state.lock().unwrap().open_orders(&user.to_lowercase())
+    }
+
+    pub fn user_fills(&self, user: &str) -> Vec<Fill> {
+        self.state.lock().unwrap().user_fills(&user.to_lowercase())
+    }
+}
+
+impl Drop for MockExchange {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Minimal HTTP/1.1 with keep-alive: JSON POST bodies with Content-Length
+async fn serve_connection(stream: TcpStream, state: Arc<Mutex<MockState>>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).await? == 0 {
+            return Ok(());
+        }
+        let path = request_line
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or("/")
+            .to_string();
+
+        let mut content_length = 0usize;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).await? == 0 {
+                return Ok(());
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+            }
+        }
+        let mut body = vec![0u8; content_length];
+        reader.read_exact(&mut body).await?;
+
+        let (status, response) = match serde_json::from_slice::<Value>(&body) {
+            Err(e) => ("422 Unprocessable Entity", json!(e.to_string())),
+            Ok(request) => {
+                let mut state = state.lock().unwrap();
+                match path.as_str() {
+                    "/info" => match state.handle_info(&request) {
+                        Ok(value) => ("200 OK", value),
+                        Err(e) => ("422 Unprocessable Entity", json!(e)),
+                    },
+                    "/exchange" => ("200 OK", state.handle_exchange(&request)),
+                    _ => ("404 Not Found", json!("Not found")),
+                }
+            }
+        };
+
+        let payload = response.to_string();
+        let head = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            status,
+            payload.len()
+        );
+        let stream = reader.get_mut();
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(payload.as_bytes()).await?;
+        stream.flush().await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_fill_accounting() {
+        let mut state = MockState::new(MockConfig {
+            maker_fee: Decimal::ZERO,
+            ..MockConfig::default()
+        });
+        state.fill("0xabc", "ETH", true, dec!(100), dec!(1), 1, None, false);
+        state.fill("0xabc", "ETH", true, dec!(110), dec!(1), 2, None, false);
+        // Short über die Position hinaus: 2 schließen, 1 neu eröffnen
+        state.fill("0xabc", "ETH", false, dec!(120), dec!(3), 3, None, false);
+
+        let account = &state.accounts["0xabc"];
+        let pos = account.positions["ETH"];
+        assert_eq!((pos.szi, pos.entry_px), (dec!(-1), dec!(120)));
+        assert_eq!(account.usdc, dec!(30));
+        assert_eq!(account.fills[2].dir, "Long > Short");
+        assert_eq!(account.fills[2].start_position, dec!(2));
+    }
+
+    #[test]
+    fn test_nonce_window_accepts_out_of_order() {
+        let mut state = MockState::new(MockConfig::default());
+        let now = 1_700_000_000_000;
+
+        // Concurrent requests may arrive out of order
+        state.use_nonce("0xabc", now + 2, now).unwrap();
+        state.use_nonce("0xabc", now + 1, now).unwrap();
+        assert!(state.use_nonce("0xabc", now + 1, now).is_err());
+        assert!(state
+            .use_nonce("0xabc", now - NONCE_MAX_AGE_MS, now)
+            .is_err());
+        assert!(state
+            .use_nonce("0xabc", now + NONCE_MAX_AHEAD_MS, now)
+            .is_err());
+
+        // Once 100 nonces are used, only values above the lowest kept one pass
+        for n in 10..110 {
+            state.use_nonce("0xdef", now + n, now).unwrap();
+        }
+        assert!(state.use_nonce("0xdef", now + 5, now).is_err());
+        state.use_nonce("0xdef", now + 50_000, now).unwrap();
+        // now + 10 dropped out of the set, now + 11 is the new floor
+        assert!(state.use_nonce("0xdef", now + 10, now).is_err());
+        // Nonces are per signer
+        state.use_nonce("0xabc", now + 5, now).unwrap();
+    }
+}
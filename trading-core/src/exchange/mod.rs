@@ -1,20 +1,32 @@
 // E:\MBCT\trading-core\src\exchange\mod.rs
+pub mod account;
+pub mod actions;
 pub mod connector;
 pub mod envelope_detection;
 pub mod errors;
 pub mod filters;
+pub mod info;
 pub mod market_data;
+pub mod mock_exchange;
+pub mod msgpack;
+pub mod nonce;
+pub mod orderbook;
+pub mod rate_limit;
+pub mod serde_decimal;
+pub mod simulated;
 pub mod traits;
 pub mod types;
 pub mod utils;
 pub mod wallet;
 pub mod ws;
+pub mod ws_replay;
 
 // Re-exports für die "Movement Based" Engine
 pub use connector::HyperliquidConnector as ExchangeConnector;
 pub use errors::ExchangeError;
 pub use market_data::HyperliquidMarketData as MarketProvider;
-pub use traits::{Exchange, MarketDataProvider};
+pub use orderbook::{BookError, BookLevel, OrderBook};
+pub use traits::{AccountQueries, Exchange, MarketDataStream, OrderExecution};
 pub use types::*;
 pub use wallet::HyperliquidWallet;
 pub use ws::HyperliquidWs as WebSocketStream;
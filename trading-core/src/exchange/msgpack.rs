@@ -0,0 +1,501 @@
+// exchange/msgpack.rs
+// Minimal MessagePack encoder (serde) for Hyperliquid action hashing
+//
+// Hyperliquid hashes `msgpack.packb(action)` from the Python SDK. Structs are
+// encoded as maps in field declaration order, integers in their most compact
+// form, Option::None as nil. Only serialization is needed, no decoder.
+
+use serde::ser::{self, Serialize};
+use std::fmt;
+
+#[derive(Debug)]
+pub struct MsgpackError(String);
+
+impl fmt::Display for MsgpackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "msgpack: {}", self.0)
+    }
+}
+
+impl std::error::Error for MsgpackError {}
+
+impl ser::Error for MsgpackError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        MsgpackError(msg.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, MsgpackError>;
+
+/// Serialize a value to MessagePack bytes
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let mut serializer = Serializer::default();
+    value.serialize(&mut serializer)?;
+    Ok(serializer.out)
+}
+
+#[derive(Default)]
+pub struct Serializer {
+    out: Vec<u8>,
+}
+
+impl Serializer {
+    fn write_uint(&mut self, v: u64) {
+        if v < 0x80 {
+            self.out.push(v as u8);
+        } else if v <= u8::MAX as u64 {
+            self.out.push(0xcc);
+            self.out.push(v as u8);
+        } else if v <= u16::MAX as u64 {
+            self.out.push(0xcd);
+            self.out.extend_from_slice(&(v as u16).to_be_bytes());
+        } else if v <= u32::MAX as u64 {
+            self.out.push(0xce);
+            self.out.extend_from_slice(&(v as u32).to_be_bytes());
+        } else {
+            self.out.push(0xcf);
+            self.out.extend_from_slice(&v.to_be_bytes());
+        }
+    }
+
+    fn write_int(&mut self, v: i64) {
+        if v >= 0 {
+            self.write_uint(v as u64);
+        } else if v >= -32 {
+            self.out.push(v as i8 as u8);
+        } else if v >= i8::MIN as i64 {
+            self.out.push(0xd0);
+            self.out.push(v as i8 as u8);
+        } else if v >= i16::MIN as i64 {
+            self.out.push(0xd1);
+            self.out.extend_from_slice(&(v as i16).to_be_bytes());
+        } else if v >= i32::MIN as i64 {
+            self.out.push(0xd2);
+            self.out.extend_from_slice(&(v as i32).to_be_bytes());
+        } else {
+            self.out.push(0xd3);
+            self.out.extend_from_slice(&v.to_be_bytes());
+        }
+    }
+
+    fn write_str(&mut self, v: &str) {
+        let len = v.len();
+        if len < 32 {
+            self.out.push(0xa0 | len as u8);
+        } else if len <= u8::MAX as usize {
+            self.out.push(0xd9);
+            self.out.push(len as u8);
+        } else if len <= u16::MAX as usize {
+            self.out.push(0xda);
+            self.out.extend_from_slice(&(len as u16).to_be_bytes());
+        } else {
+            self.out.push(0xdb);
+            self.out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+        self.out.extend_from_slice(v.as_bytes());
+    }
+
+    fn write_bin(&mut self, v: &[u8]) {
+        let len = v.len();
+        if len <= u8::MAX as usize {
+            self.out.push(0xc4);
+            self.out.push(len as u8);
+        } else if len <= u16::MAX as usize {
+            self.out.push(0xc5);
+            self.out.extend_from_slice(&(len as u16).to_be_bytes());
+        } else {
+            self.out.push(0xc6);
+            self.out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+        self.out.extend_from_slice(v);
+    }
+
+    fn write_array_len(&mut self, len: usize) {
+        if len < 16 {
+            self.out.push(0x90 | len as u8);
+        } else if len <= u16::MAX as usize {
+            self.out.push(0xdc);
+            self.out.extend_from_slice(&(len as u16).to_be_bytes());
+        } else {
+            self.out.push(0xdd);
+            self.out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+
+    fn write_map_len(&mut self, len: usize) {
+        if len < 16 {
+            self.out.push(0x80 | len as u8);
+        } else if len <= u16::MAX as usize {
+            self.out.push(0xde);
+            self.out.extend_from_slice(&(len as u16).to_be_bytes());
+        } else {
+            self.out.push(0xdf);
+            self.out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+
+    fn compound(&mut self, is_map: bool) -> Compound<'_> {
+        Compound {
+            parent: self,
+            body: Serializer::default(),
+            count: 0,
+            is_map,
+        }
+    }
+}
+
+/// Buffers elements so the header can carry the real element count
+/// (serde length hints are optional and exclude skipped fields)
+pub struct Compound<'a> {
+    parent: &'a mut Serializer,
+    body: Serializer,
+    count: usize,
+    is_map: bool,
+}
+
+impl Compound<'_> {
+    fn finish(self) {
+        if self.is_map {
+            self.parent.write_map_len(self.count);
+        } else {
+            self.parent.write_array_len(self.count);
+        }
+        self.parent.out.extend_from_slice(&self.body.out);
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = ();
+    type Error = MsgpackError;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.out.push(if v { 0xc3 } else { 0xc2 });
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.write_int(v as i64);
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.write_int(v as i64);
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.write_int(v as i64);
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.write_int(v);
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.write_uint(v as u64);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.write_uint(v as u64);
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.write_uint(v as u64);
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.write_uint(v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.out.push(0xca);
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.out.push(0xcb);
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.write_str(v.encode_utf8(&mut [0u8; 4]));
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.write_str(v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.write_bin(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.out.push(0xc0);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        self.out.push(0xc0);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.write_str(variant);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.write_map_len(1);
+        self.write_str(variant);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>> {
+        Ok(self.compound(false))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>> {
+        Ok(self.compound(false))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>> {
+        Ok(self.compound(false))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>> {
+        self.write_map_len(1);
+        self.write_str(variant);
+        Ok(self.compound(false))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>> {
+        Ok(self.compound(true))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>> {
+        Ok(self.compound(true))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>> {
+        self.write_map_len(1);
+        self.write_str(variant);
+        Ok(self.compound(true))
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.count += 1;
+        value.serialize(&mut self.body)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish();
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish();
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish();
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish();
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.count += 1;
+        key.serialize(&mut self.body)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut self.body)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish();
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.count += 1;
+        self.body.write_str(key);
+        value.serialize(&mut self.body)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish();
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Sample {
+        #[serde(rename = "type")]
+        kind: &'static str,
+        n: i64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        skipped: Option<u8>,
+        list: Vec<u32>,
+    }
+
+    #[test]
+    fn test_compact_encoding() {
+        assert_eq!(to_vec(&0u64).unwrap(), vec![0x00]);
+        assert_eq!(to_vec(&200u64).unwrap(), vec![0xcc, 200]);
+        assert_eq!(to_vec(&-1i64).unwrap(), vec![0xff]);
+        assert_eq!(to_vec(&-100i64).unwrap(), vec![0xd0, 0x9c]);
+        assert_eq!(
+            to_vec(&100_000_000_000u64).unwrap(),
+            vec![0xcf, 0, 0, 0, 0x17, 0x48, 0x76, 0xe8, 0]
+        );
+        assert_eq!(to_vec(&Option::<u8>::None).unwrap(), vec![0xc0]);
+    }
+
+    #[test]
+    fn test_struct_is_ordered_map() {
+        let bytes = to_vec(&Sample {
+            kind: "a",
+            n: 1,
+            skipped: None,
+            list: vec![7, 300],
+        })
+        .unwrap();
+        let mut expected = vec![0x83, 0xa4];
+        expected.extend_from_slice(b"type");
+        expected.extend_from_slice(&[0xa1, b'a', 0xa1, b'n', 0x01, 0xa4]);
+        expected.extend_from_slice(b"list");
+        expected.extend_from_slice(&[0x92, 0x07, 0xcd, 0x01, 0x2c]);
+        assert_eq!(bytes, expected);
+    }
+}
@@ -0,0 +1,125 @@
+// exchange/nonce.rs
+// Monotonic nonce allocation per signer
+//
+// Hyperliquid rejects a nonce that was already used by the signer and only
+// accepts nonces within (now - 2 days, now + 1 day). Millisecond timestamps
+// alone collide as soon as two orders are sent within the same millisecond.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Maximum distance a nonce may run ahead of the wall clock
+pub const MAX_FUTURE_DRIFT_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Strictly increasing nonce source for one signer, safe to share across tasks
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    last: AtomicU64,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shared manager for a signer address, so every connector using the
+    /// same key draws from the same sequence
+    pub fn for_signer(address: &str) -> Arc<NonceManager> {
+        static REGISTRY: OnceLock<Mutex<HashMap<String, Arc<NonceManager>>>> = OnceLock::new();
+        let registry = REGISTRY.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut map = registry.lock().unwrap_or_else(|e| e.into_inner());
+        map.entry(address.to_lowercase())
+            .or_insert_with(|| Arc::new(NonceManager::new()))
+            .clone()
+    }
+
+    /// Next nonce based on the current wall clock
+    pub fn next(&self) -> Result<u64> {
+        self.next_at(chrono::Utc::now().timestamp_millis() as u64)
+    }
+
+    /// Next nonce: max(now, last + 1)
+    pub fn next_at(&self, now_ms: u64) -> Result<u64> {
+        let mut last = self.last.load(Ordering::Acquire);
+        loop {
+            let candidate = now_ms.max(last + 1);
+            if candidate > now_ms + MAX_FUTURE_DRIFT_MS {
+                return Err(anyhow!(
+                    "Nonce {} runs more than {}ms ahead of clock {}",
+                    candidate,
+                    MAX_FUTURE_DRIFT_MS,
+                    now_ms
+                ));
+            }
+            match self.last.compare_exchange_weak(
+                last,
+                candidate,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Ok(candidate),
+                Err(current) => last = current,
+            }
+        }
+    }
+
+    /// Last nonce handed out (0 if none yet)
+    pub fn last(&self) -> u64 {
+        self.last.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_nonces_strictly_increasing() {
+        let nonces = NonceManager::new();
+        assert_eq!(nonces.next_at(1_000).unwrap(), 1_000);
+        // Same millisecond and clock going backwards still advance
+        assert_eq!(nonces.next_at(1_000).unwrap(), 1_001);
+        assert_eq!(nonces.next_at(900).unwrap(), 1_002);
+        assert_eq!(nonces.next_at(5_000).unwrap(), 5_000);
+    }
+
+    #[test]
+    fn test_nonces_unique_across_threads() {
+        let nonces = Arc::new(NonceManager::new());
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let nonces = nonces.clone();
+                std::thread::spawn(move || {
+                    (0..500)
+                        .map(|_| nonces.next_at(42).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let all: Vec<u64> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        let unique: HashSet<u64> = all.iter().copied().collect();
+        assert_eq!(unique.len(), all.len());
+        assert_eq!(nonces.last(), 42 + 8 * 500 - 1);
+    }
+
+    #[test]
+    fn test_nonce_window_and_registry() {
+        let nonces = NonceManager::new();
+        nonces
+            .last
+            .store(10 + MAX_FUTURE_DRIFT_MS, Ordering::Release);
+        assert!(nonces.next_at(10).is_err());
+        assert!(nonces.next_at(11).is_ok());
+
+        let a = NonceManager::for_signer("0xABC");
+        let b = NonceManager::for_signer("0xabc");
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+}
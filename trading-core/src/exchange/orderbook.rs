@@ -0,0 +1,349 @@
+// exchange/orderbook.rs
+// Locally maintained L2 order book with parsed levels and integrity checks
+//
+// The l2Book channel carries no sequence number; the exchange time is the
+// ordering key. `seq` counts accepted updates so readers can detect changes.
+// A rejected update leaves the book untouched.
+
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use thiserror::Error;
+
+use super::types::{L2Levels, L2Snapshot, Level};
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum BookError {
+    #[error("Snapshot for {got} applied to book of {expected}")]
+    CoinMismatch { expected: String, got: String },
+
+    #[error("Out-of-order snapshot: time {got} before {last}")]
+    OutOfOrder { last: u64, got: u64 },
+
+    #[error("Crossed book: bid {bid} >= ask {ask}")]
+    Crossed { bid: Decimal, ask: Decimal },
+
+    #[error("Invalid level {px}@{sz}")]
+    InvalidLevel { px: String, sz: String },
+
+    #[error("Unsorted {side} levels at {px}")]
+    Unsorted { side: &'static str, px: Decimal },
+}
+
+/// Parsed price level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookLevel {
+    pub px: Decimal,
+    pub sz: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBook {
+    pub coin: String,
+    /// Exchange time of the last accepted snapshot (Unix millis)
+    pub time: u64,
+    /// Number of accepted updates
+    pub seq: u64,
+    /// Best (highest) first
+    pub bids: Vec<BookLevel>,
+    /// Best (lowest) first
+    pub asks: Vec<BookLevel>,
+}
+
+impl OrderBook {
+    pub fn from_snapshot(snapshot: &L2Snapshot) -> Result<Self, BookError> {
+        let (bids, asks) = parse_sides(snapshot)?;
+        Ok(Self {
+            coin: snapshot.coin.clone(),
+            time: snapshot.time,
+            seq: 1,
+            bids,
+            asks,
+        })
+    }
+
+    /// Replace the book with a newer snapshot of the same coin
+    pub fn apply(&mut self, snapshot: &L2Snapshot) -> Result<(), BookError> {
+        if snapshot.coin != self.coin {
+            return Err(BookError::CoinMismatch {
+                expected: self.coin.clone(),
+                got: snapshot.coin.clone(),
+            });
+        }
+        if snapshot.time < self.time {
+            return Err(BookError::OutOfOrder {
+                last: self.time,
+                got: snapshot.time,
+            });
+        }
+
+        let (bids, asks) = parse_sides(snapshot)?;
+        self.bids = bids;
+        self.asks = asks;
+        self.time = snapshot.time;
+        self.seq += 1;
+        Ok(())
+    }
+
+    /// Snapshot with the book's levels, e.g. to record a book taken from a MarketDataStream
+    pub fn to_snapshot(&self) -> L2Snapshot {
+        let levels = |side: &[BookLevel]| {
+            side.iter()
+                .map(|l| Level {
+                    px: l.px.to_string(),
+                    sz: l.sz.to_string(),
+                })
+                .collect()
+        };
+        L2Snapshot {
+            coin: self.coin.clone(),
+            time: self.time,
+            levels: L2Levels {
+                bids: levels(&self.bids),
+                asks: levels(&self.asks),
+            },
+        }
+    }
+
+    /// Age of the book relative to `now_ms`
+    pub fn age_ms(&self, now_ms: u64) -> u64 {
+        now_ms.saturating_sub(self.time)
+    }
+
+    pub fn is_stale(&self, now_ms: u64, max_age_ms: u64) -> bool {
+        self.age_ms(now_ms) > max_age_ms
+    }
+
+    pub fn best_bid(&self) -> Option<BookLevel> {
+        self.bids.first().copied()
+    }
+
+    pub fn best_ask(&self) -> Option<BookLevel> {
+        self.asks.first().copied()
+    }
+
+    pub fn mid(&self) -> Option<Decimal> {
+        Some((self.best_bid()?.px + self.best_ask()?.px) / Decimal::TWO)
+    }
+
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_ask()?.px - self.best_bid()?.px)
+    }
+
+    pub fn spread_bps(&self) -> Option<Decimal> {
+        Some(self.spread()? / self.mid()? * BPS)
+    }
+
+    /// Side a taker order walks: asks for buys, bids for sells
+    pub fn taker_side(&self, is_buy: bool) -> &[BookLevel] {
+        if is_buy {
+            &self.asks
+        } else {
+            &self.bids
+        }
+    }
+
+    /// Limit price of a marketable order: best opposite price moved by
+    /// `slippage` (0.01 = 1%) against the taker
+    pub fn slippage_price(&self, is_buy: bool, slippage: Decimal) -> Option<Decimal> {
+        let best = self.taker_side(is_buy).first()?.px;
+        Some(if is_buy {
+            best * (Decimal::ONE + slippage)
+        } else {
+            best * (Decimal::ONE - slippage)
+        })
+    }
+
+    /// Resting size of one side within `bps` of the mid price
+    pub fn depth_within_bps(&self, is_bid: bool, bps: Decimal) -> Decimal {
+        let Some(mid) = self.mid() else {
+            return Decimal::ZERO;
+        };
+        let band = mid * bps / BPS;
+        let (levels, limit) = if is_bid {
+            (&self.bids, mid - band)
+        } else {
+            (&self.asks, mid + band)
+        };
+        levels
+            .iter()
+            .take_while(|l| if is_bid { l.px >= limit } else { l.px <= limit })
+            .map(|l| l.sz)
+            .sum()
+    }
+
+    /// Average price of a taker order of `size`; None if the book is too thin
+    pub fn vwap(&self, is_buy: bool, size: Decimal) -> Option<Decimal> {
+        if size <= Decimal::ZERO {
+            return None;
+        }
+        let mut remaining = size;
+        let mut notional = Decimal::ZERO;
+        for level in self.taker_side(is_buy) {
+            let take = remaining.min(level.sz);
+            notional += take * level.px;
+            remaining -= take;
+            if remaining.is_zero() {
+                return Some(notional / size);
+            }
+        }
+        None
+    }
+
+    /// Worst price touched by a taker order of `size`; None if the book is too thin
+    pub fn sweep_price(&self, is_buy: bool, size: Decimal) -> Option<Decimal> {
+        let mut filled = Decimal::ZERO;
+        for level in self.taker_side(is_buy) {
+            filled += level.sz;
+            if filled >= size {
+                return Some(level.px);
+            }
+        }
+        None
+    }
+}
+
+fn parse_level(level: &Level) -> Result<BookLevel, BookError> {
+    let invalid = || BookError::InvalidLevel {
+        px: level.px.clone(),
+        sz: level.sz.clone(),
+    };
+    let px = Decimal::from_str(&level.px).map_err(|_| invalid())?;
+    let sz = Decimal::from_str(&level.sz).map_err(|_| invalid())?;
+    if px <= Decimal::ZERO || sz <= Decimal::ZERO {
+        return Err(invalid());
+    }
+    Ok(BookLevel { px, sz })
+}
+
+fn parse_side(
+    levels: &[Level],
+    side: &'static str,
+    better: impl Fn(Decimal, Decimal) -> bool,
+) -> Result<Vec<BookLevel>, BookError> {
+    let parsed = levels
+        .iter()
+        .map(parse_level)
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(w) = parsed.windows(2).find(|w| !better(w[0].px, w[1].px)) {
+        return Err(BookError::Unsorted { side, px: w[1].px });
+    }
+    Ok(parsed)
+}
+
+fn parse_sides(snapshot: &L2Snapshot) -> Result<(Vec<BookLevel>, Vec<BookLevel>), BookError> {
+    let bids = parse_side(&snapshot.levels.bids, "bid", |a, b| a > b)?;
+    let asks = parse_side(&snapshot.levels.asks, "ask", |a, b| a < b)?;
+    if let (Some(bid), Some(ask)) = (bids.first(), asks.first()) {
+        if bid.px >= ask.px {
+            return Err(BookError::Crossed {
+                bid: bid.px,
+                ask: ask.px,
+            });
+        }
+    }
+    Ok((bids, asks))
+}
+
+/// Book fixtures shared by the unit tests of the crate
+#[cfg(test)]
+pub(crate) mod test_books {
+    use crate::exchange::types::{L2Levels, L2Snapshot, Level};
+
+    /// Snapshot from (px, sz) pairs, best level first
+    pub(crate) fn snapshot(
+        coin: &str,
+        time: u64,
+        bids: &[(&str, &str)],
+        asks: &[(&str, &str)],
+    ) -> L2Snapshot {
+        let levels = |side: &[(&str, &str)]| {
+            side.iter()
+                .map(|(px, sz)| Level {
+                    px: px.to_string(),
+                    sz: sz.to_string(),
+                })
+                .collect()
+        };
+        L2Snapshot {
+            coin: coin.into(),
+            time,
+            levels: L2Levels {
+                bids: levels(bids),
+                asks: levels(asks),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn snapshot(time: u64, bids: &[(&str, &str)], asks: &[(&str, &str)]) -> L2Snapshot {
+        test_books::snapshot("BTC", time, bids, asks)
+    }
+
+    #[test]
+    fn test_integrity_checks() {
+        let mut book =
+            OrderBook::from_snapshot(&snapshot(100, &[("99", "1"), ("98", "2")], &[("101", "1")]))
+                .unwrap();
+
+        assert!(matches!(
+            book.apply(&snapshot(99, &[("99", "1")], &[("101", "1")])),
+            Err(BookError::OutOfOrder { last: 100, got: 99 })
+        ));
+        assert!(matches!(
+            book.apply(&snapshot(101, &[("101", "1")], &[("100", "1")])),
+            Err(BookError::Crossed { .. })
+        ));
+        assert!(matches!(
+            book.apply(&snapshot(101, &[("98", "1"), ("99", "1")], &[])),
+            Err(BookError::Unsorted { side: "bid", .. })
+        ));
+        assert!(matches!(
+            book.apply(&snapshot(101, &[("99", "abc")], &[])),
+            Err(BookError::InvalidLevel { .. })
+        ));
+        // Rejected updates leave the book unchanged
+        assert_eq!((book.time, book.seq), (100, 1));
+
+        book.apply(&snapshot(150, &[("99.5", "1")], &[("100.5", "1")]))
+            .unwrap();
+        assert_eq!((book.time, book.seq), (150, 2));
+        assert_eq!(book.mid(), Some(dec!(100)));
+        assert_eq!(book.spread_bps(), Some(dec!(100)));
+        assert!(!book.is_stale(1_150, 1_000));
+        assert!(book.is_stale(1_151, 1_000));
+    }
+
+    #[test]
+    fn test_depth_and_vwap() {
+        let book = OrderBook::from_snapshot(&snapshot(
+            1,
+            &[("99.9", "1"), ("99.5", "2"), ("98", "5")],
+            &[("100.1", "1"), ("100.5", "3"), ("102", "4")],
+        ))
+        .unwrap();
+
+        // Mid 100, 60 bps band = [99.4, 100.6]
+        assert_eq!(book.depth_within_bps(true, dec!(60)), dec!(3));
+        assert_eq!(book.depth_within_bps(false, dec!(60)), dec!(4));
+
+        // 1 @ 100.1 + 1 @ 100.5
+        assert_eq!(book.vwap(true, dec!(2)), Some(dec!(100.3)));
+        assert_eq!(book.vwap(false, dec!(1)), Some(dec!(99.9)));
+        assert_eq!(book.sweep_price(true, dec!(4.5)), Some(dec!(102)));
+        assert_eq!(book.vwap(true, dec!(9)), None);
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let original = snapshot(7, &[("99.90", "1.5")], &[("100.1", "2"), ("101", "0.25")]);
+        let book = OrderBook::from_snapshot(&original).unwrap();
+        assert_eq!(book.to_snapshot(), original);
+    }
+}
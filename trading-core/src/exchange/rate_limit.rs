@@ -0,0 +1,347 @@
+// exchange/rate_limit.rs
+// Client-side token bucket for Hyperliquid's per-IP request weight budget
+//
+// The REST API allows 1200 weight per minute and IP. Each /info request
+// type has a fixed weight (plus extra weight for long list responses), an
+// /exchange action costs 1 + batch/40. Priorities keep a reserve of the
+// bucket for more important calls, so info polling can never use up the
+// budget that orders and cancels need.
+//
+// The bucket is per process, the exchange's budget per IP. Processes that
+// share an IP (trader, researcher, signaler on one host) must split it:
+// HL_RATE_LIMIT_SHARE=0.5 gives a process half of the IP budget.
+
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::time::{self, Duration, Instant};
+
+use super::errors::ExchangeError;
+
+/// REST weight budget per IP and minute
+pub const IP_WEIGHT_PER_MINUTE: u32 = 1200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Polling and discovery: waits at most `low_max_wait`, then is shed
+    Low,
+    /// Account and market queries
+    Normal,
+    /// Orders, cancels and the queries on their path; may use the whole bucket
+    Critical,
+}
+
+/// Weight of an /info request type
+pub fn info_weight(kind: &str) -> u32 {
+    match kind {
+        "l2Book"
+        | "allMids"
+        | "clearinghouseState"
+        | "orderStatus"
+        | "spotClearinghouseState"
+        | "exchangeStatus" => 2,
+        "userRole" => 60,
+        _ => 20,
+    }
+}
+
+/// Additional weight of list responses: 1 per 20 items returned
+pub fn response_weight(kind: &str, items: usize) -> u32 {
+    match kind {
+        "recentTrades"
+        | "historicalOrders"
+        | "userFills"
+        | "userFillsByTime"
+        | "fundingHistory"
+        | "userFunding"
+        | "nonUserFundingUpdates"
+        | "twapHistory"
+        | "userTwapSliceFills" => (items / 20) as u32,
+        _ => 0,
+    }
+}
+
+/// Weight of an /exchange action carrying `batch_len` orders or cancels
+pub fn exchange_weight(batch_len: usize) -> u32 {
+    1 + (batch_len / 40) as u32
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub capacity: u32,
+    pub refill_per_minute: u32,
+    /// Weight Normal requests leave in the bucket for Critical ones
+    pub normal_reserve: u32,
+    /// Weight Low requests leave in the bucket for Normal and Critical ones
+    pub low_reserve: u32,
+    /// Longest a Low request queues before it is shed
+    pub low_max_wait: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            capacity: IP_WEIGHT_PER_MINUTE,
+            refill_per_minute: IP_WEIGHT_PER_MINUTE,
+            normal_reserve: 100,
+            low_reserve: 400,
+            low_max_wait: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Fraction (0, 1] of the IP budget; capacity, refill and reserves scale alike
+    pub fn with_share(share: f64) -> Self {
+        let base = Self::default();
+        let scale = |weight: u32| ((weight as f64 * share).round() as u32).max(1);
+        Self {
+            capacity: scale(base.capacity),
+            refill_per_minute: scale(base.refill_per_minute),
+            normal_reserve: scale(base.normal_reserve),
+            low_reserve: scale(base.low_reserve),
+            ..base
+        }
+    }
+
+    /// Default budget scaled by HL_RATE_LIMIT_SHARE; an invalid value falls
+    /// back to the full budget with a warning
+    pub fn from_env() -> Self {
+        let Ok(raw) = std::env::var("HL_RATE_LIMIT_SHARE") else {
+            return Self::default();
+        };
+        match raw.trim().parse::<f64>() {
+            Ok(share) if share > 0.0 && share <= 1.0 => Self::with_share(share),
+            _ => {
+                eprintln!(
+                    "⚠️ HL_RATE_LIMIT_SHARE '{}' ungültig (erwartet 0 < x <= 1), volles Budget",
+                    raw
+                );
+                Self::default()
+            }
+        }
+    }
+
+    fn reserve(&self, priority: Priority) -> u32 {
+        match priority {
+            Priority::Low => self.low_reserve,
+            Priority::Normal => self.normal_reserve,
+            Priority::Critical => 0,
+        }
+    }
+}
+
+/// Budget metrics for dashboards and logs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimitStats {
+    pub capacity: u32,
+    /// Weight available right now
+    pub remaining: u32,
+    /// Total weight spent (including response weight)
+    pub used: u64,
+    pub requests: u64,
+    /// Requests that had to queue for budget
+    pub queued: u64,
+    /// Low-priority requests dropped instead of queued
+    pub shed: u64,
+    /// HTTP 429 responses seen
+    pub throttled: u64,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Can go negative through `charge`
+    tokens: f64,
+    updated: Instant,
+    stats: RateLimitStats,
+}
+
+/// Token bucket shared by every connector of the process; see the module
+/// docs for splitting the per-IP budget between processes
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    bucket: Mutex<Bucket>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        let bucket = Bucket {
+            tokens: config.capacity as f64,
+            updated: Instant::now(),
+            stats: RateLimitStats {
+                capacity: config.capacity,
+                ..RateLimitStats::default()
+            },
+        };
+        Self {
+            config,
+            bucket: Mutex::new(bucket),
+        }
+    }
+
+    /// Process-wide limiter with this process's share of the IP budget
+    /// (RateLimitConfig::from_env)
+    pub fn shared() -> Arc<RateLimiter> {
+        static SHARED: OnceLock<Arc<RateLimiter>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Arc::new(RateLimiter::new(RateLimitConfig::from_env())))
+            .clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.updated).as_secs_f64()
+            * self.config.refill_per_minute as f64
+            / 60.0;
+        bucket.tokens = (bucket.tokens + refill).min(self.config.capacity as f64);
+        bucket.updated = now;
+        bucket
+    }
+
+    /// Take `weight` if the priority's reserve stays untouched, otherwise
+    /// return how long the bucket needs to refill
+    pub fn try_acquire(&self, weight: u32, priority: Priority) -> Result<(), Duration> {
+        let reserve = self.config.reserve(priority);
+        // Never ask for more than the priority can ever get
+        let weight = weight.min(self.config.capacity.saturating_sub(reserve));
+        let needed = (weight + reserve) as f64;
+
+        let mut bucket = self.lock();
+        if bucket.tokens >= needed {
+            bucket.tokens -= weight as f64;
+            bucket.stats.used += weight as u64;
+            bucket.stats.requests += 1;
+            return Ok(());
+        }
+        let per_sec = self.config.refill_per_minute.max(1) as f64 / 60.0;
+        Err(Duration::from_secs_f64((needed - bucket.tokens) / per_sec))
+    }
+
+    /// Wait until `weight` is available; Low requests are shed with
+    /// RateLimited once they would wait longer than `low_max_wait`
+    pub async fn acquire(&self, weight: u32, priority: Priority) -> Result<(), ExchangeError> {
+        let started = Instant::now();
+        let mut queued = false;
+        loop {
+            let wait = match self.try_acquire(weight, priority) {
+                Ok(()) => {
+                    if queued {
+                        self.lock().stats.queued += 1;
+                    }
+                    return Ok(());
+                }
+                Err(wait) => wait,
+            };
+            if priority == Priority::Low && started.elapsed() + wait > self.config.low_max_wait {
+                let mut bucket = self.lock();
+                bucket.stats.shed += 1;
+                return Err(ExchangeError::RateLimited(format!(
+                    "low-priority request of weight {} shed, {:.0} weight left",
+                    weight,
+                    bucket.tokens.max(0.0)
+                )));
+            }
+            queued = true;
+            time::sleep(wait).await;
+        }
+    }
+
+    /// Book weight known only after the response (long lists)
+    pub fn charge(&self, weight: u32) {
+        let mut bucket = self.lock();
+        bucket.tokens -= weight as f64;
+        bucket.stats.used += weight as u64;
+    }
+
+    /// The server answered 429: our view of the budget was too optimistic
+    pub fn throttle(&self) {
+        let mut bucket = self.lock();
+        bucket.tokens = bucket.tokens.min(0.0);
+        bucket.stats.throttled += 1;
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.lock().tokens.max(0.0) as u32
+    }
+
+    pub fn stats(&self) -> RateLimitStats {
+        let bucket = self.lock();
+        RateLimitStats {
+            remaining: bucket.tokens.max(0.0) as u32,
+            ..bucket.stats.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weights() {
+        assert_eq!(info_weight("l2Book"), 2);
+        assert_eq!(info_weight("clearinghouseState"), 2);
+        assert_eq!(info_weight("meta"), 20);
+        assert_eq!(info_weight("userRole"), 60);
+        assert_eq!(response_weight("userFills", 100), 5);
+        assert_eq!(response_weight("openOrders", 100), 0);
+        assert_eq!(exchange_weight(1), 1);
+        assert_eq!(exchange_weight(80), 3);
+    }
+
+    #[test]
+    fn test_share_scales_budget() {
+        let half = RateLimitConfig::with_share(0.5);
+        assert_eq!((half.capacity, half.refill_per_minute), (600, 600));
+        assert_eq!((half.normal_reserve, half.low_reserve), (50, 200));
+
+        let full = RateLimitConfig::with_share(1.0);
+        assert_eq!(full.capacity, IP_WEIGHT_PER_MINUTE);
+        assert_eq!(full.low_reserve, RateLimitConfig::default().low_reserve);
+    }
+
+    #[tokio::test]
+    async fn test_priority_reserves() {
+        // 100 weight, refills 1 per 10ms
+        let limiter = RateLimiter::new(RateLimitConfig {
+            capacity: 100,
+            refill_per_minute: 6000,
+            normal_reserve: 20,
+            low_reserve: 50,
+            low_max_wait: Duration::from_millis(50),
+        });
+
+        // Low stops at its reserve and is shed rather than queued for long
+        limiter.acquire(25, Priority::Low).await.unwrap();
+        limiter.acquire(25, Priority::Low).await.unwrap();
+        assert!(matches!(
+            limiter.acquire(40, Priority::Low).await,
+            Err(ExchangeError::RateLimited(_))
+        ));
+
+        // Normal leaves 20 for Critical, Critical may drain the bucket
+        limiter.acquire(30, Priority::Normal).await.unwrap();
+        assert!(limiter.try_acquire(15, Priority::Normal).is_err());
+        limiter.try_acquire(20, Priority::Critical).unwrap();
+
+        // Empty bucket: Critical queues until the refill covers it
+        let started = Instant::now();
+        limiter.acquire(5, Priority::Critical).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(40));
+
+        let stats = limiter.stats();
+        assert_eq!(stats.requests, 5);
+        assert_eq!(stats.used, 105);
+        assert_eq!((stats.queued, stats.shed), (1, 1));
+
+        limiter.throttle();
+        assert_eq!(limiter.remaining(), 0);
+        assert_eq!(limiter.stats().throttled, 1);
+    }
+}
@@ -0,0 +1,96 @@
+// exchange/serde_decimal.rs
+// Serde helper for the exchange's decimal strings ("2986.3", "-0.0134")
+//
+// Use with `#[serde(with = "serde_decimal")]`, or `serde_decimal::option`
+// for nullable fields. A malformed number fails the whole response instead
+// of turning into zero.
+
+use rust_decimal::Decimal;
+use serde::{de, Deserialize, Deserializer, Serializer};
+use std::str::FromStr;
+
+use super::actions::decimal_to_wire;
+
+fn parse<E: de::Error>(value: &str) -> Result<Decimal, E> {
+    Decimal::from_str(value).map_err(|e| E::custom(format!("invalid decimal '{}': {}", value, e)))
+}
+
+pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&decimal_to_wire(*value))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse(&value)
+}
+
+/// Nullable variant: `null` and missing fields (with `#[serde(default)]`) are `None`
+pub mod option {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Decimal>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Decimal>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .as_deref()
+            .map(parse)
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Amounts {
+        #[serde(with = "super")]
+        px: Decimal,
+        #[serde(default, with = "super::option")]
+        liq: Option<Decimal>,
+    }
+
+    #[test]
+    fn test_round_trip_keeps_wire_format() {
+        let parsed: Amounts = serde_json::from_str(r#"{"px": "2986.30", "liq": null}"#).unwrap();
+        assert_eq!(
+            parsed,
+            Amounts {
+                px: dec!(2986.3),
+                liq: None
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&parsed).unwrap(),
+            r#"{"px":"2986.3","liq":null}"#
+        );
+
+        let parsed: Amounts = serde_json::from_str(r#"{"px": "-0.0134"}"#).unwrap();
+        assert_eq!(parsed.liq, None);
+        let parsed: Amounts = serde_json::from_str(r#"{"px": "1", "liq": "0.5"}"#).unwrap();
+        assert_eq!(parsed.liq, Some(dec!(0.5)));
+    }
+
+    #[test]
+    fn test_malformed_number_is_an_error() {
+        let err = serde_json::from_str::<Amounts>(r#"{"px": "12,5"}"#).unwrap_err();
+        assert!(
+            err.to_string().contains("invalid decimal '12,5'"),
+            "{}",
+            err
+        );
+        assert!(serde_json::from_str::<Amounts>(r#"{"px": "1", "liq": "n/a"}"#).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+// exchange/simulated.rs
+// In-process venue with the matching and margin model of the mock exchange
+//
+// Books come from `feed` (live collector or replay) and orders match against
+// them right away: no HTTP, signing, nonces or rate limits. Coins missing from
+// the configured universe are registered with their first book, taking
+// szDecimals from the finest size step seen in it.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::sync::Mutex;
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+use super::account::ClearinghouseState;
+use super::actions::OrderRequest;
+use super::connector::OrderStatus;
+use super::errors::ExchangeError;
+use super::filters::ExchangeFilters;
+use super::info::{Fill, OpenOrder};
+use super::mock_exchange::{MockAsset, MockConfig, MockState};
+use super::orderbook::{BookError, OrderBook};
+use super::traits::{AccountQueries, MarketDataStream, OrderExecution};
+use super::types::L2Snapshot;
+
+/// Perp szDecimals never exceed this; inferred values are capped
+const MAX_SZ_DECIMALS: u32 = 5;
+
+type Subscriber = (Vec<String>, mpsc::Sender<OrderBook>);
+
+pub struct SimulatedExchange {
+    address: String,
+    state: Mutex<MockState>,
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl SimulatedExchange {
+    /// Venue with a single funded account trading as `address`
+    pub fn new(config: MockConfig, address: &str, usdc: Decimal) -> Self {
+        let mut state = MockState::new(config);
+        state.fund(address, usdc);
+        Self {
+            address: address.to_lowercase(),
+            state: Mutex::new(state),
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// New book for a coin: fills crossed resting orders and notifies subscribers
+    pub fn feed(&self, snapshot: &L2Snapshot) -> Result<(), BookError> {
+        self.feed_book(&OrderBook::from_snapshot(snapshot)?)
+    }
+
+    /// Like `feed`, for a book the caller already checked (e.g. the collector's)
+    pub fn feed_book(&self, book: &OrderBook) -> Result<(), BookError> {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.asset(&book.coin).is_none() {
+                state.register(MockAsset::new(&book.coin, size_decimals(book)));
+            }
+            state.set_order_book(book.clone())?;
+        }
+
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|(symbols, tx)| {
+            if !symbols.contains(&book.coin) {
+                return !tx.is_closed();
+            }
+            !matches!(tx.try_send(book.clone()), Err(TrySendError::Closed(_)))
+        });
+        Ok(())
+    }
+}
+
+/// Finest size step of a book, as szDecimals
+fn size_decimals(book: &OrderBook) -> u32 {
+    book.bids
+        .iter()
+        .chain(&book.asks)
+        .map(|l| l.sz.normalize().scale())
+        .max()
+        .unwrap_or(0)
+        .min(MAX_SZ_DECIMALS)
+}
+
+#[async_trait]
+impl MarketDataStream for SimulatedExchange {
+    async fn orderbook(&self, symbol: &str) -> Result<OrderBook> {
+        self.state
+            .lock()
+            .unwrap()
+            .book(symbol)
+            .cloned()
+            .ok_or_else(|| anyhow!("Kein Buch für {}", symbol))
+    }
+
+    async fn subscribe_books(&self, symbols: &[String]) -> Result<mpsc::Receiver<OrderBook>> {
+        let (tx, rx) = mpsc::channel(1024);
+        self.subscribers
+            .lock()
+            .unwrap()
+            .push((symbols.to_vec(), tx));
+        Ok(rx)
+    }
+}
+
+#[async_trait]
+impl AccountQueries for SimulatedExchange {
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    async fn account_state(&self, user: &str) -> Result<ClearinghouseState> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .clearinghouse_state(&user.to_lowercase()))
+    }
+
+    async fn open_orders(&self, user: &str) -> Result<Vec<OpenOrder>> {
+        Ok(self.state.lock().unwrap().open_orders(&user.to_lowercase()))
+    }
+
+    async fn user_fills(&self, user: &str) -> Result<Vec<Fill>> {
+        Ok(self.state.lock().unwrap().user_fills(&user.to_lowercase()))
+    }
+}
+
+#[async_trait]
+impl OrderExecution for SimulatedExchange {
+    async fn place_order(&self, request: &OrderRequest) -> Result<OrderStatus> {
+        let mut state = self.state.lock().unwrap();
+        let (index, sz_decimals) = state
+            .asset(&request.coin)
+            .map(|(index, asset)| (index, asset.sz_decimals))
+            .ok_or_else(|| ExchangeError::UnknownAsset(request.coin.clone()))?;
+        let wire = ExchangeFilters::perp(sz_decimals)
+            .normalize_order(request)
+            .map_err(ExchangeError::from)?
+            .to_wire(index);
+
+        let status: OrderStatus = serde_json::from_value(state.place(&self.address, &wire))?;
+        match status.error() {
+            Some(error) => Err(error.into()),
+            None => Ok(status),
+        }
+    }
+
+    async fn cancel_order(&self, symbol: &str, oid: u64) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let (index, _) = state
+            .asset(symbol)
+            .ok_or_else(|| ExchangeError::UnknownAsset(symbol.to_string()))?;
+        let status = state.cancel_oid(&self.address, index, oid);
+        match status["error"].as_str() {
+            Some(message) => Err(ExchangeError::from_reject(message).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::actions::Tif;
+    use crate::exchange::orderbook::test_books::snapshot;
+    use rust_decimal_macros::dec;
+
+    fn book(time: u64, bid: &str, ask: &str) -> L2Snapshot {
+        snapshot("SOL", time, &[(bid, "10.25")], &[(ask, "10.25")])
+    }
+
+    #[tokio::test]
+    async fn test_trading_through_traits() {
+        let sim = SimulatedExchange::new(MockConfig::default(), "0xSIM", dec!(500));
+        let venue: &dyn OrderExecution = &sim;
+        let mut books = venue.subscribe_books(&["SOL".to_string()]).await.unwrap();
+
+        // Unknown coin: registered with szDecimals 2 from the book
+        sim.feed(&book(1, "150.1", "150.2")).unwrap();
+        assert_eq!(books.recv().await.unwrap().time, 1);
+
+        venue
+            .market_open("SOL", true, dec!(1.234), dec!(0.01))
+            .await
+            .unwrap();
+        let state = venue.account_state(venue.address()).await.unwrap();
+        let position = state.position("SOL").unwrap();
+        assert_eq!(position.size(), dec!(1.23));
+        assert_eq!(position.entry_price(), Some(dec!(150.2)));
+
+        // Resting bid is filled by a later book
+        let request = OrderRequest::limit("SOL", true, dec!(0.1), dec!(149), Tif::Gtc);
+        let oid = venue
+            .place_order(&request)
+            .await
+            .unwrap()
+            .order_id()
+            .unwrap();
+        assert_eq!(venue.open_orders("0xsim").await.unwrap().len(), 1);
+        sim.feed(&book(2, "148.5", "148.9")).unwrap();
+        assert!(venue.open_orders("0xsim").await.unwrap().is_empty());
+        assert!(venue
+            .cancel_order("SOL", oid.parse().unwrap())
+            .await
+            .is_err());
+
+        venue.market_close("SOL", None, dec!(0.01)).await.unwrap();
+        let fills = venue.user_fills(venue.address()).await.unwrap();
+        assert_eq!(fills[0].dir, "Close Long");
+        assert!(venue
+            .account_state(venue.address())
+            .await
+            .unwrap()
+            .position("SOL")
+            .is_none());
+    }
+}
@@ -1,8 +1,24 @@
 // E:\mbct\trading-core\src\exchange\traits.rs
+//
+// Venue-Abstraktion: Marktdaten, Account-Abfragen und Order-Ausführung.
+// Implementiert von HyperliquidConnector und SimulatedExchange (Marktdaten ohne
+// Schlüssel: HyperliquidMarketData); Trader und Paper-Trading kennen nur diese
+// Traits. Offen: der Backtester in trading-common kann sie nicht nutzen, solange
+// sie in trading-core liegen (trading-core hängt von trading-common ab).
+// Ablehnungen der Venue kommen als ExchangeError im anyhow::Error
+// (downcast_ref::<ExchangeError>()).
 
+use crate::exchange::account::ClearinghouseState;
+use crate::exchange::actions::{OrderRequest, Tif};
+use crate::exchange::connector::OrderStatus;
 use crate::exchange::errors::ExchangeError;
+use crate::exchange::info::{Fill, OpenOrder};
+use crate::exchange::orderbook::OrderBook;
 use crate::exchange::types::L2Snapshot;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use rust_decimal::Decimal;
+use tokio::sync::mpsc;
 use trading_common::data::types::MarketState;
 
 #[async_trait]
@@ -15,10 +31,92 @@ pub trait Exchange: Send + Sync {
 }
 
 #[async_trait]
-pub trait MarketDataProvider: Send + Sync {
-    /// Streamt die thermodynamische Bewegung (Cybernetic Loop)
-    async fn subscribe_movement(
+pub trait MarketDataStream: Send + Sync {
+    /// Aktuelles, geprüftes L2-Buch eines Symbols
+    async fn orderbook(&self, symbol: &str) -> Result<OrderBook>;
+
+    /// Geprüfte Bücher der Symbole, solange der Receiver lebt; Snapshots, die die
+    /// Integritätsprüfung nicht bestehen, werden nicht weitergegeben
+    async fn subscribe_books(&self, symbols: &[String]) -> Result<mpsc::Receiver<OrderBook>>;
+}
+
+#[async_trait]
+pub trait AccountQueries: Send + Sync {
+    /// Signer-Adresse (bei Agent-Wallets nicht die Master-Adresse)
+    fn address(&self) -> &str;
+
+    /// Gehandelter Account (bei Agent-Wallets die Master-Adresse); Default: Signer
+    fn account_address(&self) -> &str {
+        self.address()
+    }
+
+    /// Perp-Margin-Status eines Accounts
+    async fn account_state(&self, user: &str) -> Result<ClearinghouseState>;
+
+    async fn open_orders(&self, user: &str) -> Result<Vec<OpenOrder>>;
+
+    /// Fills, neueste zuerst
+    async fn user_fills(&self, user: &str) -> Result<Vec<Fill>>;
+
+    /// Account-Wert für Anzeigen; darf bei knappem Request-Budget ausfallen
+    async fn account_value(&self, user: &str) -> Result<Decimal> {
+        Ok(self.account_state(user).await?.account_value())
+    }
+}
+
+/// Order-Ausführung; Market-Orders brauchen Buch und Position, daher die Supertraits
+#[async_trait]
+pub trait OrderExecution: MarketDataStream + AccountQueries {
+    async fn place_order(&self, request: &OrderRequest) -> Result<OrderStatus>;
+
+    async fn cancel_order(&self, symbol: &str, oid: u64) -> Result<()>;
+
+    /// IOC mit maximal `slippage` (0.01 = 1%) Abstand zum besten Gegenpreis
+    async fn market_open(
         &self,
         symbol: &str,
-    ) -> Result<tokio::sync::mpsc::UnboundedReceiver<MarketState>, ExchangeError>;
+        is_buy: bool,
+        size: Decimal,
+        slippage: Decimal,
+    ) -> Result<String> {
+        let limit_px = self
+            .orderbook(symbol)
+            .await?
+            .slippage_price(is_buy, slippage)
+            .ok_or_else(|| anyhow!("Leeres Orderbuch für {}", symbol))?;
+        let request = OrderRequest::limit(symbol, is_buy, size, limit_px, Tif::Ioc);
+        self.place_order(&request)
+            .await?
+            .order_id()
+            .ok_or_else(|| anyhow!("Market-Order nicht ausgeführt: {}", symbol))
+    }
+
+    /// Offene Position (ganz oder bis `size`) per Reduce-Only-IOC schließen
+    async fn market_close(
+        &self,
+        symbol: &str,
+        size: Option<Decimal>,
+        slippage: Decimal,
+    ) -> Result<String> {
+        let state = self.account_state(self.account_address()).await?;
+        let position = state
+            .position(symbol)
+            .filter(|p| !p.size().is_zero())
+            .ok_or_else(|| anyhow!("Keine offene Position für {}", symbol))?;
+
+        let open = position.size().abs();
+        let size = size.map_or(open, |s| s.min(open));
+        let is_buy = !position.is_long();
+        let limit_px = self
+            .orderbook(symbol)
+            .await?
+            .slippage_price(is_buy, slippage)
+            .ok_or_else(|| anyhow!("Leeres Orderbuch für {}", symbol))?;
+        let request =
+            OrderRequest::limit(symbol, is_buy, size, limit_px, Tif::Ioc).reduce_only(true);
+        self.place_order(&request)
+            .await?
+            .order_id()
+            .ok_or_else(|| anyhow!("Close-Order nicht ausgeführt: {}", symbol))
+    }
 }
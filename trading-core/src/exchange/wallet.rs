@@ -7,11 +7,42 @@
 
 use anyhow::{anyhow, Context, Result};
 use hex;
-use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
 use k256::SecretKey;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sha3::{Digest, Keccak256};
 
+use super::actions::UserSignedAction;
+use super::msgpack;
+
+/// Chain id of the L1 signing domain (identical on mainnet and testnet)
+pub const L1_CHAIN_ID: u64 = 1337;
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// ECDSA signature in the format expected by the /exchange endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionSignature {
+    /// 0x-prefixed, 32 bytes
+    pub r: String,
+    /// 0x-prefixed, 32 bytes
+    pub s: String,
+    /// 27 + recovery id
+    pub v: u8,
+}
+
+impl ActionSignature {
+    /// 65 byte r ‖ s ‖ v hex string
+    pub fn to_hex(&self) -> String {
+        format!(
+            "0x{}{}{:02x}",
+            self.r.trim_start_matches("0x"),
+            self.s.trim_start_matches("0x"),
+            self.v
+        )
+    }
+}
+
 /// Hyperliquid Wallet
 ///
 /// Custom wallet implementation for Hyperliquid
@@ -56,54 +87,38 @@ impl HyperliquidWallet {
 
     /// Derive Ethereum address from signing key
     fn derive_address(signing_key: &SigningKey) -> Result<String> {
-        // Get public key
-        let verifying_key = signing_key.verifying_key();
-        let public_key_bytes = verifying_key.to_encoded_point(false);
-        let public_key_bytes = public_key_bytes.as_bytes();
-
-        // Skip first byte (0x04 prefix for uncompressed key)
-        let public_key = &public_key_bytes[1..];
-
-        // Keccak256 hash
-        let mut hasher = Keccak256::new();
-        hasher.update(public_key);
-        let hash = hasher.finalize();
-
-        // Take last 20 bytes
-        let address_bytes = &hash[12..];
-
-        // Format as 0x...
-        Ok(format!("0x{}", hex::encode(address_bytes)))
+        address_of(signing_key.verifying_key())
     }
 
     /// Sign EIP-712 typed data
     ///
     /// Used for Hyperliquid API requests
-    pub fn sign_typed_data(&self, typed_data: &TypedData) -> Result<String> {
-        // Encode typed data
-        let encoded = typed_data.encode()?;
+    pub fn sign_typed_data(&self, typed_data: &TypedData) -> Result<ActionSignature> {
+        let digest = typed_data.encode()?;
+        self.sign_hash(&digest)
+    }
 
-        // Sign
-        let signature: Signature = self.private_key.sign(&encoded);
+    /// Sign an L1 action (orders, cancels, leverage, ...)
+    ///
+    /// The action is hashed with msgpack together with nonce and vault address,
+    /// wrapped in a phantom agent and signed as EIP-712 `Agent` message.
+    pub fn sign_l1_action<T: Serialize>(
+        &self,
+        action: &T,
+        vault_address: Option<&str>,
+        nonce: u64,
+        expires_after: Option<u64>,
+        is_mainnet: bool,
+    ) -> Result<ActionSignature> {
+        let hash = action_hash(action, vault_address, nonce, expires_after)?;
+        self.sign_typed_data(&l1_typed_data(&hash, is_mainnet))
+    }
 
-        // Format signature (r, s, v)
-        let sig_bytes = signature.to_bytes();
-        let r = &sig_bytes[..32];
-        let s = &sig_bytes[32..64];
-
-        // Calculate v (recovery id)
-        // For Ethereum, v = 27 + recovery_id
-        // We use 27 as default (most common)
-        let v = 27u8;
-
-        // Concatenate r + s + v
-        let mut full_sig = Vec::with_capacity(65);
-        full_sig.extend_from_slice(r);
-        full_sig.extend_from_slice(s);
-        full_sig.push(v);
-
-        // Return as 0x... hex string
-        Ok(format!("0x{}", hex::encode(full_sig)))
+    /// Sign a user-signed action (approveAgent, usdSend, withdraw3, usdClassTransfer)
+    ///
+    /// Signed directly as EIP-712 message in the `HyperliquidSignTransaction` domain.
+    pub fn sign_user_signed_action(&self, action: &UserSignedAction) -> Result<ActionSignature> {
+        self.sign_typed_data(&user_signed_typed_data(action)?)
     }
 
     /// Sign message (personal_sign)
@@ -114,27 +129,157 @@ impl HyperliquidWallet {
         let prefix = format!("\x19Ethereum Signed Message:\n{}", message.len());
         let full_message = format!("{}{}", prefix, message);
 
-        // Keccak256 hash
-        let mut hasher = Keccak256::new();
-        hasher.update(full_message.as_bytes());
-        let hash = hasher.finalize();
+        let hash = keccak256(full_message.as_bytes());
+        Ok(self.sign_hash(&hash)?.to_hex())
+    }
 
-        // Sign
-        let signature: Signature = self.private_key.sign(&hash);
+    /// Sign a 32 byte digest, v = 27 + recovery id
+    fn sign_hash(&self, digest: &[u8]) -> Result<ActionSignature> {
+        let (signature, recovery_id) = self
+            .private_key
+            .sign_prehash_recoverable(digest)
+            .map_err(|e| anyhow!("Signing failed: {}", e))?;
 
-        // Format signature
         let sig_bytes = signature.to_bytes();
-        let r = &sig_bytes[..32];
-        let s = &sig_bytes[32..64];
-        let v = 27u8;
+        Ok(ActionSignature {
+            r: format!("0x{}", hex::encode(&sig_bytes[..32])),
+            s: format!("0x{}", hex::encode(&sig_bytes[32..64])),
+            v: 27 + recovery_id.to_byte(),
+        })
+    }
+}
+
+// ====
+// Addresses & Signature Recovery
+// ====
+
+/// Ethereum address of a public key: last 20 bytes of keccak256(uncompressed key without 0x04)
+fn address_of(verifying_key: &VerifyingKey) -> Result<String> {
+    let public_key_bytes = verifying_key.to_encoded_point(false);
+    let public_key = &public_key_bytes.as_bytes()[1..];
+    let hash = keccak256(public_key);
+    Ok(format!("0x{}", hex::encode(&hash[12..])))
+}
+
+/// Signer address of a 32 byte digest (e.g. TypedData::encode), inverse of the wallet signing
+pub fn recover_address(digest: &[u8], signature: &ActionSignature) -> Result<String> {
+    let r = hex::decode(signature.r.trim_start_matches("0x")).context("Invalid signature r")?;
+    let s = hex::decode(signature.s.trim_start_matches("0x")).context("Invalid signature s")?;
+    let mut bytes = [0u8; 64];
+    if r.len() > 32 || s.len() > 32 {
+        return Err(anyhow!("Invalid signature length"));
+    }
+    bytes[32 - r.len()..32].copy_from_slice(&r);
+    bytes[64 - s.len()..].copy_from_slice(&s);
+
+    let signature_k =
+        Signature::from_slice(&bytes).map_err(|e| anyhow!("Invalid signature: {}", e))?;
+    let recovery_id = signature
+        .v
+        .checked_sub(27)
+        .and_then(RecoveryId::from_byte)
+        .ok_or_else(|| anyhow!("Invalid recovery id v={}", signature.v))?;
+    let key = VerifyingKey::recover_from_prehash(digest, &signature_k, recovery_id)
+        .map_err(|e| anyhow!("Signature recovery failed: {}", e))?;
+    address_of(&key)
+}
+
+// ====
+// L1 Action Hashing (Phantom Agent)
+// ====
+
+/// keccak256(msgpack(action) ‖ nonce ‖ vault flag [‖ vault] [‖ 0x00 ‖ expiresAfter])
+pub fn action_hash<T: Serialize>(
+    action: &T,
+    vault_address: Option<&str>,
+    nonce: u64,
+    expires_after: Option<u64>,
+) -> Result<[u8; 32]> {
+    let mut data = msgpack::to_vec(action).context("Failed to msgpack action")?;
+    data.extend_from_slice(&nonce.to_be_bytes());
+
+    match vault_address {
+        None => data.push(0x00),
+        Some(vault) => {
+            let vault_bytes =
+                hex::decode(vault.trim_start_matches("0x")).context("Invalid vault address")?;
+            data.push(0x01);
+            data.extend_from_slice(&vault_bytes);
+        }
+    }
+
+    if let Some(expires_after) = expires_after {
+        data.push(0x00);
+        data.extend_from_slice(&expires_after.to_be_bytes());
+    }
+
+    Ok(keccak256(&data))
+}
 
-        let mut full_sig = Vec::with_capacity(65);
-        full_sig.extend_from_slice(r);
-        full_sig.extend_from_slice(s);
-        full_sig.push(v);
+/// EIP-712 payload of the phantom agent: source "a" (mainnet) / "b" (testnet)
+pub fn l1_typed_data(action_hash: &[u8; 32], is_mainnet: bool) -> TypedData {
+    TypedData {
+        domain: EIP712Domain {
+            name: "Exchange".to_string(),
+            version: "1".to_string(),
+            chain_id: L1_CHAIN_ID,
+            verifying_contract: ZERO_ADDRESS.to_string(),
+        },
+        primary_type: "Agent".to_string(),
+        types: json!({
+            "Agent": [
+                { "name": "source", "type": "string" },
+                { "name": "connectionId", "type": "bytes32" }
+            ]
+        }),
+        message: json!({
+            "source": if is_mainnet { "a" } else { "b" },
+            "connectionId": format!("0x{}", hex::encode(action_hash)),
+        }),
+    }
+}
+
+// ====
+// User-Signed Actions
+// ====
 
-        Ok(format!("0x{}", hex::encode(full_sig)))
+/// EIP-712 payload of a user-signed action; the domain chain id is taken from `signatureChainId`
+pub fn user_signed_typed_data(action: &UserSignedAction) -> Result<TypedData> {
+    let chain_id = u64::from_str_radix(action.signature_chain_id().trim_start_matches("0x"), 16)
+        .context("Invalid signatureChainId")?;
+
+    let mut message = serde_json::to_value(action).context("Failed to serialize action")?;
+    // approveAgent signs an empty name when none is sent
+    if let UserSignedAction::ApproveAgent {
+        agent_name: None, ..
+    } = action
+    {
+        message["agentName"] = json!("");
     }
+
+    let fields: Vec<serde_json::Value> = action
+        .sign_fields()
+        .iter()
+        .map(|(name, ty)| json!({ "name": name, "type": ty }))
+        .collect();
+
+    let mut types = serde_json::Map::new();
+    types.insert(
+        action.primary_type().to_string(),
+        serde_json::Value::Array(fields),
+    );
+
+    Ok(TypedData {
+        domain: EIP712Domain {
+            name: "HyperliquidSignTransaction".to_string(),
+            version: "1".to_string(),
+            chain_id,
+            verifying_contract: ZERO_ADDRESS.to_string(),
+        },
+        primary_type: action.primary_type().to_string(),
+        types: serde_json::Value::Object(types),
+        message,
+    })
 }
 
 /// EIP-712 Domain
@@ -296,6 +441,17 @@ impl TypedData {
                 bytes[12..].copy_from_slice(&addr_bytes);
                 Ok(bytes)
             }
+            "bytes32" => {
+                let hex_str = value
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Expected bytes32 hex"))?;
+                let bytes =
+                    hex::decode(hex_str.trim_start_matches("0x")).context("Invalid bytes32")?;
+                if bytes.len() != 32 {
+                    return Err(anyhow!("bytes32 has {} bytes", bytes.len()));
+                }
+                Ok(bytes)
+            }
             "bool" => {
                 let b = value.as_bool().ok_or_else(|| anyhow!("Expected bool"))?;
                 let mut bytes = vec![0u8; 32];
@@ -343,4 +499,136 @@ mod tests {
         assert!(signature.starts_with("0x"));
         assert_eq!(signature.len(), 132); // 0x + 130 hex chars (65 bytes)
     }
+
+    #[test]
+    fn test_recover_address() {
+        let wallet = HyperliquidWallet::from_private_key(
+            "0xe908f86dbb4d55ac876378565aafeabc187f6690f046459397b17d9b9a19688e",
+        )
+        .unwrap();
+        let digest = keccak256(b"alliance");
+        let signature = wallet.sign_hash(&digest).unwrap();
+        assert_eq!(
+            recover_address(&digest, &signature).unwrap(),
+            wallet.address
+        );
+
+        let other = keccak256(b"other");
+        assert_ne!(recover_address(&other, &signature).unwrap(), wallet.address);
+    }
+
+    // Test vectors from the Hyperliquid Python SDK (tests/signing_test.py)
+
+    #[derive(Serialize)]
+    struct DummyAction {
+        #[serde(rename = "type")]
+        kind: &'static str,
+        num: u64,
+    }
+
+    fn trim_hex(h: &str) -> &str {
+        h.trim_start_matches("0x").trim_start_matches('0')
+    }
+
+    #[test]
+    fn test_phantom_agent_matches_production() {
+        use crate::exchange::actions::{Action, Grouping, OrderTypeWire, OrderWire, Tif};
+
+        let action = Action::Order {
+            orders: vec![OrderWire {
+                a: 4,
+                b: true,
+                p: "1670.1".into(),
+                s: "0.0147".into(),
+                r: false,
+                t: OrderTypeWire::Limit { tif: Tif::Ioc },
+                c: None,
+            }],
+            grouping: Grouping::Na,
+        };
+        let hash = action_hash(&action, None, 1677777606040, None).unwrap();
+        assert_eq!(
+            hex::encode(hash),
+            "0fcbeda5ae3c4950a548021552a4fea2226858c4453571bf3f24ba017eac2908"
+        );
+    }
+
+    #[test]
+    fn test_l1_action_signing_matches() {
+        let wallet = HyperliquidWallet::from_private_key(
+            "0x0123456789012345678901234567890123456789012345678901234567890123",
+        )
+        .unwrap();
+        // float_to_int_for_hashing(1000)
+        let action = DummyAction {
+            kind: "dummy",
+            num: 100_000_000_000,
+        };
+
+        let mainnet = wallet.sign_l1_action(&action, None, 0, None, true).unwrap();
+        assert_eq!(
+            trim_hex(&mainnet.r),
+            "53749d5b30552aeb2fca34b530185976545bb22d0b3ce6f62e31be961a59298"
+        );
+        assert_eq!(
+            trim_hex(&mainnet.s),
+            "755c40ba9bf05223521753995abb2f73ab3229be8ec921f350cb447e384d8ed8"
+        );
+        assert_eq!(mainnet.v, 27);
+
+        let testnet = wallet
+            .sign_l1_action(&action, None, 0, None, false)
+            .unwrap();
+        assert_eq!(
+            trim_hex(&testnet.r),
+            "542af61ef1f429707e3c76c5293c80d01f74ef853e34b76efffcb57e574f9510"
+        );
+        assert_eq!(
+            trim_hex(&testnet.s),
+            "17b8b32f086e8cdede991f1e2c529f5dd5297cbe8128500e00cbaf766204a613"
+        );
+        assert_eq!(testnet.v, 28);
+    }
+
+    #[test]
+    fn test_user_signed_action_signing_matches() {
+        let wallet = HyperliquidWallet::from_private_key(
+            "0x0123456789012345678901234567890123456789012345678901234567890123",
+        )
+        .unwrap();
+
+        let send = UserSignedAction::usd_send(
+            "0x5e9ee1089755c3435139848e47e6635505d5a13a",
+            "1",
+            1687816341423,
+            false,
+        );
+        let signature = wallet.sign_user_signed_action(&send).unwrap();
+        assert_eq!(
+            trim_hex(&signature.r),
+            "637b37dd731507cdd24f46532ca8ba6eec616952c56218baeff04144e4a77073"
+        );
+        assert_eq!(
+            trim_hex(&signature.s),
+            "11a6a24900e6e314136d2592e2f8d502cd89b7c15b198e1bee043c9589f9fad7"
+        );
+        assert_eq!(signature.v, 27);
+
+        let withdraw = UserSignedAction::withdraw(
+            "0x5e9ee1089755c3435139848e47e6635505d5a13a",
+            "1",
+            1687816341423,
+            false,
+        );
+        let signature = wallet.sign_user_signed_action(&withdraw).unwrap();
+        assert_eq!(
+            trim_hex(&signature.r),
+            "8363524c799e90ce9bc41022f7c39b4e9bdba786e5f9c72b20e43e1462c37cf9"
+        );
+        assert_eq!(
+            trim_hex(&signature.s),
+            "58b1411a775938b83e29182e8ef74975f9054c8e97ebf5ec2dc8d51bfc893881"
+        );
+        assert_eq!(signature.v, 28);
+    }
 }
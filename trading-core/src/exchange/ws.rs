@@ -1,115 +1,1028 @@
 // E:\MBCT\trading-core\src\exchange\ws.rs
 // ====
-// Hyperliquid WebSocket Connector - ALLIANZ RESILIENT EDITION v4.2
-// One-Shot Pattern: Collector handles reconnection.
+// Hyperliquid WebSocket Connector - ALLIANZ RESILIENT EDITION v5.0
+// Self-healing: backoff with jitter, client ping, subscription replay after reconnect.
+// Optional: record raw frames (WsConfig::record_path) or run against a WsReplayServer (HL_WS_URL).
+// Multiplex: market data and the own execution feed over one connection.
+// Bounded event buffer: market data is dropped when full, everything else waits.
 // ====
 
-use crate::exchange::types::L2Snapshot;
+use crate::exchange::info::{Fill, OpenOrder, Side};
+use crate::exchange::orderbook::OrderBook;
+use crate::exchange::types::{L2Snapshot, Level};
+use crate::exchange::ws_replay::WsRecorder;
 use anyhow::{anyhow, Result};
 use futures_util::{SinkExt, StreamExt};
-use serde_json::json;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::net::TcpStream;
 use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio::time::{self, Duration, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 
-#[derive(Debug, serde::Deserialize)]
-struct WSResponse {
-    data: Option<L2Snapshot>,
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+// ====
+// Subscriptions
+// ====
+
+/// Websocket channel, serialized as the `subscription` object
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Subscription {
+    L2Book {
+        coin: String,
+    },
+    Trades {
+        coin: String,
+    },
+    Bbo {
+        coin: String,
+    },
+    /// interval: "1m", "5m", "1h", ...
+    Candle {
+        coin: String,
+        interval: String,
+    },
+    AllMids,
+    ActiveAssetCtx {
+        coin: String,
+    },
+    UserFills {
+        user: String,
+    },
+    OrderUpdates {
+        user: String,
+    },
+    UserEvents {
+        user: String,
+    },
+    UserFundings {
+        user: String,
+    },
 }
 
 #[derive(Debug)]
+enum WsCommand {
+    Subscribe(Subscription),
+    Unsubscribe(Subscription),
+}
+
+impl WsCommand {
+    fn to_message(&self) -> String {
+        let (method, subscription) = match self {
+            WsCommand::Subscribe(s) => ("subscribe", s),
+            WsCommand::Unsubscribe(s) => ("unsubscribe", s),
+        };
+        json!({ "method": method, "subscription": subscription }).to_string()
+    }
+}
+
+// ====
+// Events
+// ====
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WsTrade {
+    pub coin: String,
+    pub side: Side,
+    pub px: String,
+    pub sz: String,
+    pub time: u64,
+    pub hash: String,
+    pub tid: u64,
+    /// [buyer, seller]
+    #[serde(default)]
+    pub users: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WsBbo {
+    pub coin: String,
+    pub time: u64,
+    /// [best bid, best ask]; None when a side is empty
+    pub bbo: [Option<Level>; 2],
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WsCandle {
+    /// Open time (ms)
+    #[serde(rename = "t")]
+    pub open_time: u64,
+    /// Close time (ms)
+    #[serde(rename = "T")]
+    pub close_time: u64,
+    #[serde(rename = "s")]
+    pub coin: String,
+    #[serde(rename = "i")]
+    pub interval: String,
+    pub o: String,
+    pub c: String,
+    pub h: String,
+    pub l: String,
+    /// Volume in base units
+    pub v: String,
+    /// Number of trades
+    pub n: u64,
+}
+
+/// Context of a perp (spot assets have no funding / open interest)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetCtx {
+    pub day_ntl_vlm: String,
+    pub prev_day_px: String,
+    pub mark_px: String,
+    #[serde(default)]
+    pub mid_px: Option<String>,
+    #[serde(default)]
+    pub funding: Option<String>,
+    #[serde(default)]
+    pub open_interest: Option<String>,
+    #[serde(default)]
+    pub oracle_px: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WsActiveAssetCtx {
+    pub coin: String,
+    pub ctx: AssetCtx,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsUserFills {
+    /// First message after subscribing carries the recent history
+    #[serde(default)]
+    pub is_snapshot: bool,
+    pub user: String,
+    pub fills: Vec<Fill>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsOrderUpdate {
+    pub order: OpenOrder,
+    /// open, filled, canceled, triggered, rejected, marginCanceled, ...
+    pub status: String,
+    pub status_timestamp: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsFunding {
+    pub time: u64,
+    pub coin: String,
+    pub usdc: String,
+    pub szi: String,
+    pub funding_rate: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsUserFundings {
+    #[serde(default)]
+    pub is_snapshot: bool,
+    pub user: String,
+    pub fundings: Vec<WsFunding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NonUserCancel {
+    pub coin: String,
+    pub oid: u64,
+}
+
+/// Payload of the `user` channel (subscription `userEvents`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WsUserEvent {
+    Fills(Vec<Fill>),
+    Funding(WsFunding),
+    Liquidation(Value),
+    NonUserCancel(Vec<NonUserCancel>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum HLEvent {
     Snapshot(L2Snapshot),
+    Trades(Vec<WsTrade>),
+    Bbo(WsBbo),
+    Candle(WsCandle),
+    /// coin -> mid price
+    AllMids(HashMap<String, String>),
+    ActiveAssetCtx(WsActiveAssetCtx),
+    UserFills(WsUserFills),
+    OrderUpdates(Vec<WsOrderUpdate>),
+    UserEvent(WsUserEvent),
+    UserFundings(WsUserFundings),
+    /// Server confirmed a subscribe / unsubscribe
+    SubscriptionResponse(Value),
+    /// Error message of the server (e.g. invalid subscription)
+    Error(String),
+    /// Connection state change (generated by the client itself)
+    Connection(ConnectionState),
+    /// Channel without typed decoding or with unexpected payload
+    Other {
+        channel: String,
+        data: Value,
+    },
 }
 
-pub struct HyperliquidWs {
-    rx: mpsc::UnboundedReceiver<HLEvent>,
-    sub_tx: mpsc::UnboundedSender<String>,
+impl HLEvent {
+    /// Public market data: superseded by the next update, so it may be dropped
+    /// when the event buffer is full
+    pub fn is_market_data(&self) -> bool {
+        matches!(
+            self,
+            HLEvent::Snapshot(_)
+                | HLEvent::Trades(_)
+                | HLEvent::Bbo(_)
+                | HLEvent::Candle(_)
+                | HLEvent::AllMids(_)
+                | HLEvent::ActiveAssetCtx(_)
+        )
+    }
 }
 
-impl HyperliquidWs {
-    pub async fn new(is_testnet: bool) -> Result<Self> {
-        let (tx, rx) = mpsc::unbounded_channel();
-        let (sub_tx, mut sub_rx) = mpsc::unbounded_channel::<String>();
+#[derive(Debug, Deserialize)]
+struct WsMessage {
+    channel: String,
+    #[serde(default)]
+    data: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct AllMidsData {
+    mids: HashMap<String, String>,
+}
+
+/// Decode one websocket text frame; None for frames without channel (e.g. pong)
+pub fn parse_message(text: &str) -> Option<HLEvent> {
+    let msg: WsMessage = serde_json::from_str(text).ok()?;
+
+    fn typed<T: serde::de::DeserializeOwned>(data: &Value) -> Option<T> {
+        serde_json::from_value(data.clone()).ok()
+    }
+
+    let event = match msg.channel.as_str() {
+        "l2Book" => typed(&msg.data).map(HLEvent::Snapshot),
+        "trades" => typed(&msg.data).map(HLEvent::Trades),
+        "bbo" => typed(&msg.data).map(HLEvent::Bbo),
+        "candle" => typed(&msg.data).map(HLEvent::Candle),
+        "allMids" => typed::<AllMidsData>(&msg.data).map(|d| HLEvent::AllMids(d.mids)),
+        "activeAssetCtx" | "activeSpotAssetCtx" => typed(&msg.data).map(HLEvent::ActiveAssetCtx),
+        "userFills" => typed(&msg.data).map(HLEvent::UserFills),
+        "orderUpdates" => typed(&msg.data).map(HLEvent::OrderUpdates),
+        "user" => typed(&msg.data).map(HLEvent::UserEvent),
+        "userFundings" => typed(&msg.data).map(HLEvent::UserFundings),
+        "subscriptionResponse" => Some(HLEvent::SubscriptionResponse(msg.data.clone())),
+        "error" => Some(HLEvent::Error(
+            msg.data
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| msg.data.to_string()),
+        )),
+        "pong" => return None,
+        _ => None,
+    };
+
+    Some(event.unwrap_or(HLEvent::Other {
+        channel: msg.channel,
+        data: msg.data,
+    }))
+}
+
+// ====
+// Connection: backoff, keepalive, state
+// ====
+
+const MAINNET_URL: &str = "wss://api.hyperliquid.xyz/ws";
+const TESTNET_URL: &str = "wss://api.hyperliquid-testnet.xyz/ws";
+
+#[derive(Debug, Clone)]
+pub struct WsConfig {
+    /// Interval of client pings (`{"method":"ping"}`); the server disconnects after 60s of silence
+    pub ping_interval: Duration,
+    /// Without an incoming message the connection counts as stale after this
+    pub stale_after: Duration,
+    /// Without an incoming message the client reconnects after this
+    pub dead_after: Duration,
+    /// First wait before a reconnect, doubles per attempt
+    pub backoff_base: Duration,
+    pub backoff_max: Duration,
+    /// Server URL override, e.g. a local WsReplayServer
+    pub url: Option<String>,
+    /// Also write every received frame to this file (see ws_replay). Set per
+    /// instance: two clients recording into one file interleave their frames.
+    pub record_path: Option<PathBuf>,
+    /// Capacity of the event channel to the consumer (see `HyperliquidWs`)
+    pub event_buffer: usize,
+}
+
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_secs(15),
+            stale_after: Duration::from_secs(5),
+            dead_after: Duration::from_secs(30),
+            backoff_base: Duration::from_millis(500),
+            backoff_max: Duration::from_secs(30),
+            url: None,
+            record_path: None,
+            event_buffer: 4096,
+        }
+    }
+}
 
-        let url = if is_testnet {
-            "wss://api.hyperliquid-testnet.xyz/ws"
+impl WsConfig {
+    /// Default plus HL_WS_URL (server URL); recording stays off
+    pub fn from_env() -> Self {
+        Self {
+            url: std::env::var("HL_WS_URL").ok().filter(|u| !u.is_empty()),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Connected, all remembered subscriptions were (re)sent
+    Connected,
+    /// Connected, but no message for `silent_ms`
+    Stale { silent_ms: u64 },
+    /// Connection lost, next attempt after `delay_ms`
+    Reconnecting { attempt: u32, delay_ms: u64 },
+}
+
+impl ConnectionState {
+    pub fn is_live(&self) -> bool {
+        matches!(self, ConnectionState::Connected)
+    }
+}
+
+/// Exponential backoff with equal jitter: one half fixed, the other half
+/// scaled by `jitter` in [0, 1). `attempt` starts at 1.
+pub fn backoff_delay(attempt: u32, base: Duration, max: Duration, jitter: f64) -> Duration {
+    let exp = base
+        .saturating_mul(1u32 << attempt.saturating_sub(1).min(16))
+        .min(max);
+    let half = exp / 2;
+    half + half.mul_f64(jitter.clamp(0.0, 1.0))
+}
+
+/// Pseudo-random value in [0, 1) from the system time (splitmix64), good enough for jitter
+fn jitter() -> f64 {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Remembered subscriptions, resent after a reconnect
+fn remember(subscriptions: &mut Vec<Subscription>, cmd: &WsCommand) {
+    match cmd {
+        WsCommand::Subscribe(s) => {
+            if !subscriptions.contains(s) {
+                subscriptions.push(s.clone());
+            }
+        }
+        WsCommand::Unsubscribe(s) => subscriptions.retain(|x| x != s),
+    }
+}
+
+enum SessionEnd {
+    /// HyperliquidWs was dropped
+    Shutdown,
+    /// Connection gone; `received`: at least one message arrived
+    Lost { received: bool },
+}
+
+struct Supervisor {
+    url: String,
+    config: WsConfig,
+    recorder: Option<WsRecorder>,
+    subscriptions: Vec<Subscription>,
+    cmd_rx: mpsc::UnboundedReceiver<WsCommand>,
+    event_tx: mpsc::Sender<HLEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Supervisor {
+    async fn run(mut self, first: WsStream) {
+        let mut stream = Some(first);
+        let mut attempt = 0u32;
+
+        loop {
+            let ws = match stream.take() {
+                Some(ws) => ws,
+                None => {
+                    attempt += 1;
+                    let delay = backoff_delay(
+                        attempt,
+                        self.config.backoff_base,
+                        self.config.backoff_max,
+                        jitter(),
+                    );
+                    self.emit(HLEvent::Connection(ConnectionState::Reconnecting {
+                        attempt,
+                        delay_ms: delay.as_millis() as u64,
+                    }))
+                    .await;
+                    if !self.wait(delay).await {
+                        break;
+                    }
+                    match connect_async(&self.url).await {
+                        Ok((ws, _)) => ws,
+                        Err(e) => {
+                            eprintln!("⚠️ WS: Reconnect #{} fehlgeschlagen: {}", attempt, e);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            match self.session(ws).await {
+                SessionEnd::Shutdown => break,
+                SessionEnd::Lost { received } => {
+                    self.record(None);
+                    // Only a connection that actually delivered data resets the backoff
+                    if received {
+                        attempt = 0;
+                    }
+                    eprintln!("⚠️ WS: Verbindung verloren. Reconnect...");
+                }
+            }
+        }
+        println!("ℹ️ WS-Task beendet.");
+    }
+
+    /// Market data is dropped on a full buffer, everything else waits for space
+    async fn emit(&self, event: HLEvent) {
+        if event.is_market_data() {
+            if let Err(mpsc::error::TrySendError::Full(_)) = self.event_tx.try_send(event) {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
         } else {
-            "wss://api.hyperliquid.xyz/ws"
+            let _ = self.event_tx.send(event).await;
         }
-        .to_string();
+    }
 
-        let (ws_stream, _) = connect_async(&url).await.map_err(|e| anyhow!("Connect failed: {}", e))?;
-        println!("✅ WS: Verbindung zur Allianz-Zentrale steht.");
-        
-        let (mut write, mut read) = ws_stream.split();
-        let event_tx = tx.clone();
+    /// Record a frame (None = disconnect); a write error ends the recording
+    fn record(&mut self, text: Option<&str>) {
+        if let Some(recorder) = &mut self.recorder {
+            let res = match text {
+                Some(text) => recorder.record_text(text),
+                None => recorder.record_disconnect(),
+            };
+            if let Err(e) = res {
+                eprintln!("⚠️ WS: Aufzeichnung beendet: {}", e);
+                self.recorder = None;
+            }
+        }
+    }
 
-        tokio::spawn(async move {
-            loop {
-                tokio::select! {
-                    // Subscriptions from Collector
-                    res = sub_rx.recv() => {
-                        match res {
-                            Some(symbol) => {
-                                let sub_msg = json!({
-                                    "method": "subscribe",
-                                    "subscription": { "type": "l2Book", "coin": symbol }
-                                });
-                                if let Err(_) = write.send(Message::Text(sub_msg.to_string())).await {
-                                    break;
-                                }
+    /// Wait out the backoff while still taking subscriptions; false once the client is gone
+    async fn wait(&mut self, delay: Duration) -> bool {
+        let sleep = time::sleep(delay);
+        tokio::pin!(sleep);
+        loop {
+            tokio::select! {
+                _ = &mut sleep => return true,
+                cmd = self.cmd_rx.recv() => match cmd {
+                    Some(cmd) => remember(&mut self.subscriptions, &cmd),
+                    None => return false,
+                },
+            }
+        }
+    }
+
+    async fn session(&mut self, ws: WsStream) -> SessionEnd {
+        let (mut write, mut read) = ws.split();
+
+        for s in &self.subscriptions {
+            let msg = WsCommand::Subscribe(s.clone()).to_message();
+            if write.send(Message::Text(msg)).await.is_err() {
+                return SessionEnd::Lost { received: false };
+            }
+        }
+        self.emit(HLEvent::Connection(ConnectionState::Connected))
+            .await;
+
+        let mut last_msg = Instant::now();
+        let mut last_ping = Instant::now();
+        let mut stale = false;
+        let mut received = false;
+
+        let mut check = time::interval(self.config.stale_after.min(self.config.ping_interval) / 2);
+        check.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+        loop {
+            tokio::select! {
+                // Subscribe / Unsubscribe from Collector and Co.
+                res = self.cmd_rx.recv() => {
+                    match res {
+                        Some(cmd) => {
+                            remember(&mut self.subscriptions, &cmd);
+                            if write.send(Message::Text(cmd.to_message())).await.is_err() {
+                                return SessionEnd::Lost { received };
                             }
-                            None => break, // sub_tx was dropped
+                        }
+                        None => {
+                            // cmd_tx was dropped
+                            let _ = write.send(Message::Close(None)).await;
+                            return SessionEnd::Shutdown;
                         }
                     }
-                    
-                    // Incoming Messages
-                    msg_res = read.next() => {
-                        match msg_res {
-                            Some(Ok(Message::Text(text))) => {
-                                if let Ok(resp) = serde_json::from_str::<WSResponse>(&text) {
-                                    if let Some(snapshot) = resp.data {
-                                        let _ = event_tx.send(HLEvent::Snapshot(snapshot));
-                                    }
-                                }
-                            }
-                            Some(Ok(Message::Binary(bin))) => {
-                                if let Ok(resp) = serde_json::from_slice::<WSResponse>(&bin) {
-                                    if let Some(snapshot) = resp.data {
-                                        let _ = event_tx.send(HLEvent::Snapshot(snapshot));
-                                    }
-                                }
-                            }
-                            Some(Ok(Message::Ping(payload))) => {
-                                // Server -> Client Ping: Respond with Pong
-                                let _ = write.send(Message::Pong(payload)).await;
-                            }
-                            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
-                                break;
-                            }
-                            _ => {}
+                }
+
+                // Incoming Messages
+                msg_res = read.next() => {
+                    let msg = match msg_res {
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                            return SessionEnd::Lost { received };
+                        }
+                        Some(Ok(msg)) => msg,
+                    };
+
+                    // Every message (pong included) counts as a sign of life
+                    last_msg = Instant::now();
+                    received = true;
+                    if stale {
+                        stale = false;
+                        self.emit(HLEvent::Connection(ConnectionState::Connected))
+                            .await;
+                    }
+
+                    let event = match msg {
+                        Message::Text(text) => {
+                            self.record(Some(&text));
+                            parse_message(&text)
+                        }
+                        Message::Binary(bin) => std::str::from_utf8(&bin).ok().and_then(|text| {
+                            self.record(Some(text));
+                            parse_message(text)
+                        }),
+                        Message::Ping(payload) => {
+                            // Server -> Client Ping: Respond with Pong
+                            let _ = write.send(Message::Pong(payload)).await;
+                            None
+                        }
+                        _ => None,
+                    };
+                    if let Some(event) = event {
+                        self.emit(event).await;
+                    }
+                }
+
+                // Keepalive and silence detection
+                _ = check.tick() => {
+                    let silent = last_msg.elapsed();
+                    if silent >= self.config.dead_after {
+                        return SessionEnd::Lost { received };
+                    }
+                    if silent >= self.config.stale_after && !stale {
+                        stale = true;
+                        self.emit(HLEvent::Connection(ConnectionState::Stale {
+                            silent_ms: silent.as_millis() as u64,
+                        }))
+                        .await;
+                    }
+                    if last_ping.elapsed() >= self.config.ping_interval {
+                        last_ping = Instant::now();
+                        let ping = json!({ "method": "ping" }).to_string();
+                        if write.send(Message::Text(ping)).await.is_err() {
+                            return SessionEnd::Lost { received };
                         }
                     }
                 }
             }
-            println!("ℹ️ WS-Task beendet.");
-        });
+        }
+    }
+}
+
+// ====
+// Client
+// ====
 
-        Ok(Self { rx, sub_tx })
+/// Self-healing client: reconnects with backoff after a drop and resends all
+/// active subscriptions. State changes arrive as `HLEvent::Connection`.
+///
+/// Events are buffered up to `WsConfig::event_buffer`. When the buffer is full,
+/// market data (see `HLEvent::is_market_data`) is dropped and counted in
+/// `dropped_events`; the next book or trade supersedes it. Execution and
+/// connection events are never dropped: the supervisor waits for space and
+/// stops reading the socket meanwhile, so a consumer that stalls for longer
+/// than the server's 60s timeout gets disconnected.
+pub struct HyperliquidWs {
+    rx: mpsc::Receiver<HLEvent>,
+    cmd_tx: mpsc::UnboundedSender<WsCommand>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl HyperliquidWs {
+    pub async fn new(is_testnet: bool) -> Result<Self> {
+        Self::with_config(is_testnet, WsConfig::from_env()).await
     }
 
-    pub async fn subscribe_l2(&self, symbol: &str) -> Result<()> {
-        self.sub_tx
-            .send(symbol.to_string())
+    /// `config.url` takes precedence over mainnet/testnet
+    pub async fn with_config(is_testnet: bool, config: WsConfig) -> Result<Self> {
+        let url = match &config.url {
+            Some(url) => url.clone(),
+            None if is_testnet => TESTNET_URL.to_string(),
+            None => MAINNET_URL.to_string(),
+        };
+        Self::with_url(&url, config).await
+    }
+
+    /// Only the first connect can fail; after that the supervisor takes over
+    pub async fn with_url(url: &str, config: WsConfig) -> Result<Self> {
+        let (event_tx, rx) = mpsc::channel(config.event_buffer.max(1));
+        let (cmd_tx, cmd_rx) = mpsc::unbounded_channel::<WsCommand>();
+        let dropped = Arc::new(AtomicU64::new(0));
+
+        let recorder = match &config.record_path {
+            Some(path) => Some(WsRecorder::create(path)?),
+            None => None,
+        };
+
+        let (ws_stream, _) = connect_async(url)
+            .await
+            .map_err(|e| anyhow!("Connect failed: {}", e))?;
+        println!("✅ WS: Verbindung zur Allianz-Zentrale steht.");
+
+        let supervisor = Supervisor {
+            url: url.to_string(),
+            config,
+            recorder,
+            subscriptions: Vec::new(),
+            cmd_rx,
+            event_tx,
+            dropped: dropped.clone(),
+        };
+        tokio::spawn(supervisor.run(ws_stream));
+
+        Ok(Self {
+            rx,
+            cmd_tx,
+            dropped,
+        })
+    }
+
+    /// Market data events dropped so far because the buffer was full
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub async fn subscribe(&self, subscription: Subscription) -> Result<()> {
+        self.cmd_tx
+            .send(WsCommand::Subscribe(subscription))
             .map_err(|e| anyhow!("Sub-Error: {}", e))
     }
 
+    pub async fn unsubscribe(&self, subscription: Subscription) -> Result<()> {
+        self.cmd_tx
+            .send(WsCommand::Unsubscribe(subscription))
+            .map_err(|e| anyhow!("Unsub-Error: {}", e))
+    }
+
+    pub async fn subscribe_l2(&self, symbol: &str) -> Result<()> {
+        self.subscribe(Subscription::L2Book {
+            coin: symbol.to_string(),
+        })
+        .await
+    }
+
+    pub async fn subscribe_trades(&self, symbol: &str) -> Result<()> {
+        self.subscribe(Subscription::Trades {
+            coin: symbol.to_string(),
+        })
+        .await
+    }
+
+    pub async fn subscribe_bbo(&self, symbol: &str) -> Result<()> {
+        self.subscribe(Subscription::Bbo {
+            coin: symbol.to_string(),
+        })
+        .await
+    }
+
+    pub async fn subscribe_candle(&self, symbol: &str, interval: &str) -> Result<()> {
+        self.subscribe(Subscription::Candle {
+            coin: symbol.to_string(),
+            interval: interval.to_string(),
+        })
+        .await
+    }
+
+    pub async fn subscribe_all_mids(&self) -> Result<()> {
+        self.subscribe(Subscription::AllMids).await
+    }
+
+    pub async fn subscribe_active_asset_ctx(&self, symbol: &str) -> Result<()> {
+        self.subscribe(Subscription::ActiveAssetCtx {
+            coin: symbol.to_string(),
+        })
+        .await
+    }
+
+    /// Own execution feed: fills, order updates, events and funding of one account
+    pub async fn subscribe_user(&self, user: &str) -> Result<()> {
+        let user = user.to_string();
+        self.subscribe(Subscription::UserFills { user: user.clone() })
+            .await?;
+        self.subscribe(Subscription::OrderUpdates { user: user.clone() })
+            .await?;
+        self.subscribe(Subscription::UserEvents { user: user.clone() })
+            .await?;
+        self.subscribe(Subscription::UserFundings { user }).await
+    }
+
+    /// Next event of any channel
+    pub async fn next_event(&mut self) -> Option<HLEvent> {
+        self.rx.recv().await
+    }
+
+    /// Next L2 snapshot; other events (connection states included) are discarded
     pub async fn next_snapshot(&mut self) -> Option<L2Snapshot> {
         while let Some(event) = self.rx.recv().await {
-            match event {
-                HLEvent::Snapshot(s) => return Some(s),
+            if let HLEvent::Snapshot(s) = event {
+                return Some(s);
             }
         }
         None
     }
+
+    /// Checked books of the subscribed coins until the receiver is dropped;
+    /// snapshots that fail the integrity check are logged and skipped
+    pub fn into_books(mut self) -> mpsc::Receiver<OrderBook> {
+        let (tx, rx) = mpsc::channel(1024);
+        tokio::spawn(async move {
+            let mut books: HashMap<String, OrderBook> = HashMap::new();
+            while let Some(snapshot) = self.next_snapshot().await {
+                let book = match books.get_mut(&snapshot.coin) {
+                    Some(book) => book.apply(&snapshot).map(|()| book.clone()),
+                    None => OrderBook::from_snapshot(&snapshot).inspect(|book| {
+                        books.insert(snapshot.coin.clone(), book.clone());
+                    }),
+                };
+                match book {
+                    Ok(book) => {
+                        if tx.send(book).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => eprintln!("⚠️ {} verworfen: {}", snapshot.coin, e),
+                }
+            }
+        });
+        rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_messages() {
+        let sub = WsCommand::Subscribe(Subscription::Candle {
+            coin: "SOL".into(),
+            interval: "1m".into(),
+        });
+        let parsed: Value = serde_json::from_str(&sub.to_message()).unwrap();
+        assert_eq!(
+            parsed,
+            json!({
+                "method": "subscribe",
+                "subscription": { "type": "candle", "coin": "SOL", "interval": "1m" }
+            })
+        );
+        let unsub = WsCommand::Unsubscribe(Subscription::AllMids);
+        let parsed: Value = serde_json::from_str(&unsub.to_message()).unwrap();
+        assert_eq!(
+            parsed,
+            json!({ "method": "unsubscribe", "subscription": { "type": "allMids" } })
+        );
+        let user = serde_json::to_string(&Subscription::UserEvents {
+            user: "0xabc".into(),
+        })
+        .unwrap();
+        assert_eq!(user, r#"{"type":"userEvents","user":"0xabc"}"#);
+    }
+
+    #[test]
+    fn test_parse_market_channels() {
+        let book = r#"{"channel":"l2Book","data":{"coin":"BTC","time":1,"levels":[[{"px":"100","sz":"1","n":1}],[{"px":"101","sz":"2","n":1}]]}}"#;
+        let Some(HLEvent::Snapshot(s)) = parse_message(book) else {
+            panic!("expected snapshot");
+        };
+        assert_eq!(s.levels.asks[0].px, "101");
+
+        let trades = r#"{"channel":"trades","data":[{"coin":"BTC","side":"B","px":"100.5","sz":"0.1","time":2,"hash":"0x01","tid":7,"users":["0xa","0xb"]}]}"#;
+        let Some(HLEvent::Trades(t)) = parse_message(trades) else {
+            panic!("expected trades");
+        };
+        assert!(t[0].side.is_buy());
+
+        let bbo = r#"{"channel":"bbo","data":{"coin":"BTC","time":3,"bbo":[{"px":"100","sz":"1","n":2},null]}}"#;
+        let Some(HLEvent::Bbo(b)) = parse_message(bbo) else {
+            panic!("expected bbo");
+        };
+        assert!(b.bbo[1].is_none());
+
+        let candle = r#"{"channel":"candle","data":{"t":0,"T":59999,"s":"SOL","i":"1m","o":"1","c":"2","h":"3","l":"0.5","v":"10","n":4}}"#;
+        let Some(HLEvent::Candle(c)) = parse_message(candle) else {
+            panic!("expected candle");
+        };
+        assert_eq!(c.close_time, 59999);
+
+        let mids = r#"{"channel":"allMids","data":{"mids":{"BTC":"100.5"}}}"#;
+        let Some(HLEvent::AllMids(m)) = parse_message(mids) else {
+            panic!("expected mids");
+        };
+        assert_eq!(m["BTC"], "100.5");
+
+        let ctx = r#"{"channel":"activeAssetCtx","data":{"coin":"BTC","ctx":{"dayNtlVlm":"1","prevDayPx":"2","markPx":"3","midPx":"3","funding":"0.0001","openInterest":"5","oraclePx":"3"}}}"#;
+        let Some(HLEvent::ActiveAssetCtx(a)) = parse_message(ctx) else {
+            panic!("expected ctx");
+        };
+        assert_eq!(a.ctx.funding.as_deref(), Some("0.0001"));
+    }
+
+    #[test]
+    fn test_parse_user_channels() {
+        let updates = r#"{"channel":"orderUpdates","data":[{"order":{"coin":"ETH","side":"A","limitPx":"2000","sz":"0.5","oid":9,"timestamp":1,"origSz":"1"},"status":"open","statusTimestamp":2}]}"#;
+        let Some(HLEvent::OrderUpdates(u)) = parse_message(updates) else {
+            panic!("expected order updates");
+        };
+        assert_eq!(u[0].order.oid, 9);
+
+        let cancel = r#"{"channel":"user","data":{"nonUserCancel":[{"coin":"ETH","oid":9}]}}"#;
+        assert_eq!(
+            parse_message(cancel),
+            Some(HLEvent::UserEvent(WsUserEvent::NonUserCancel(vec![
+                NonUserCancel {
+                    coin: "ETH".into(),
+                    oid: 9
+                }
+            ])))
+        );
+
+        let fundings = r#"{"channel":"userFundings","data":{"isSnapshot":true,"user":"0xa","fundings":[{"time":1,"coin":"ETH","usdc":"-0.5","szi":"1","fundingRate":"0.0001"}]}}"#;
+        let Some(HLEvent::UserFundings(f)) = parse_message(fundings) else {
+            panic!("expected fundings");
+        };
+        assert!(f.is_snapshot);
+
+        let unknown = r#"{"channel":"webData2","data":{"x":1}}"#;
+        assert!(matches!(
+            parse_message(unknown),
+            Some(HLEvent::Other { .. })
+        ));
+        assert_eq!(parse_message(r#"{"channel":"pong"}"#), None);
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let base = Duration::from_millis(500);
+        let max = Duration::from_secs(30);
+        assert_eq!(backoff_delay(1, base, max, 0.0), Duration::from_millis(250));
+        assert_eq!(backoff_delay(1, base, max, 1.0), Duration::from_millis(500));
+        assert_eq!(
+            backoff_delay(3, base, max, 0.5),
+            Duration::from_millis(1500)
+        );
+        // Capped, even after very many attempts
+        assert_eq!(backoff_delay(40, base, max, 1.0), max);
+        for _ in 0..100 {
+            let j = jitter();
+            assert!((0.0..1.0).contains(&j));
+        }
+    }
+
+    #[test]
+    fn test_remember_subscriptions() {
+        let mut subs = Vec::new();
+        let btc = Subscription::L2Book { coin: "BTC".into() };
+        remember(&mut subs, &WsCommand::Subscribe(btc.clone()));
+        remember(&mut subs, &WsCommand::Subscribe(btc.clone()));
+        remember(&mut subs, &WsCommand::Subscribe(Subscription::AllMids));
+        assert_eq!(subs.len(), 2);
+        remember(&mut subs, &WsCommand::Unsubscribe(btc));
+        assert_eq!(subs, vec![Subscription::AllMids]);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_replays_subscriptions() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        // Server: drop the first connection after the subscribe, the second must resend it
+        let server = tokio::spawn(async move {
+            let mut received = Vec::new();
+            for _ in 0..2 {
+                let (tcp, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+                while let Some(Ok(msg)) = ws.next().await {
+                    if let Message::Text(text) = msg {
+                        let value: Value = serde_json::from_str(&text).unwrap();
+                        if value["method"] == "subscribe" {
+                            received.push(value["subscription"].clone());
+                            break;
+                        }
+                    }
+                }
+                if received.len() == 2 {
+                    let book =
+                        r#"{"channel":"l2Book","data":{"coin":"BTC","time":1,"levels":[[],[]]}}"#;
+                    ws.send(Message::Text(book.into())).await.unwrap();
+                    // Keep open until the client has read the data
+                    let _ = ws.next().await;
+                }
+            }
+            received
+        });
+
+        let config = WsConfig {
+            backoff_base: Duration::from_millis(10),
+            backoff_max: Duration::from_millis(20),
+            ..WsConfig::default()
+        };
+        let mut ws = HyperliquidWs::with_url(&url, config).await.unwrap();
+        ws.subscribe_l2("BTC").await.unwrap();
+
+        let mut states = Vec::new();
+        let snapshot = time::timeout(Duration::from_secs(5), async {
+            loop {
+                match ws.next_event().await {
+                    Some(HLEvent::Connection(state)) => states.push(state),
+                    Some(HLEvent::Snapshot(s)) => return s,
+                    other => panic!("unexpected event {:?}", other),
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(snapshot.coin, "BTC");
+        assert_eq!(states.first(), Some(&ConnectionState::Connected));
+        assert!(matches!(
+            states[1],
+            ConnectionState::Reconnecting { attempt: 1, .. }
+        ));
+        assert_eq!(states.last(), Some(&ConnectionState::Connected));
+
+        drop(ws);
+        let received = server.await.unwrap();
+        assert_eq!(received[0], json!({ "type": "l2Book", "coin": "BTC" }));
+        assert_eq!(received[0], received[1]);
+    }
+
+    #[tokio::test]
+    async fn test_full_buffer_drops_market_data_only() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            let book = r#"{"channel":"l2Book","data":{"coin":"BTC","time":1,"levels":[[],[]]}}"#;
+            for _ in 0..5 {
+                ws.send(Message::Text(book.into())).await.unwrap();
+            }
+            let update = r#"{"channel":"orderUpdates","data":[{"order":{"coin":"ETH","side":"A","limitPx":"2000","sz":"0.5","oid":9,"timestamp":1},"status":"open","statusTimestamp":2}]}"#;
+            ws.send(Message::Text(update.into())).await.unwrap();
+            let _ = ws.next().await;
+        });
+
+        let config = WsConfig {
+            event_buffer: 2,
+            ..WsConfig::default()
+        };
+        let mut ws = HyperliquidWs::with_url(&url, config).await.unwrap();
+        // Consumer lags: Connected and one book fill the buffer, the order update waits
+        time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(ws.dropped_events(), 4);
+
+        let mut events = Vec::new();
+        for _ in 0..3 {
+            events.push(ws.next_event().await.unwrap());
+        }
+        assert_eq!(events[0], HLEvent::Connection(ConnectionState::Connected));
+        assert!(matches!(events[1], HLEvent::Snapshot(_)));
+        assert!(matches!(&events[2], HLEvent::OrderUpdates(u) if u[0].order.oid == 9));
+
+        drop(ws);
+        server.await.unwrap();
+    }
 }
@@ -0,0 +1,412 @@
+// exchange/ws_replay.rs
+// Record-and-replay of the raw Hyperliquid websocket protocol
+//
+// WsRecorder appends every frame the client receives to a JSONL file (one
+// RecordedFrame per line, Unix-ms timestamps) from its own writer thread. WsReplayServer serves such a
+// file on a local port, so HyperliquidWs and everything built on it can run
+// against recorded traffic instead of the network.
+
+use anyhow::{anyhow, Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self as std_mpsc, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration, Instant};
+use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
+
+use crate::replay::ReplaySpeed;
+
+/// One line of a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RecordedFrame {
+    /// Text frame sent by the server
+    Text { t: u64, data: String },
+    /// Connection lost; the replay server drops the client at this point
+    Disconnect { t: u64 },
+}
+
+impl RecordedFrame {
+    pub fn text(t: u64, data: impl Into<String>) -> Self {
+        RecordedFrame::Text {
+            t,
+            data: data.into(),
+        }
+    }
+
+    /// Unix millis at which the frame was received
+    pub fn time(&self) -> u64 {
+        match self {
+            RecordedFrame::Text { t, .. } | RecordedFrame::Disconnect { t } => *t,
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Load a recording written by WsRecorder (or by hand)
+pub fn load_frames(path: impl AsRef<Path>) -> Result<Vec<RecordedFrame>> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(l) if l.trim().is_empty()))
+        .map(|(i, line)| {
+            serde_json::from_str(&line?).map_err(|e| anyhow!("{}:{}: {}", path.display(), i + 1, e))
+        })
+        .collect()
+}
+
+/// Write a recording in one go
+pub fn save_frames(path: impl AsRef<Path>, frames: &[RecordedFrame]) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for frame in frames {
+        serde_json::to_writer(&mut writer, frame)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// ====
+// Recorder
+// ====
+
+/// Frames buffered between the websocket task and the writer thread
+const RECORD_QUEUE: usize = 4096;
+/// The buffered rest reaches the file at the latest after this interval
+const RECORD_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Appends received frames to a JSONL file
+///
+/// `record` only queues the frame; a writer thread does the file IO and
+/// flushes every second, so a crashed run loses at most the last second.
+/// When the queue is full the frame is dropped and counted (`dropped`)
+/// rather than blocking the websocket task. Dropping the recorder writes
+/// the rest and waits for the writer thread.
+pub struct WsRecorder {
+    tx: Option<SyncSender<RecordedFrame>>,
+    writer: Option<thread::JoinHandle<()>>,
+    dropped: usize,
+}
+
+impl WsRecorder {
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_flush_interval(path, RECORD_FLUSH_INTERVAL)
+    }
+
+    pub fn with_flush_interval(path: impl AsRef<Path>, flush_interval: Duration) -> Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("open {}", path.display()))?;
+        let (tx, rx) = std_mpsc::sync_channel::<RecordedFrame>(RECORD_QUEUE);
+        let name = path.display().to_string();
+
+        let writer = thread::Builder::new()
+            .name("ws-recorder".into())
+            .spawn(move || {
+                let mut writer = BufWriter::new(file);
+                let mut last_flush = std::time::Instant::now();
+                let result = loop {
+                    match rx.recv_timeout(flush_interval) {
+                        Ok(frame) => {
+                            let written = serde_json::to_writer(&mut writer, &frame)
+                                .map_err(std::io::Error::from)
+                                .and_then(|()| writer.write_all(b"\n"));
+                            if let Err(e) = written {
+                                break Err(e);
+                            }
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => break writer.flush(),
+                    }
+                    if last_flush.elapsed() >= flush_interval {
+                        if let Err(e) = writer.flush() {
+                            break Err(e);
+                        }
+                        last_flush = std::time::Instant::now();
+                    }
+                };
+                // A failed writer drops the receiver; the next record() reports it
+                if let Err(e) = result {
+                    eprintln!("⚠️ WS: Aufzeichnung {} fehlgeschlagen: {}", name, e);
+                }
+            })
+            .context("spawn ws-recorder thread")?;
+
+        Ok(Self {
+            tx: Some(tx),
+            writer: Some(writer),
+            dropped: 0,
+        })
+    }
+
+    /// Queue a frame; errors only once the writer thread has stopped
+    pub fn record(&mut self, frame: RecordedFrame) -> Result<()> {
+        let Some(tx) = &self.tx else {
+            return Err(anyhow!("recorder closed"));
+        };
+        match tx.try_send(frame) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                self.dropped += 1;
+                Ok(())
+            }
+            Err(TrySendError::Disconnected(_)) => Err(anyhow!("recorder writer stopped")),
+        }
+    }
+
+    pub fn record_text(&mut self, data: &str) -> Result<()> {
+        self.record(RecordedFrame::text(now_ms(), data))
+    }
+
+    pub fn record_disconnect(&mut self) -> Result<()> {
+        self.record(RecordedFrame::Disconnect { t: now_ms() })
+    }
+
+    /// Frames lost because the writer thread fell behind
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+}
+
+impl Drop for WsRecorder {
+    fn drop(&mut self) {
+        // Closing the channel makes the writer flush and exit
+        self.tx.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+// ====
+// Replay server
+// ====
+
+/// Local websocket server playing back a recording
+///
+/// Playback on a connection starts with the first client message (usually a
+/// subscribe) and keeps the recorded gaps, scaled by `speed`. A Disconnect
+/// frame drops the client; the next connection continues after it. Client
+/// pings are answered with pong, all other client messages are kept for
+/// assertions. After the last frame the connection stays open.
+pub struct WsReplayServer {
+    url: String,
+    received: Arc<Mutex<Vec<String>>>,
+    connections: Arc<AtomicUsize>,
+    sent: Arc<AtomicUsize>,
+    handle: JoinHandle<()>,
+}
+
+impl WsReplayServer {
+    /// Serve on a free port of 127.0.0.1
+    pub async fn start(frames: Vec<RecordedFrame>, speed: ReplaySpeed) -> Result<Self> {
+        Self::bind("127.0.0.1:0", frames, speed).await
+    }
+
+    pub async fn bind(addr: &str, frames: Vec<RecordedFrame>, speed: ReplaySpeed) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let connections = Arc::new(AtomicUsize::new(0));
+        let sent = Arc::new(AtomicUsize::new(0));
+
+        let state = ServerState {
+            frames,
+            cursor: 0,
+            speed,
+            received: received.clone(),
+            sent: sent.clone(),
+        };
+        let handle = tokio::spawn(state.run(listener, connections.clone()));
+
+        Ok(Self {
+            url,
+            received,
+            connections,
+            sent,
+            handle,
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Client messages except pings, in arrival order
+    pub fn received(&self) -> Vec<String> {
+        self.received.lock().unwrap().clone()
+    }
+
+    /// Received messages that are subscribe requests, as `subscription` objects
+    pub fn subscriptions(&self) -> Vec<Value> {
+        self.received()
+            .iter()
+            .filter_map(|m| serde_json::from_str::<Value>(m).ok())
+            .filter(|v| v["method"] == "subscribe")
+            .map(|v| v["subscription"].clone())
+            .collect()
+    }
+
+    /// Accepted websocket connections so far
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::Relaxed)
+    }
+
+    /// Recorded frames played back so far (including disconnects)
+    pub fn frames_sent(&self) -> usize {
+        self.sent.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for WsReplayServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+struct ServerState {
+    frames: Vec<RecordedFrame>,
+    cursor: usize,
+    speed: ReplaySpeed,
+    received: Arc<Mutex<Vec<String>>>,
+    sent: Arc<AtomicUsize>,
+}
+
+impl ServerState {
+    async fn run(mut self, listener: TcpListener, connections: Arc<AtomicUsize>) {
+        while let Ok((tcp, _)) = listener.accept().await {
+            let Ok(ws) = accept_async(tcp).await else {
+                continue;
+            };
+            connections.fetch_add(1, Ordering::Relaxed);
+            self.serve(ws).await;
+        }
+    }
+
+    fn gap(&self, from: u64, to: u64) -> Duration {
+        let gap = Duration::from_millis(to.saturating_sub(from));
+        match self.speed {
+            ReplaySpeed::Realtime => gap,
+            ReplaySpeed::Multiplier(x) => gap.div_f64(x),
+            ReplaySpeed::AsFastAsPossible => Duration::ZERO,
+        }
+    }
+
+    async fn serve(&mut self, ws: WebSocketStream<TcpStream>) {
+        let (mut write, mut read) = ws.split();
+        let mut started = false;
+        let mut next_at = Instant::now();
+
+        loop {
+            let pending = started && self.cursor < self.frames.len();
+            tokio::select! {
+                msg = read.next() => match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        let is_ping = serde_json::from_str::<Value>(&text)
+                            .map(|v| v["method"] == "ping")
+                            .unwrap_or(false);
+                        if is_ping {
+                            let pong = r#"{"channel":"pong"}"#.to_string();
+                            if write.send(Message::Text(pong)).await.is_err() {
+                                return;
+                            }
+                        } else {
+                            self.received.lock().unwrap().push(text);
+                        }
+                        if !started {
+                            started = true;
+                            next_at = Instant::now();
+                        }
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(_)) | None => return,
+                },
+
+                _ = time::sleep_until(next_at), if pending => {
+                    let frame = self.frames[self.cursor].clone();
+                    self.cursor += 1;
+                    self.sent.fetch_add(1, Ordering::Relaxed);
+
+                    match &frame {
+                        RecordedFrame::Text { data, .. } => {
+                            if write.send(Message::Text(data.clone())).await.is_err() {
+                                return;
+                            }
+                        }
+                        // Drop without close handshake, like a lost connection
+                        RecordedFrame::Disconnect { .. } => return,
+                    }
+                    if let Some(next) = self.frames.get(self.cursor) {
+                        next_at = Instant::now() + self.gap(frame.time(), next.time());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_file_roundtrip() {
+        let path = std::env::temp_dir().join(format!("ws_frames_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut recorder = WsRecorder::create(&path).unwrap();
+        recorder.record_text(r#"{"channel":"pong"}"#).unwrap();
+        recorder.record_disconnect().unwrap();
+        assert_eq!(recorder.dropped(), 0);
+        drop(recorder);
+
+        let frames = load_frames(&path).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert!(
+            matches!(&frames[0], RecordedFrame::Text { data, .. } if data == r#"{"channel":"pong"}"#)
+        );
+        assert!(matches!(frames[1], RecordedFrame::Disconnect { .. }));
+        assert!(frames[0].time() <= frames[1].time());
+
+        let line = serde_json::to_string(&RecordedFrame::Disconnect { t: 5 }).unwrap();
+        assert_eq!(line, r#"{"kind":"disconnect","t":5}"#);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_recorder_flushes_while_running() {
+        let path = std::env::temp_dir().join(format!("ws_flush_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut recorder =
+            WsRecorder::with_flush_interval(&path, Duration::from_millis(20)).unwrap();
+        recorder.record_text(r#"{"channel":"pong"}"#).unwrap();
+
+        // Recorder still alive: only the interval flush makes the frame readable
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(load_frames(&path).unwrap().len(), 1);
+
+        drop(recorder);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,9 +1,12 @@
 // E:\MBCT\trading-core\src\lib.rs
 // THE ALLIANCE - Core Library Definitions
 
+pub mod clock;
 pub mod config;
 pub mod exchange;
 pub mod live_trading;
+pub mod physics;
+pub mod replay;
 pub mod service;
 pub mod universe; // NEU: Aktivierung der kinetischen Selektion
 
@@ -1,9 +1,15 @@
 // src/live_trading/paper_trading.rs
+// Strategy signals are executed on an OrderExecution venue, normally a
+// SimulatedExchange whose books the caller feeds. Cash and position come
+// from the venue's account state.
 use rust_decimal::Decimal;
 use std::sync::Arc;
 use std::time::Instant;
 use tracing::debug;
 
+use crate::exchange::connector::DEFAULT_SLIPPAGE;
+use crate::exchange::OrderExecution;
+
 use trading_common::backtest::strategy::{Signal, Strategy};
 use trading_common::data::cache::TickDataCache;
 use trading_common::data::repository::TickDataRepository;
@@ -12,12 +18,12 @@ use trading_common::data::types::{LiveStrategyLog, TickData};
 pub struct PaperTradingProcessor {
     strategy: Box<dyn Strategy + Send>,
     repository: Arc<TickDataRepository>,
+    venue: Arc<dyn OrderExecution>,
     initial_capital: Decimal,
 
     //Simple status tracking
     cash: Decimal,
     position: Decimal,
-    avg_cost: Decimal,
     total_trades: u64,
 }
 
@@ -25,15 +31,16 @@ impl PaperTradingProcessor {
     pub fn new(
         strategy: Box<dyn Strategy + Send>,
         repository: Arc<TickDataRepository>,
+        venue: Arc<dyn OrderExecution>,
         initial_capital: Decimal,
     ) -> Self {
         Self {
             strategy,
             repository,
+            venue,
             initial_capital,
             cash: initial_capital,
             position: Decimal::ZERO,
-            avg_cost: Decimal::ZERO,
             total_trades: 0,
         }
     }
@@ -56,10 +63,10 @@ impl PaperTradingProcessor {
         let signal = self.strategy.on_tick(tick);
 
         // 3. Execution of trading signals
-        let signal_type = self.execute_signal(&signal, tick)?;
+        let signal_type = self.execute_signal(&signal).await;
 
-        // 4. Calculate Portfolio Value
-        let portfolio_value = self.calculate_portfolio_value(tick.price);
+        // 4. Portfolio value from the venue
+        let portfolio_value = self.refresh_account(&tick.symbol).await?;
         let total_pnl = portfolio_value - self.initial_capital;
 
         // 5. Record to database
@@ -95,69 +102,47 @@ impl PaperTradingProcessor {
         Ok(())
     }
 
-    fn execute_signal(&mut self, signal: &Signal, tick: &TickData) -> Result<String, String> {
-        match signal {
-            Signal::Buy { quantity, .. } => {
-                let cost = quantity * tick.price;
-
-                if cost <= self.cash {
-                    if self.position == Decimal::ZERO {
-                        self.position = *quantity;
-                        self.avg_cost = tick.price;
-                    } else {
-                        let total_cost = (self.position * self.avg_cost) + cost;
-                        self.position += quantity;
-                        self.avg_cost = total_cost / self.position;
-                    }
-
-                    self.cash -= cost;
-                    self.total_trades += 1;
-
-                    debug!(
-                        "BUY executed: {} @ {}, position: {}, cash: {}",
-                        quantity, tick.price, self.position, self.cash
-                    );
-                    return Ok("BUY".to_string());
-                } else {
-                    debug!(
-                        "BUY signal ignored: insufficient cash ({} needed, {} available)",
-                        cost, self.cash
-                    );
-                }
-            }
+    async fn execute_signal(&mut self, signal: &Signal) -> String {
+        let (signal_type, result) = match signal {
+            Signal::Buy { symbol, quantity } => (
+                "BUY",
+                self.venue
+                    .market_open(symbol, true, *quantity, DEFAULT_SLIPPAGE)
+                    .await,
+            ),
+            // Long-only: Sell reduces the open position
+            Signal::Sell { symbol, quantity } => (
+                "SELL",
+                self.venue
+                    .market_close(symbol, Some(*quantity), DEFAULT_SLIPPAGE)
+                    .await,
+            ),
+            Signal::Hold => return "HOLD".to_string(),
+        };
 
-            Signal::Sell { quantity, .. } => {
-                if *quantity <= self.position {
-                    let proceeds = quantity * tick.price;
-                    self.cash += proceeds;
-                    self.position -= quantity;
-                    self.total_trades += 1;
-
-                    if self.position == Decimal::ZERO {
-                        self.avg_cost = Decimal::ZERO;
-                    }
-
-                    debug!(
-                        "SELL executed: {} @ {}, position: {}, cash: {}",
-                        quantity, tick.price, self.position, self.cash
-                    );
-                    return Ok("SELL".to_string());
-                } else {
-                    debug!(
-                        "SELL signal ignored: insufficient position ({} needed, {} available)",
-                        quantity, self.position
-                    );
-                }
+        match result {
+            Ok(oid) => {
+                self.total_trades += 1;
+                debug!("{} executed: oid {}", signal_type, oid);
+                signal_type.to_string()
+            }
+            Err(e) => {
+                debug!("{} signal ignored: {}", signal_type, e);
+                "HOLD".to_string()
             }
-
-            Signal::Hold => return Ok("HOLD".to_string()),
         }
-
-        Ok("HOLD".to_string())
     }
 
-    fn calculate_portfolio_value(&self, current_price: Decimal) -> Decimal {
-        self.cash + (self.position * current_price)
+    /// Update cash and position of `symbol` from the venue; returns the account value
+    async fn refresh_account(&mut self, symbol: &str) -> Result<Decimal, String> {
+        let state = self
+            .venue
+            .account_state(self.venue.account_address())
+            .await
+            .map_err(|e| format!("Venue error: {}", e))?;
+        self.cash = state.withdrawable();
+        self.position = state.position(symbol).map_or(Decimal::ZERO, |p| p.size());
+        Ok(state.account_value())
     }
 
     fn log_activity(
@@ -1,14 +1,21 @@
-// E:\MBCT\trading-core\src\bin\researcher\modules\archive.rs
-use crate::modules::chronos::MBCTFullRecord;
+// E:\MBCT\trading-core\src\physics\archive.rs
+// THE ALLIANCE - MBCT Archive
+// Persistiert abgeschlossene Forward-Label Records (SQLite + CSV)
+
+use super::chronos::MBCTFullRecord;
+use super::features::FeatureSet;
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
 use sqlx::{Pool, Sqlite};
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::str::FromStr;
+use trading_common::data::migrations;
 
 pub struct Archive {
     pool: Pool<Sqlite>,
     csv_path: String,
+    /// Fingerprint der Feature-Definitionen, die die Records erzeugt haben
+    feature_set: String,
 }
 
 impl Archive {
@@ -23,54 +30,35 @@ impl Archive {
             .connect_with(opts)
             .await?;
 
-        // Tabelle mit ret_377s erweitert
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS mbct_research_v2 (
-                timestamp INTEGER,
-                symbol TEXT,
-                price REAL,
-                entropy REAL,
-                pressure REAL,
-                nrg REAL,
-                regime TEXT,
-                symmetry REAL,
-                slope REAL,
-                ret_3s REAL,
-                ret_5s REAL,
-                ret_8s REAL,
-                ret_13s REAL,
-                ret_21s REAL,
-                ret_34s REAL,
-                ret_55s REAL,
-                ret_89s REAL,
-                ret_144s REAL,
-                ret_233s REAL,
-                ret_377s REAL,
-                z_entropy_21s REAL,
-                z_pressure_21s REAL,
-                z_nrg_21s REAL,
-                z_entropy_34s REAL,
-                z_pressure_34s REAL,
-                z_nrg_34s REAL
-            )",
-        )
-        .execute(&pool)
-        .await?;
+        // Schema (inkl. ret_377s) kommt aus den versionierten Migrationen
+        migrations::run_migrations(&pool).await?;
 
         Ok(Self {
             pool,
             csv_path: csv_path.to_string(),
+            feature_set: FeatureSet::default().fingerprint(),
         })
     }
 
+    pub fn with_feature_set(mut self, features: &FeatureSet) -> Self {
+        self.feature_set = features.fingerprint();
+        self
+    }
+
+    /// Ermöglicht dem ParamManager Zugriff auf den DB-Pool für die Kalibrierung
+    pub fn get_pool(&self) -> &Pool<Sqlite> {
+        &self.pool
+    }
+
     pub async fn store_batch(&self, records: Vec<MBCTFullRecord>) -> Result<(), sqlx::Error> {
         for record in records {
             sqlx::query(
                 "INSERT INTO mbct_research_v2 (
                     timestamp, symbol, price, entropy, pressure, nrg, regime, symmetry, slope,
                     ret_3s, ret_5s, ret_8s, ret_13s, ret_21s, ret_34s, ret_55s, ret_89s, ret_144s, ret_233s, ret_377s,
-                    z_entropy_21s, z_pressure_21s, z_nrg_21s, z_entropy_34s, z_pressure_34s, z_nrg_34s
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+                    z_entropy_21s, z_pressure_21s, z_nrg_21s, z_entropy_34s, z_pressure_34s, z_nrg_34s,
+                    feature_set
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
             )
             .bind(record.timestamp as i64)
             .bind(&record.symbol)
@@ -98,6 +86,7 @@ impl Archive {
             .bind(record.z_entropy_34s)
             .bind(record.z_pressure_34s)
             .bind(record.z_nrg_34s)
+            .bind(&self.feature_set)
             .execute(&self.pool)
             .await?;
 
@@ -115,19 +104,16 @@ impl Archive {
 
         // CSV Header mit ret_377s
         if file.metadata().unwrap().len() == 0 {
-            writeln!(file, "timestamp,symbol,price,entropy,pressure,nrg,regime,symmetry,slope,ret_3s,ret_5s,ret_8s,ret_13s,ret_21s,ret_34s,ret_55s,ret_89s,ret_144s,ret_233s,ret_377s,z_entropy_21s,z_pressure_21s,z_nrg_21s,z_entropy_34s,z_pressure_34s,z_nrg_34s").unwrap();
+            writeln!(file, "timestamp,symbol,price,entropy,pressure,nrg,regime,symmetry,slope,ret_3s,ret_5s,ret_8s,ret_13s,ret_21s,ret_34s,ret_55s,ret_89s,ret_144s,ret_233s,ret_377s,z_entropy_21s,z_pressure_21s,z_nrg_21s,z_entropy_34s,z_pressure_34s,z_nrg_34s,feature_set").unwrap();
         }
 
-        let f_opt = |opt: Option<f64>| {
-            opt.map(|v| format!("{:.8}", v))
-                .unwrap_or_else(|| "".to_string())
-        };
+        let f_opt = |opt: Option<f64>| opt.map(|v| format!("{:.8}", v)).unwrap_or_default();
 
         let regime_str = format!("{:?}", record.regime.regime);
 
         writeln!(
             file,
-            "{},{},{:.8},{:.4},{:.4},{:.4},{},{:.4},{:.8},{},{},{},{},{},{},{},{},{},{},{},{:.4},{:.4},{:.4},{:.4},{:.4},{:.4}",
+            "{},{},{:.8},{:.4},{:.4},{:.4},{},{:.4},{:.8},{},{},{},{},{},{},{},{},{},{},{},{:.4},{:.4},{:.4},{:.4},{:.4},{:.4},{}",
             record.timestamp,
             record.symbol,
             record.physics.price,
@@ -153,7 +139,8 @@ impl Archive {
             record.z_nrg_21s,
             record.z_entropy_34s,
             record.z_pressure_34s,
-            record.z_nrg_34s
+            record.z_nrg_34s,
+            self.feature_set
         ).unwrap();
     }
 }
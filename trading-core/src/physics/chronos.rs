@@ -1,12 +1,12 @@
-// E:\MBCT\trading-core\src\bin\trader\modules\chronos.rs
-// THE ALLIANCE - MBCT Chronos v2.0 (Trader Integration)
+// E:\MBCT\trading-core\src\physics\chronos.rs
+// THE ALLIANCE - MBCT Chronos v2.1 (Shared Forward-Labeling)
 // Fokus: Fibonacci Time-Horizons & Peak Detection
 
 use super::physicist::PhysicsState;
 use super::regime::RegimeState;
+use crate::clock::{elapsed_secs, SharedClock, WallClock};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::time::Instant;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MBCTFullRecord {
@@ -24,7 +24,7 @@ pub struct MBCTFullRecord {
     pub ret_89s: Option<f64>,
     pub ret_144s: Option<f64>,
     pub ret_233s: Option<f64>,
-    pub ret_377s: Option<f64>,
+    pub ret_377s: Option<f64>, // Deep-Time Horizon
     pub z_entropy_21s: f64,
     pub z_pressure_21s: f64,
     pub z_nrg_21s: f64,
@@ -32,27 +32,37 @@ pub struct MBCTFullRecord {
     pub z_pressure_34s: f64,
     pub z_nrg_34s: f64,
     pub is_complete: bool,
-    #[serde(skip, default = "Instant::now")]
-    #[allow(dead_code)]
-    pub created_at: Instant,
 }
 
 struct PeakCandidate {
     physics: PhysicsState,
     regime: RegimeState,
-    last_update: Instant,
+    last_update_ms: u64,
 }
 
 pub struct Chronos {
     pending_records: HashMap<String, Vec<MBCTFullRecord>>,
     active_peaks: HashMap<String, PeakCandidate>,
+    clock: SharedClock,
+}
+
+impl Default for Chronos {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Chronos {
     pub fn new() -> Self {
+        Self::with_clock(WallClock::shared())
+    }
+
+    /// Chronos mit injizierter Uhr (Replay, Tests)
+    pub fn with_clock(clock: SharedClock) -> Self {
         Self {
             pending_records: HashMap::new(),
             active_peaks: HashMap::new(),
+            clock,
         }
     }
 
@@ -84,14 +94,14 @@ impl Chronos {
                     peak.physics = physics.clone();
                     peak.regime = regime.clone();
                 }
-                peak.last_update = Instant::now();
+                peak.last_update_ms = self.clock.now_ms();
             } else {
                 self.active_peaks.insert(
                     symbol.to_string(),
                     PeakCandidate {
                         physics: physics.clone(),
                         regime: regime.clone(),
-                        last_update: Instant::now(),
+                        last_update_ms: self.clock.now_ms(),
                     },
                 );
             }
@@ -104,7 +114,7 @@ impl Chronos {
 
         let mut force_finalize = false;
         if let Some(peak) = self.active_peaks.get(symbol) {
-            if peak.last_update.elapsed().as_secs() > 10 {
+            if elapsed_secs(self.clock.as_ref(), peak.last_update_ms) > 10 {
                 force_finalize = true;
             }
         }
@@ -120,10 +130,7 @@ impl Chronos {
     }
 
     fn finalize_peak(&mut self, symbol: &str, peak: PeakCandidate) {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis();
+        let now = self.clock.now_ms() as u128;
 
         let record = MBCTFullRecord {
             timestamp: now,
@@ -148,16 +155,14 @@ impl Chronos {
             z_pressure_34s: 0.0,
             z_nrg_34s: 0.0,
             is_complete: false,
-            created_at: Instant::now(),
         };
 
         self.pending_records
             .entry(symbol.to_string())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(record);
     }
 
-    #[allow(dead_code)]
     pub fn update_and_flush(
         &mut self,
         symbol: &str,
@@ -167,12 +172,13 @@ impl Chronos {
     ) -> Vec<MBCTFullRecord> {
         let mut completed = Vec::new();
         if let Some(records) = self.pending_records.get_mut(symbol) {
-            let now = Instant::now();
+            let now = self.clock.now_ms() as u128;
             for r in records.iter_mut() {
                 if r.is_complete {
                     continue;
                 }
-                let elapsed = now.duration_since(r.created_at).as_secs();
+                // timestamp = Zeitpunkt der Peak-Finalisierung
+                let elapsed = (now.saturating_sub(r.timestamp) / 1000) as u64;
                 let p0 = r.physics.price;
                 let calc_ret = |p_s: f64, p_n: f64| {
                     if p_s <= 0.0 {
@@ -235,7 +241,6 @@ impl Chronos {
         completed
     }
 
-    #[allow(dead_code)]
     pub fn get_pending_count(&self) -> usize {
         self.pending_records
             .values()
@@ -244,3 +249,59 @@ impl Chronos {
             + self.active_peaks.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SimulatedClock;
+    use crate::physics::regime::MarketRegime;
+    use std::sync::Arc;
+
+    fn regime(symmetry_score: f64) -> RegimeState {
+        RegimeState {
+            regime: MarketRegime::Ballistic,
+            symmetry_score,
+            slope: 0.0,
+            reversion_speed: 0.0,
+            confidence: 1.0,
+        }
+    }
+
+    fn physics(price: f64) -> PhysicsState {
+        PhysicsState {
+            price,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_peak_is_finalized_at_most_extreme_point() {
+        let clock = Arc::new(SimulatedClock::new(1_000));
+        let mut chronos = Chronos::with_clock(clock.clone());
+
+        assert!(!chronos.observe_potential_hit("BTC", &physics(100.0), &regime(0.10), 0.2, 0.8));
+        assert!(!chronos.observe_potential_hit("BTC", &physics(99.0), &regime(0.05), 0.2, 0.8));
+        assert!(!chronos.observe_potential_hit("BTC", &physics(98.0), &regime(0.08), 0.2, 0.8));
+        // Zurück in die Mitte -> Peak wird finalisiert
+        clock.advance_ms(500);
+        assert!(chronos.observe_potential_hit("BTC", &physics(98.5), &regime(0.5), 0.2, 0.8));
+        assert_eq!(chronos.get_pending_count(), 1);
+
+        // Forward-Labels laufen auf der simulierten Uhr
+        clock.advance_ms(3_000);
+        assert!(chronos
+            .update_and_flush("BTC", 99.99, (0.0, 0.0, 0.0), (0.0, 0.0, 0.0))
+            .is_empty());
+
+        clock.advance_ms(377_000);
+        let done = chronos.update_and_flush("BTC", 100.0, (1.0, 2.0, 3.0), (4.0, 5.0, 6.0));
+        assert_eq!(done.len(), 1);
+        let record = &done[0];
+        assert_eq!(record.timestamp, 1_500);
+        assert_eq!(record.physics.price, 99.0);
+        assert!((record.ret_3s.unwrap() - 1.0).abs() < 1e-9);
+        assert!((record.ret_377s.unwrap() - (1.0 / 99.0) * 100.0).abs() < 1e-9);
+        assert_eq!(record.z_nrg_34s, 6.0);
+        assert_eq!(chronos.get_pending_count(), 0);
+    }
+}
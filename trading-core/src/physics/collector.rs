@@ -0,0 +1,573 @@
+// E:\MBCT\trading-core\src\physics\collector.rs
+// THE ALLIANCE - MBCT Collector v4.7 (Shared)
+// Fokus: 100ms Sampling geprüfter Orderbücher, Verbindungszustand aus dem selbstheilenden WS, Replay über dieselbe Schnittstelle
+// Live-Eingang: eigener Hyperliquid-WS (stream_provider) oder eine beliebige Venue (book_feed)
+
+use crate::clock::{SharedClock, WallClock};
+use crate::exchange::ws::{backoff_delay, ConnectionState, HLEvent, HyperliquidWs, WsConfig};
+use crate::exchange::{L2Snapshot, MarketDataStream, OrderBook};
+use crate::replay::ReplayProvider;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self as std_mpsc, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use tokio::time::{self, Duration};
+use trading_common::data::snapshot_store::SnapshotStore;
+
+pub struct CollectorStats {
+    pub messages_received: AtomicUsize,
+    pub snapshots_sampled: AtomicUsize,
+    /// Verworfene Snapshots (veraltet, gekreuzt, ungültige Level)
+    pub books_rejected: AtomicUsize,
+    /// Im Heartbeat übersprungene Bücher, deren letzter gültiger Snapshot zu alt ist
+    pub books_stale: AtomicUsize,
+    /// Nicht aufgezeichnete Snapshots, weil der Schreib-Thread nicht nachkam
+    pub records_dropped: AtomicUsize,
+}
+
+/// Puffer zwischen Stream und Schreib-Thread (Snapshots)
+const RECORD_QUEUE: usize = 4096;
+/// Spätestens nach diesem Intervall landet der gepufferte Rest auf der Platte
+const RECORD_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+/// Ältere Bücher (Exchange-Zeit gegen Uhr) gibt der Heartbeat nicht mehr weiter
+pub const DEFAULT_MAX_BOOK_AGE: Duration = Duration::from_secs(5);
+
+/// Snapshot-Store hinter einem eigenen Schreib-Thread: Kompression und Datei-IO blockieren nie den Stream
+struct SnapshotRecorder {
+    tx: SyncSender<L2Snapshot>,
+}
+
+impl SnapshotRecorder {
+    fn spawn(mut store: SnapshotStore, flush_interval: Duration) -> Self {
+        let (tx, rx) = std_mpsc::sync_channel::<L2Snapshot>(RECORD_QUEUE);
+        std::thread::Builder::new()
+            .name("snapshot-recorder".into())
+            .spawn(move || {
+                let mut last_flush = Instant::now();
+                loop {
+                    match rx.recv_timeout(flush_interval) {
+                        Ok(snapshot) => {
+                            if let Err(e) = store.append(&snapshot) {
+                                eprintln!("[COLLECTOR] Snapshot-Store Fehler: {}", e);
+                            }
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                    if last_flush.elapsed() >= flush_interval {
+                        if let Err(e) = store.flush() {
+                            eprintln!("[COLLECTOR] Snapshot-Store Flush-Fehler: {}", e);
+                        }
+                        last_flush = Instant::now();
+                    }
+                }
+                // Collector weg: Rest schreiben (Drop des Stores flusht ebenfalls)
+                if let Err(e) = store.flush() {
+                    eprintln!("[COLLECTOR] Snapshot-Store Flush-Fehler: {}", e);
+                }
+            })
+            .expect("snapshot recorder thread");
+        Self { tx }
+    }
+}
+
+pub struct Collector {
+    /// Geparstes Orderbuch je Symbol, nur mit Snapshots, die die Integritätsprüfung bestehen
+    /// (Eingang für Heartbeat und Replay)
+    pub books: Arc<DashMap<String, OrderBook>>,
+    pub stats: Arc<CollectorStats>,
+    is_testnet: bool,
+    clock: SharedClock,
+    max_book_age: Duration,
+    recorder: Option<SnapshotRecorder>,
+    connection: RwLock<Option<ConnectionState>>,
+    ws_config: WsConfig,
+}
+
+impl Collector {
+    pub fn new(is_testnet: bool) -> Self {
+        Self {
+            books: Arc::new(DashMap::new()),
+            stats: Arc::new(CollectorStats {
+                messages_received: AtomicUsize::new(0),
+                snapshots_sampled: AtomicUsize::new(0),
+                books_rejected: AtomicUsize::new(0),
+                books_stale: AtomicUsize::new(0),
+                records_dropped: AtomicUsize::new(0),
+            }),
+            is_testnet,
+            clock: WallClock::shared(),
+            max_book_age: DEFAULT_MAX_BOOK_AGE,
+            recorder: None,
+            connection: RwLock::new(None),
+            // Nur der Marktdaten-Stream des Collectors zeichnet auf, nicht jede WS-Instanz im Prozess
+            ws_config: WsConfig {
+                record_path: std::env::var("HL_WS_RECORD")
+                    .ok()
+                    .filter(|p| !p.is_empty())
+                    .map(PathBuf::from),
+                ..WsConfig::from_env()
+            },
+        }
+    }
+
+    /// Schreibt jeden empfangenen Roh-Snapshot zusätzlich in den Snapshot-Store
+    /// (eigener Schreib-Thread, Flush alle 5s)
+    pub fn with_recorder(self, store: SnapshotStore) -> Self {
+        self.with_recorder_flush(store, RECORD_FLUSH_INTERVAL)
+    }
+
+    /// Wie with_recorder, mit eigenem Flush-Intervall
+    pub fn with_recorder_flush(mut self, store: SnapshotStore, flush_interval: Duration) -> Self {
+        self.recorder = Some(SnapshotRecorder::spawn(store, flush_interval));
+        self
+    }
+
+    /// Maximales Alter eines Buchs im Heartbeat (Standard: DEFAULT_MAX_BOOK_AGE)
+    pub fn with_max_book_age(mut self, max_age: Duration) -> Self {
+        self.max_book_age = max_age;
+        self
+    }
+
+    /// Uhr für die Altersprüfung im Heartbeat (Standard: Wall-Clock)
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Eigene WS-Konfiguration (Standard: WsConfig::from_env, z.B. HL_WS_URL für Replays,
+    /// plus HL_WS_RECORD als Aufzeichnungsdatei)
+    pub fn with_ws_config(mut self, config: WsConfig) -> Self {
+        self.ws_config = config;
+        self
+    }
+
+    fn record(&self, snapshot: &L2Snapshot) {
+        if let Some(recorder) = &self.recorder {
+            match recorder.tx.try_send(snapshot.clone()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    // Lieber eine Lücke in der Aufzeichnung als einen blockierten Stream
+                    self.stats.records_dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    eprintln!("[COLLECTOR] Snapshot-Recorder beendet.");
+                }
+            }
+        }
+    }
+
+    /// Snapshot ins Orderbuch übernehmen; false wenn er die Integritätsprüfung nicht besteht
+    fn ingest(&self, symbol: &str, snapshot: &L2Snapshot) -> bool {
+        let result = match self.books.get_mut(symbol) {
+            Some(mut book) => book.apply(snapshot),
+            None => OrderBook::from_snapshot(snapshot).map(|book| {
+                self.books.insert(symbol.to_string(), book);
+            }),
+        };
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                self.stats.books_rejected.fetch_add(1, Ordering::Relaxed);
+                eprintln!("[COLLECTOR] {} verworfen: {}", symbol, e);
+                false
+            }
+        }
+    }
+
+    /// Von der Quelle geprüftes Buch übernehmen; false wenn es älter als das vorhandene ist
+    fn accept(&self, book: OrderBook) -> bool {
+        let last = self.books.get(&book.coin).map(|b| b.time);
+        if let Some(last) = last.filter(|&last| book.time < last) {
+            self.stats.books_rejected.fetch_add(1, Ordering::Relaxed);
+            eprintln!(
+                "[COLLECTOR] {} verworfen: Buch von {} älter als {}",
+                book.coin, book.time, last
+            );
+            return false;
+        }
+        self.books.insert(book.coin.clone(), book);
+        true
+    }
+
+    /// Aktuelles Orderbuch eines Symbols
+    pub fn book(&self, symbol: &str) -> Option<OrderBook> {
+        self.books.get(symbol).map(|b| b.clone())
+    }
+
+    /// (empfangene Websocket-Snapshots, an den Callback übergebene Samples)
+    pub fn get_stats(&self) -> (usize, usize) {
+        (
+            self.stats.messages_received.load(Ordering::Relaxed),
+            self.stats.snapshots_sampled.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Letzter Verbindungszustand des Streams; None vor dem ersten Verbindungsaufbau
+    pub fn connection_state(&self) -> Option<ConnectionState> {
+        *self.connection.read().unwrap()
+    }
+
+    /// Stream verbunden und nicht stumm: Marktdaten sind aktuell
+    pub fn is_live(&self) -> bool {
+        self.connection_state().is_some_and(|s| s.is_live())
+    }
+
+    fn set_connection(&self, state: ConnectionState) {
+        *self.connection.write().unwrap() = Some(state);
+    }
+
+    /// Reconnect und Abo-Replay übernimmt HyperliquidWs; hier nur der erste Verbindungsaufbau
+    pub async fn stream_provider(self: Arc<Self>, symbols: Vec<String>) {
+        let config = self.ws_config.clone();
+        let mut attempt = 0u32;
+
+        let mut ws = loop {
+            println!("[COLLECTOR] Allianz-Kanal wird aufgebaut...");
+            match HyperliquidWs::with_config(self.is_testnet, config.clone()).await {
+                Ok(ws) => break ws,
+                Err(e) => {
+                    attempt += 1;
+                    let delay =
+                        backoff_delay(attempt, config.backoff_base, config.backoff_max, 0.5);
+                    self.set_connection(ConnectionState::Reconnecting {
+                        attempt,
+                        delay_ms: delay.as_millis() as u64,
+                    });
+                    eprintln!(
+                        "[COLLECTOR] Verbindungsfehler: {:?}. Retry in {:?}...",
+                        e, delay
+                    );
+                    time::sleep(delay).await;
+                }
+            }
+        };
+
+        for symbol in &symbols {
+            if let Err(e) = ws.subscribe_l2(symbol).await {
+                eprintln!("[COLLECTOR] Abo-Fehler für {}: {:?}", symbol, e);
+            }
+        }
+        println!("[COLLECTOR] ✅ Stream aktiv.");
+
+        while let Some(event) = ws.next_event().await {
+            match event {
+                HLEvent::Snapshot(snapshot) => {
+                    self.stats.messages_received.fetch_add(1, Ordering::Relaxed);
+                    self.record(&snapshot);
+                    self.ingest(&snapshot.coin, &snapshot);
+                }
+                HLEvent::Connection(state) => {
+                    match state {
+                        ConnectionState::Connected => println!("[COLLECTOR] ✅ Verbunden."),
+                        ConnectionState::Stale { silent_ms } => {
+                            eprintln!("[COLLECTOR] ⚠️ Stream stumm seit {}ms.", silent_ms)
+                        }
+                        ConnectionState::Reconnecting { attempt, delay_ms } => eprintln!(
+                            "[COLLECTOR] 🚨 Verbindung verloren. Reconnect #{} in {}ms...",
+                            attempt, delay_ms
+                        ),
+                    }
+                    self.set_connection(state);
+                }
+                HLEvent::Error(e) => eprintln!("[COLLECTOR] Server-Fehler: {}", e),
+                _ => {}
+            }
+        }
+        eprintln!("[COLLECTOR] Stream-Ende.");
+    }
+
+    /// Live-Eingang über eine beliebige Venue (MarketDataStream); die Bücher prüft die Quelle.
+    /// Kommt innerhalb von max_book_age kein Buch, gilt der Feed als stumm; endet er, wird mit
+    /// Backoff neu abonniert.
+    pub async fn book_feed(
+        self: Arc<Self>,
+        source: Arc<dyn MarketDataStream>,
+        symbols: Vec<String>,
+    ) {
+        let config = self.ws_config.clone();
+        let mut attempt = 0u32;
+
+        loop {
+            match source.subscribe_books(&symbols).await {
+                Ok(mut rx) => {
+                    attempt = 0;
+                    self.set_connection(ConnectionState::Connected);
+                    println!("[COLLECTOR] ✅ Book-Feed aktiv.");
+                    let mut last_book = Instant::now();
+                    loop {
+                        match time::timeout(self.max_book_age, rx.recv()).await {
+                            Ok(Some(book)) => {
+                                if !self.is_live() {
+                                    self.set_connection(ConnectionState::Connected);
+                                }
+                                last_book = Instant::now();
+                                self.stats.messages_received.fetch_add(1, Ordering::Relaxed);
+                                self.record(&book.to_snapshot());
+                                self.accept(book);
+                            }
+                            Ok(None) => break,
+                            Err(_) => self.set_connection(ConnectionState::Stale {
+                                silent_ms: last_book.elapsed().as_millis() as u64,
+                            }),
+                        }
+                    }
+                    eprintln!("[COLLECTOR] 🚨 Book-Feed beendet.");
+                }
+                Err(e) => eprintln!("[COLLECTOR] Abo-Fehler: {:?}", e),
+            }
+
+            attempt += 1;
+            let delay = backoff_delay(attempt, config.backoff_base, config.backoff_max, 0.5);
+            self.set_connection(ConnectionState::Reconnecting {
+                attempt,
+                delay_ms: delay.as_millis() as u64,
+            });
+            time::sleep(delay).await;
+        }
+    }
+
+    /// Aktuelle Bücher; veraltete (Stream stumm oder alle neuen Snapshots verworfen)
+    /// werden übersprungen und in books_stale gezählt
+    fn fresh_books(&self) -> Vec<OrderBook> {
+        let now_ms = self.clock.now_ms();
+        let max_age_ms = self.max_book_age.as_millis() as u64;
+        let mut fresh = Vec::new();
+        for entry in self.books.iter() {
+            if entry.is_stale(now_ms, max_age_ms) {
+                self.stats.books_stale.fetch_add(1, Ordering::Relaxed);
+            } else {
+                fresh.push(entry.value().clone());
+            }
+        }
+        fresh
+    }
+
+    pub async fn heartbeat_loop<F, Fut>(self: Arc<Self>, mut callback: F)
+    where
+        F: FnMut(Vec<OrderBook>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send,
+    {
+        let mut interval = time::interval(Duration::from_millis(100));
+        interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+        println!("[COLLECTOR] Heartbeat Loop (100ms) aktiv.");
+
+        loop {
+            interval.tick().await;
+            let updates = self.fresh_books();
+
+            if !updates.is_empty() {
+                self.stats
+                    .snapshots_sampled
+                    .fetch_add(updates.len(), Ordering::Relaxed);
+                callback(updates).await;
+            }
+        }
+    }
+
+    /// Replay-Gegenstück zu heartbeat_loop: gleiche Callback-Schnittstelle, Takt aus der Aufzeichnung.
+    /// Übergibt wie der Heartbeat bei jedem Tick das letzte gültige Buch jedes Symbols; nur Snapshots
+    /// mit neuer Zeit werden übernommen, ein verworfener also nicht bei jedem Tick erneut geprüft.
+    /// Ohne Altersprüfung, die Aufzeichnung bestimmt die Zeit. Kehrt zurück, sobald sie erschöpft ist.
+    pub async fn replay_loop<F, Fut>(self: Arc<Self>, mut replay: ReplayProvider, mut callback: F)
+    where
+        F: FnMut(Vec<OrderBook>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send,
+    {
+        println!("[COLLECTOR] Replay Loop aktiv.");
+        // Aufzeichnung gilt als durchgehend verbunden
+        self.set_connection(ConnectionState::Connected);
+        // Zeit des zuletzt übernommenen (oder verworfenen) Snapshots je Symbol
+        let mut seen: HashMap<String, u64> = HashMap::new();
+
+        loop {
+            match replay.next_tick().await {
+                Ok(Some(tick)) => {
+                    for (symbol, snapshot) in &tick.updates {
+                        if seen.insert(symbol.clone(), snapshot.time) != Some(snapshot.time) {
+                            self.stats.messages_received.fetch_add(1, Ordering::Relaxed);
+                            self.ingest(symbol, snapshot);
+                        }
+                    }
+                    let updates: Vec<OrderBook> = tick
+                        .updates
+                        .iter()
+                        .filter_map(|(symbol, _)| self.book(symbol))
+                        .collect();
+                    if !updates.is_empty() {
+                        self.stats
+                            .snapshots_sampled
+                            .fetch_add(updates.len(), Ordering::Relaxed);
+                        callback(updates).await;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    eprintln!("[COLLECTOR] Replay-Fehler: {}", e);
+                    break;
+                }
+            }
+        }
+
+        println!("[COLLECTOR] Replay beendet.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SimulatedClock;
+    use crate::exchange::mock_exchange::MockConfig;
+    use crate::exchange::orderbook::test_books::snapshot;
+    use crate::exchange::simulated::SimulatedExchange;
+    use crate::replay::ReplaySpeed;
+    use rust_decimal::Decimal;
+
+    #[tokio::test]
+    async fn test_recorder_flushes_while_running() {
+        let dir = std::env::temp_dir().join(format!("collector_rec_{}", std::process::id()));
+        let store = SnapshotStore::new(&dir).unwrap();
+        let collector = Collector::new(true).with_recorder_flush(store, Duration::from_millis(20));
+
+        let base = 1_700_000_000_000;
+        collector.record(&snapshot("BTC", base, &[("100", "1")], &[("101", "1")]));
+        collector.record(&snapshot(
+            "BTC",
+            base + 100,
+            &[("100", "1")],
+            &[("101", "1")],
+        ));
+
+        // Collector lebt weiter: nur der Intervall-Flush macht die Snapshots lesbar
+        time::sleep(Duration::from_millis(200)).await;
+        let reader = SnapshotStore::new(&dir)
+            .unwrap()
+            .reader(&["BTC".to_string()], 0, u64::MAX)
+            .unwrap();
+        let times: Vec<u64> = reader.map(|s| s.unwrap().time).collect();
+        assert_eq!(times, vec![base, base + 100]);
+
+        drop(collector);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stale_books_are_not_sampled() {
+        let clock = Arc::new(SimulatedClock::new(10_000));
+        let collector = Collector::new(true)
+            .with_clock(clock.clone())
+            .with_max_book_age(Duration::from_secs(2));
+
+        assert!(collector.ingest(
+            "BTC",
+            &snapshot("BTC", 9_000, &[("100", "1")], &[("101", "1")])
+        ));
+        assert!(collector.ingest(
+            "ETH",
+            &snapshot("ETH", 7_000, &[("10", "1")], &[("11", "1")])
+        ));
+        // Gekreuzt: verworfen, ETH bleibt beim alten Buch
+        assert!(!collector.ingest(
+            "ETH",
+            &snapshot("ETH", 9_500, &[("12", "1")], &[("11", "1")])
+        ));
+
+        let fresh = collector.fresh_books();
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].coin, "BTC");
+        assert_eq!(collector.stats.books_stale.load(Ordering::Relaxed), 1);
+
+        clock.advance_ms(5_000);
+        assert!(collector.fresh_books().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replay_keeps_last_good_book_across_bad_snapshot() {
+        let recording = vec![
+            snapshot("BTC", 1_000, &[("100", "1")], &[("101", "1")]),
+            // Gekreuzt: einmal verworfen, nicht bei jedem Tick erneut
+            snapshot("BTC", 1_200, &[("102", "1")], &[("101", "1")]),
+            snapshot("BTC", 1_500, &[("103", "1")], &[("104", "1")]),
+        ];
+        let replay =
+            ReplayProvider::new(recording.into_iter().map(Ok), ReplaySpeed::AsFastAsPossible);
+        let collector = Arc::new(Collector::new(true));
+
+        let sampled = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = sampled.clone();
+        collector
+            .clone()
+            .replay_loop(replay, move |books| {
+                let sink = sink.clone();
+                async move {
+                    sink.lock()
+                        .unwrap()
+                        .extend(books.iter().map(|b| (b.time, b.seq)));
+                }
+            })
+            .await;
+
+        // Ticks 1000..=1500: bis zum nächsten gültigen Snapshot das alte Buch, seq unverändert
+        let sampled = sampled.lock().unwrap();
+        assert_eq!(sampled.len(), 6);
+        assert!(sampled[..5].iter().all(|&b| b == (1_000, 1)));
+        assert_eq!(sampled[5], (1_500, 2));
+        assert_eq!(collector.stats.books_rejected.load(Ordering::Relaxed), 1);
+        assert_eq!(collector.get_stats(), (3, 6));
+    }
+
+    #[tokio::test]
+    async fn test_book_feed_from_any_venue() {
+        // Simulierte Venue als Marktdaten-Quelle statt Hyperliquid
+        let venue = Arc::new(SimulatedExchange::new(
+            MockConfig::default(),
+            "0x0000000000000000000000000000000000000000",
+            Decimal::ZERO,
+        ));
+        let collector = Arc::new(Collector::new(true));
+        let feed = tokio::spawn(
+            collector
+                .clone()
+                .book_feed(venue.clone(), vec!["BTC".to_string()]),
+        );
+
+        time::timeout(Duration::from_secs(1), async {
+            while !collector.is_live() {
+                time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        venue
+            .feed(&snapshot("BTC", 1_000, &[("100", "1")], &[("101", "1")]))
+            .unwrap();
+        venue
+            .feed(&snapshot("ETH", 1_000, &[("10", "1")], &[("11", "1")]))
+            .unwrap();
+        venue
+            .feed(&snapshot("BTC", 1_100, &[("100.5", "1")], &[("101", "2")]))
+            .unwrap();
+
+        time::timeout(Duration::from_secs(1), async {
+            while collector.book("BTC").map(|b| b.time) != Some(1_100) {
+                time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        // Nur abonnierte Symbole
+        assert!(collector.book("ETH").is_none());
+        assert_eq!(collector.get_stats().0, 2);
+
+        // Älteres Buch einer Quelle ersetzt das aktuelle nicht
+        let mut old = collector.book("BTC").unwrap();
+        old.time = 900;
+        assert!(!collector.accept(old));
+        assert_eq!(collector.book("BTC").unwrap().time, 1_100);
+        feed.abort();
+    }
+}
@@ -0,0 +1,418 @@
+// E:\MBCT\trading-core\src\physics\features.rs
+// THE ALLIANCE - Feature Registry
+// Jede physikalische Größe ist eine benannte, versionierte Berechnung über ein geprüftes OrderBook.
+// Welche Variante gilt, bestimmt ein FeatureSet (Preset oder JSON-Config). Research-Outputs
+// speichern dessen Fingerprint, damit Ergebnisse den Formeln zugeordnet bleiben.
+
+use crate::exchange::orderbook::{BookLevel, OrderBook};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// ==== Einzelne Features ====
+
+/// Druck (P) aus den (gewichteten) Bid/Ask-Volumina
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PressureDef {
+    /// (bid - ask) / (bid + ask) * 100, vorzeichenbehaftet (Physicist)
+    ImbalancePct,
+    /// |bid - ask| / (bid + ask) (Signaler)
+    AbsImbalance,
+    /// bid + ask, gewichtete Markttiefe (HyperliquidMarketData)
+    WeightedDepth,
+}
+
+impl PressureDef {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PressureDef::ImbalancePct => "imbalance_pct",
+            PressureDef::AbsImbalance => "abs_imbalance",
+            PressureDef::WeightedDepth => "weighted_depth",
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        1
+    }
+
+    pub fn compute(&self, bid_vol: f64, ask_vol: f64) -> f64 {
+        let total = bid_vol + ask_vol;
+        match self {
+            PressureDef::WeightedDepth => total,
+            _ if total == 0.0 => 0.0,
+            PressureDef::ImbalancePct => (bid_vol - ask_vol) / total * 100.0,
+            PressureDef::AbsImbalance => (bid_vol - ask_vol).abs() / total,
+        }
+    }
+}
+
+/// Logarithmus-Basis der Shannon-Entropie (S)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntropyBase {
+    /// Nats (ln)
+    Natural,
+    /// Bits (log2)
+    Bits,
+}
+
+impl EntropyBase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntropyBase::Natural => "ln",
+            EntropyBase::Bits => "log2",
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        1
+    }
+
+    /// Entropie der Volumenverteilung; Nullvolumina werden ignoriert
+    pub fn compute(&self, volumes: impl Iterator<Item = f64> + Clone) -> f64 {
+        let total: f64 = volumes.clone().sum();
+        if total == 0.0 {
+            return 0.0;
+        }
+        let log = |p: f64| match self {
+            EntropyBase::Natural => p.ln(),
+            EntropyBase::Bits => p.log2(),
+        };
+        volumes
+            .map(|v| v / total)
+            .filter(|p| *p > 0.0)
+            .map(|p| -p * log(p))
+            .sum()
+    }
+}
+
+/// Wie viele Ebenen je Seite in Volumina und Druck eingehen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookDepth {
+    Full,
+    Top(usize),
+}
+
+impl BookDepth {
+    pub fn name(&self) -> String {
+        match self {
+            BookDepth::Full => "full".to_string(),
+            BookDepth::Top(n) => format!("top{}", n),
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        1
+    }
+
+    pub fn levels(&self) -> usize {
+        match self {
+            BookDepth::Full => usize::MAX,
+            BookDepth::Top(n) => *n,
+        }
+    }
+}
+
+/// Gewicht einer Ebene nach Abstand zum Top of Book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelWeighting {
+    /// Alle Ebenen zählen gleich
+    Flat,
+    /// 1.0, 0.9, 0.8, ... (HyperliquidMarketData), nie negativ
+    LinearDecay,
+}
+
+impl LevelWeighting {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LevelWeighting::Flat => "flat",
+            LevelWeighting::LinearDecay => "linear_decay",
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        1
+    }
+
+    pub fn weight(&self, level: usize) -> f64 {
+        match self {
+            LevelWeighting::Flat => 1.0,
+            LevelWeighting::LinearDecay => (1.0 - level as f64 * 0.1).max(0.0),
+        }
+    }
+}
+
+/// Energie (NRG) aus Druck und Entropie
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NrgDef {
+    /// |P| * S (Trader)
+    PressureEntropy,
+    /// PEP = pi * S * ln(1 + |P|) (Researcher v2)
+    Pep,
+}
+
+impl NrgDef {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NrgDef::PressureEntropy => "pressure_entropy",
+            NrgDef::Pep => "pep",
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        1
+    }
+
+    pub fn compute(&self, pressure: f64, entropy: f64) -> f64 {
+        match self {
+            NrgDef::PressureEntropy => pressure.abs() * entropy,
+            NrgDef::Pep => std::f64::consts::PI * entropy * pressure.abs().ln_1p(),
+        }
+    }
+}
+
+/// Volumen (V) = Spread
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpreadDef {
+    /// ask - bid in Preiseinheiten
+    Absolute,
+    /// (ask - bid) / mid
+    Relative,
+}
+
+impl SpreadDef {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpreadDef::Absolute => "absolute",
+            SpreadDef::Relative => "relative",
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        1
+    }
+
+    pub fn compute(&self, best_bid: f64, best_ask: f64) -> f64 {
+        let spread = best_ask - best_bid;
+        match self {
+            SpreadDef::Absolute => spread,
+            SpreadDef::Relative => {
+                let mid = (best_bid + best_ask) / 2.0;
+                if mid > 0.0 {
+                    spread / mid
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+// ==== FeatureSet ====
+
+/// Auswahl je Feature. Tiefe und Gewichtung gelten für Volumina und Druck,
+/// die Entropie läuft immer über das ganze Buch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeatureSet {
+    pub pressure: PressureDef,
+    pub entropy: EntropyBase,
+    pub depth: BookDepth,
+    pub weighting: LevelWeighting,
+    pub nrg: NrgDef,
+    pub spread: SpreadDef,
+}
+
+impl Default for FeatureSet {
+    /// Die Trader-Definitionen (Stand Physicist v5.8)
+    fn default() -> Self {
+        Self {
+            pressure: PressureDef::ImbalancePct,
+            entropy: EntropyBase::Natural,
+            depth: BookDepth::Full,
+            weighting: LevelWeighting::Flat,
+            nrg: NrgDef::PressureEntropy,
+            spread: SpreadDef::Absolute,
+        }
+    }
+}
+
+impl FeatureSet {
+    /// Benannte Presets: "trader_v1", "pep_v1", "market_data_v1", "signaler_v1"
+    pub fn preset(name: &str) -> Option<Self> {
+        let base = Self::default();
+        match name {
+            "trader_v1" => Some(base),
+            "pep_v1" => Some(Self {
+                depth: BookDepth::Top(10),
+                nrg: NrgDef::Pep,
+                spread: SpreadDef::Relative,
+                ..base
+            }),
+            "market_data_v1" => Some(Self {
+                pressure: PressureDef::WeightedDepth,
+                depth: BookDepth::Top(5),
+                weighting: LevelWeighting::LinearDecay,
+                spread: SpreadDef::Relative,
+                ..base
+            }),
+            "signaler_v1" => Some(Self {
+                pressure: PressureDef::AbsImbalance,
+                entropy: EntropyBase::Bits,
+                ..base
+            }),
+            _ => None,
+        }
+    }
+
+    /// Preset-Name oder Pfad zu einer JSON-Datei (fehlende Felder = Default)
+    pub fn from_spec(spec: &str) -> Result<Self, String> {
+        if let Some(set) = Self::preset(spec.trim()) {
+            return Ok(set);
+        }
+        let content = std::fs::read_to_string(spec)
+            .map_err(|e| format!("Unbekanntes FeatureSet '{}': {}", spec, e))?;
+        serde_json::from_str(content.trim_start_matches('\u{feff}'))
+            .map_err(|e| format!("FeatureSet '{}' nicht lesbar: {}", spec, e))
+    }
+
+    /// Liest die Auswahl aus der Umgebungsvariable FEATURE_SET, sonst Default
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_env_or(Self::default())
+    }
+
+    /// Wie from_env, mit eigenem Fallback (z.B. pep_v1 im Researcher)
+    pub fn from_env_or(fallback: Self) -> Result<Self, String> {
+        match std::env::var("FEATURE_SET") {
+            Ok(spec) => Self::from_spec(&spec),
+            Err(_) => Ok(fallback),
+        }
+    }
+
+    /// Gewichtete Summe der Volumina je Seite innerhalb der Buchtiefe
+    pub fn volumes(&self, book: &OrderBook) -> (f64, f64) {
+        let side = |levels: &[BookLevel]| -> f64 {
+            levels
+                .iter()
+                .take(self.depth.levels())
+                .enumerate()
+                .map(|(j, l)| l.sz.to_f64().unwrap_or(0.0) * self.weighting.weight(j))
+                .sum()
+        };
+        (side(&book.bids), side(&book.asks))
+    }
+
+    pub fn entropy(&self, book: &OrderBook) -> f64 {
+        let volumes = book
+            .bids
+            .iter()
+            .chain(book.asks.iter())
+            .map(|l| l.sz.to_f64().unwrap_or(0.0));
+        self.entropy.compute(volumes)
+    }
+
+    /// Eindeutige Kennung aller Feature-Versionen, wird mit Research-Records gespeichert
+    pub fn fingerprint(&self) -> String {
+        format!(
+            "pressure={}@{};entropy={}@{};depth={}@{};weighting={}@{};nrg={}@{};spread={}@{}",
+            self.pressure.name(),
+            self.pressure.version(),
+            self.entropy.name(),
+            self.entropy.version(),
+            self.depth.name(),
+            self.depth.version(),
+            self.weighting.name(),
+            self.weighting.version(),
+            self.nrg.name(),
+            self.nrg.version(),
+            self.spread.name(),
+            self.spread.version(),
+        )
+    }
+}
+
+impl fmt::Display for FeatureSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.fingerprint())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    /// Buch aus Größen je Ebene, Preise im Abstand 1 um 100
+    fn book(bids: &[&str], asks: &[&str]) -> OrderBook {
+        let side = |szs: &[&str], step: i64| {
+            szs.iter()
+                .enumerate()
+                .map(|(i, sz)| BookLevel {
+                    px: Decimal::from(100 + step * (i as i64 + 1)),
+                    sz: sz.parse().unwrap(),
+                })
+                .collect()
+        };
+        OrderBook {
+            coin: "BTC".into(),
+            time: 0,
+            seq: 1,
+            bids: side(bids, -1),
+            asks: side(asks, 1),
+        }
+    }
+
+    #[test]
+    fn test_depth_and_weighting() {
+        let book = book(&["1", "1", "1"], &["2"]);
+        assert_eq!(FeatureSet::default().volumes(&book), (3.0, 2.0));
+
+        let market_data = FeatureSet::preset("market_data_v1").unwrap();
+        let (bid, ask) = market_data.volumes(&book);
+        assert!((bid - 2.7).abs() < 1e-9);
+        assert_eq!(ask, 2.0);
+        assert!((market_data.pressure.compute(bid, ask) - 4.7).abs() < 1e-9);
+
+        let top2 = FeatureSet {
+            depth: BookDepth::Top(2),
+            ..FeatureSet::default()
+        };
+        assert_eq!(top2.volumes(&book), (2.0, 2.0));
+    }
+
+    #[test]
+    fn test_variants_disagree_as_documented() {
+        assert_eq!(PressureDef::ImbalancePct.compute(3.0, 1.0), 50.0);
+        assert_eq!(PressureDef::AbsImbalance.compute(1.0, 3.0), 0.5);
+        assert_eq!(PressureDef::ImbalancePct.compute(0.0, 0.0), 0.0);
+
+        let uniform = [1.0, 1.0, 1.0, 1.0];
+        assert!((EntropyBase::Bits.compute(uniform.iter().copied()) - 2.0).abs() < 1e-9);
+        assert!((EntropyBase::Natural.compute(uniform.iter().copied()) - 4f64.ln()).abs() < 1e-9);
+
+        assert_eq!(NrgDef::PressureEntropy.compute(-2.0, 1.5), 3.0);
+        assert!((NrgDef::Pep.compute(0.0, 1.5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_spec_and_fingerprint() {
+        let pep = FeatureSet::from_spec("pep_v1").unwrap();
+        assert_eq!(
+            pep.fingerprint(),
+            "pressure=imbalance_pct@1;entropy=ln@1;depth=top10@1;weighting=flat@1;nrg=pep@1;spread=relative@1"
+        );
+
+        let partial: FeatureSet =
+            serde_json::from_str(r#"{"entropy": "bits", "depth": {"top": 5}}"#).unwrap();
+        assert_eq!(partial.entropy, EntropyBase::Bits);
+        assert_eq!(partial.depth, BookDepth::Top(5));
+        assert_eq!(partial.pressure, PressureDef::ImbalancePct);
+
+        assert!(FeatureSet::from_spec("does_not_exist_v9").is_err());
+    }
+}
@@ -0,0 +1,19 @@
+// E:\MBCT\trading-core\src\physics\mod.rs
+// THE ALLIANCE - Thermodynamische Markt-Physik
+// Eine gemeinsame Implementierung für Researcher und Trader: identische Features im Research und live.
+
+pub mod archive;
+pub mod chronos;
+pub mod collector;
+pub mod features;
+pub mod physicist;
+pub mod regime;
+pub mod rolling;
+
+pub use archive::Archive;
+pub use chronos::{Chronos, MBCTFullRecord};
+pub use collector::Collector;
+pub use features::FeatureSet;
+pub use physicist::{Physicist, PhysicsState};
+pub use regime::{MarketRegime, RegimeClassifier, RegimeState};
+pub use rolling::{PhysicsField, RegimeTracker, RollingSlope, RollingStats, RollingUpDown};
@@ -0,0 +1,278 @@
+// E:\MBCT\trading-core\src\physics\physicist.rs
+// ====
+// THE ALLIANCE - MBCT Physicist Modul v5.8
+// Fokus: Thermodynamische Transformation (Entropy, Pressure, NRG)
+// ====
+
+use super::features::FeatureSet;
+use crate::clock::Clock;
+use crate::exchange::orderbook::OrderBook;
+use crate::exchange::types::MarketState;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PhysicsState {
+    pub price: f64,
+    pub spread: f64,
+    pub entropy: f64,
+    pub pressure: f64,
+    pub temperature: f64,
+    pub nrg: f64,
+    pub total_volume: f64,
+    pub bid_volume: f64,
+    pub ask_volume: f64,
+    pub timestamp: i64,
+}
+
+impl PhysicsState {
+    /// Speicherform (Decimal) für Repository & Archiv. Temperatur = Mid-Price, Volumen = Spread.
+    pub fn to_market_state(&self, symbol: &str) -> MarketState {
+        MarketState::from_f64(
+            symbol,
+            self.temperature,
+            self.pressure,
+            self.spread,
+            Some(self.entropy),
+            self.timestamp,
+        )
+    }
+
+    /// Rückweg in den f64-Fast-Path. Volumina sind in der Speicherform nicht enthalten und bleiben 0.
+    /// NRG wird nicht gespeichert und muss mit demselben FeatureSet neu berechnet werden,
+    /// mit dem der Zustand entstanden ist.
+    pub fn from_market_state(state: &MarketState, features: &FeatureSet) -> Self {
+        let pressure = state.pressure_f64();
+        let entropy = state.entropy_f64().unwrap_or(0.0);
+        PhysicsState {
+            price: state.temperature_f64(),
+            spread: state.volume_spread_f64(),
+            entropy,
+            pressure,
+            temperature: state.temperature_f64(),
+            nrg: Physicist::nrg(pressure, entropy, features),
+            timestamp: state.timestamp,
+            ..Default::default()
+        }
+    }
+}
+
+pub struct Physicist;
+
+impl Physicist {
+    /// Transformiert ein geprüftes Orderbuch in einen thermodynamischen PhysicsState (Default-FeatureSet)
+    pub fn process_book(book: &OrderBook, clock: &dyn Clock) -> PhysicsState {
+        Self::process_book_with(book, clock, &FeatureSet::default())
+    }
+
+    /// Energie aus Druck und Entropie nach der NRG-Definition des FeatureSets
+    pub fn nrg(pressure: f64, entropy: f64, features: &FeatureSet) -> f64 {
+        features.nrg.compute(pressure, entropy)
+    }
+
+    /// Wie process_book, aber mit explizit gewählten Feature-Definitionen
+    pub fn process_book_with(
+        book: &OrderBook,
+        clock: &dyn Clock,
+        features: &FeatureSet,
+    ) -> PhysicsState {
+        Self::process_book_at(book, clock.now_ms(), features)
+    }
+
+    /// Wie process_book_with, aber mit vorgegebenem Zeitstempel (z.B. Exchange-Zeit des Buchs)
+    pub fn process_book_at(
+        book: &OrderBook,
+        timestamp_ms: u64,
+        features: &FeatureSet,
+    ) -> PhysicsState {
+        let (bid_vol, ask_vol) = features.volumes(book);
+        let entropy = features.entropy(book);
+        let pressure = features.pressure.compute(bid_vol, ask_vol);
+
+        let (mid_price, spread) = match (book.best_bid(), book.best_ask()) {
+            (Some(bid), Some(ask)) => {
+                let best_bid = bid.px.to_f64().unwrap_or(0.0);
+                let best_ask = ask.px.to_f64().unwrap_or(0.0);
+                (
+                    (best_bid + best_ask) / 2.0,
+                    features.spread.compute(best_bid, best_ask),
+                )
+            }
+            _ => (0.0, 0.0),
+        };
+
+        let nrg = Self::nrg(pressure, entropy, features);
+
+        PhysicsState {
+            price: mid_price,
+            spread,
+            entropy,
+            pressure,
+            temperature: mid_price, // Im Trader nutzen wir Mid-Price als Basis-Temperatur
+            nrg,
+            total_volume: bid_vol + ask_vol,
+            bid_volume: bid_vol,
+            ask_volume: ask_vol,
+            timestamp: timestamp_ms as i64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SimulatedClock;
+    use crate::exchange::types::{L2Levels, L2Snapshot, Level};
+
+    fn parsed(snapshot: &L2Snapshot) -> OrderBook {
+        OrderBook::from_snapshot(snapshot).unwrap()
+    }
+
+    fn level(px: &str, sz: &str) -> Level {
+        Level {
+            px: px.into(),
+            sz: sz.into(),
+        }
+    }
+
+    #[test]
+    fn test_process_snapshot() {
+        let snapshot = L2Snapshot {
+            coin: "BTC".into(),
+            time: 0,
+            levels: L2Levels {
+                bids: vec![level("99", "3"), level("98", "1")],
+                asks: vec![level("101", "1"), level("102", "1")],
+            },
+        };
+        let state = Physicist::process_book(&parsed(&snapshot), &SimulatedClock::new(42));
+
+        assert_eq!(state.price, 100.0);
+        assert_eq!(state.spread, 2.0);
+        assert_eq!(state.bid_volume, 4.0);
+        assert_eq!(state.ask_volume, 2.0);
+        // (4 - 2) / 6 * 100
+        assert!((state.pressure - 100.0 / 3.0).abs() < 1e-9);
+        // p = [0.5, 1/6, 1/6, 1/6]
+        let expected_entropy = -(0.5f64 * 0.5f64.ln()) - 3.0 * (1.0 / 6.0) * (1.0f64 / 6.0).ln();
+        assert!((state.entropy - expected_entropy).abs() < 1e-9);
+        assert!((state.nrg - state.pressure.abs() * state.entropy).abs() < 1e-9);
+        assert_eq!(state.timestamp, 42);
+    }
+
+    #[test]
+    fn test_feature_set_selects_formulas() {
+        let snapshot = L2Snapshot {
+            coin: "BTC".into(),
+            time: 0,
+            levels: L2Levels {
+                bids: vec![level("99", "3"), level("98", "1")],
+                asks: vec![level("101", "1"), level("102", "1")],
+            },
+        };
+        let pep = FeatureSet::preset("pep_v1").unwrap();
+        let state = Physicist::process_book_with(&parsed(&snapshot), &SimulatedClock::new(0), &pep);
+
+        assert_eq!(state.spread, 0.02);
+        let expected_nrg = std::f64::consts::PI * state.entropy * state.pressure.abs().ln_1p();
+        assert!((state.nrg - expected_nrg).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pep_v1_reproduces_researcher_formulas() {
+        // 12 Bid-Ebenen: die Researcher-Volumina zählen nur die ersten 10, die Entropie alle
+        let bids: Vec<Level> = (0..12)
+            .map(|i| level(&format!("{}", 99 - i), "1"))
+            .collect();
+        let snapshot = L2Snapshot {
+            coin: "BTC".into(),
+            time: 0,
+            levels: L2Levels {
+                bids,
+                asks: vec![level("101", "2")],
+            },
+        };
+        let pep = FeatureSet::preset("pep_v1").unwrap();
+        let state = Physicist::process_book_with(&parsed(&snapshot), &SimulatedClock::new(0), &pep);
+
+        assert_eq!(state.bid_volume, 10.0);
+        assert_eq!(state.ask_volume, 2.0);
+        assert!((state.pressure - 8.0 / 12.0 * 100.0).abs() < 1e-9);
+        // p = [1/14 x 12, 2/14]
+        let expected_entropy =
+            -12.0 * (1.0 / 14.0) * (1.0f64 / 14.0).ln() - (2.0 / 14.0) * (2.0f64 / 14.0).ln();
+        assert!((state.entropy - expected_entropy).abs() < 1e-9);
+        assert_eq!(state.spread, 0.02);
+    }
+
+    #[test]
+    fn test_market_state_round_trip() {
+        let snapshot = L2Snapshot {
+            coin: "ETH".into(),
+            time: 0,
+            levels: L2Levels {
+                bids: vec![level("1999.5", "2"), level("1999", "1")],
+                asks: vec![level("2000.5", "1")],
+            },
+        };
+        let state = Physicist::process_book(&parsed(&snapshot), &SimulatedClock::new(7));
+        let stored = state.to_market_state("ETH");
+
+        assert_eq!(stored.symbol, "ETH");
+        assert_eq!(stored.timestamp, 7);
+        assert_eq!(stored.volume_spread_f64(), 1.0);
+        assert!(stored.regime.is_none());
+
+        let back = PhysicsState::from_market_state(&stored, &FeatureSet::default());
+        assert_eq!(back.price, 2000.0);
+        assert!((back.pressure - state.pressure).abs() < 1e-9);
+        assert!((back.entropy - state.entropy).abs() < 1e-9);
+        assert!((back.nrg - state.nrg).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_market_state_round_trip_keeps_feature_set() {
+        let snapshot = L2Snapshot {
+            coin: "ETH".into(),
+            time: 0,
+            levels: L2Levels {
+                bids: vec![level("1999.5", "2"), level("1999", "1")],
+                asks: vec![level("2000.5", "1")],
+            },
+        };
+        for name in ["pep_v1", "market_data_v1"] {
+            let features = FeatureSet::preset(name).unwrap();
+            let state = Physicist::process_book_at(&parsed(&snapshot), 7, &features);
+            let stored = state.to_market_state("ETH");
+
+            let back = PhysicsState::from_market_state(&stored, &features);
+            assert!((back.pressure - state.pressure).abs() < 1e-9, "{}", name);
+            assert!((back.entropy - state.entropy).abs() < 1e-9, "{}", name);
+            assert!((back.nrg - state.nrg).abs() < 1e-9, "{}", name);
+            assert_eq!(back.timestamp, 7);
+        }
+
+        // Mit dem Default-FeatureSet zurückgelesen wäre die PEP-Energie falsch
+        let pep = FeatureSet::preset("pep_v1").unwrap();
+        let state = Physicist::process_book_at(&parsed(&snapshot), 7, &pep);
+        let default_back =
+            PhysicsState::from_market_state(&state.to_market_state("ETH"), &FeatureSet::default());
+        assert!((default_back.nrg - state.nrg).abs() > 1e-6);
+    }
+
+    #[test]
+    fn test_empty_book_is_neutral() {
+        let snapshot = L2Snapshot {
+            coin: "BTC".into(),
+            time: 0,
+            levels: L2Levels {
+                bids: vec![],
+                asks: vec![],
+            },
+        };
+        let state = Physicist::process_book(&parsed(&snapshot), &SimulatedClock::new(0));
+        assert_eq!(state.price, 0.0);
+        assert_eq!(state.entropy, 0.0);
+        assert_eq!(state.pressure, 0.0);
+    }
+}
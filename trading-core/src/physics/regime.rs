@@ -1,10 +1,11 @@
-// E:\MBCT\trading-core\src\bin\trader\modules\regime.rs
+// E:\MBCT\trading-core\src\physics\regime.rs
 // ====
 // THE ALLIANCE - MBCT Regime Modul v2.2 (Trader-Edition)
 // Fokus: Kybernetische Symmetrie & Z-Score Anomalie-Detektion
 // ====
 
 use super::physicist::PhysicsState;
+use super::rolling::PhysicsField;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
@@ -15,6 +16,19 @@ pub enum MarketRegime {
     Ballistic,   // Ausbruch / Starker Trend
 }
 
+impl MarketRegime {
+    /// Ballistic außerhalb 0.2..=0.8, Compression in (0.4, 0.6), sonst Oscillatory
+    pub fn from_symmetry(symmetry: f64) -> Self {
+        if !(0.2..=0.8).contains(&symmetry) {
+            MarketRegime::Ballistic
+        } else if symmetry > 0.4 && symmetry < 0.6 {
+            MarketRegime::Compression
+        } else {
+            MarketRegime::Oscillatory
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegimeState {
     pub regime: MarketRegime,
@@ -33,6 +47,7 @@ impl RegimeClassifier {
         Self { window_size }
     }
 
+    /// Referenz-Implementierung über die ganze Historie; im Heartbeat RegimeTracker verwenden
     pub fn classify(&self, history: &VecDeque<PhysicsState>) -> RegimeState {
         if history.len() < self.window_size {
             return RegimeState {
@@ -55,16 +70,8 @@ impl RegimeClassifier {
             0.0
         };
 
-        let regime = if symmetry > 0.8 || symmetry < 0.2 {
-            MarketRegime::Ballistic
-        } else if symmetry > 0.4 && symmetry < 0.6 {
-            MarketRegime::Compression
-        } else {
-            MarketRegime::Oscillatory
-        };
-
         RegimeState {
-            regime,
+            regime: MarketRegime::from_symmetry(symmetry),
             symmetry_score: symmetry,
             slope,
             reversion_speed: reversion,
@@ -72,17 +79,13 @@ impl RegimeClassifier {
         }
     }
 
+    /// Referenz-Implementierung über die ganze Historie; im Heartbeat RegimeTracker verwenden
     pub fn calculate_z_score(
         current_val: f64,
         history: &VecDeque<PhysicsState>,
-        field: &str,
+        field: PhysicsField,
     ) -> f64 {
-        let values: Vec<f64> = match field {
-            "entropy" => history.iter().map(|h| h.entropy).collect(),
-            "pressure" => history.iter().map(|h| h.pressure).collect(),
-            "nrg" => history.iter().map(|h| h.nrg).collect(),
-            _ => return 0.0,
-        };
+        let values: Vec<f64> = history.iter().map(|h| field.value(h)).collect();
 
         let n = values.len() as f64;
         if n < 2.0 {
@@ -134,3 +137,56 @@ impl RegimeClassifier {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(prices: &[f64]) -> VecDeque<PhysicsState> {
+        prices
+            .iter()
+            .map(|&price| PhysicsState {
+                price,
+                nrg: price,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_short_history_is_neutral() {
+        let state = RegimeClassifier::new(5).classify(&history(&[1.0, 2.0]));
+        assert_eq!(state.regime, MarketRegime::Compression);
+        assert_eq!(state.symmetry_score, 0.5);
+        assert_eq!(state.confidence, 0.0);
+    }
+
+    #[test]
+    fn test_trend_is_ballistic() {
+        let prices: Vec<f64> = (0..10).map(|i| 100.0 + i as f64).collect();
+        let state = RegimeClassifier::new(10).classify(&history(&prices));
+        assert_eq!(state.regime, MarketRegime::Ballistic);
+        assert_eq!(state.symmetry_score, 1.0);
+        assert!((state.slope - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_balanced_moves_are_compression() {
+        let prices = [100.0, 101.0, 100.0, 101.0, 100.0, 101.0, 100.0];
+        let state = RegimeClassifier::new(7).classify(&history(&prices));
+        assert_eq!(state.regime, MarketRegime::Compression);
+        assert_eq!(state.symmetry_score, 0.5);
+    }
+
+    #[test]
+    fn test_z_score() {
+        let h = history(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        // mean 3, sample std sqrt(2.5)
+        let z = RegimeClassifier::calculate_z_score(5.0, &h, PhysicsField::Nrg);
+        assert!((z - 2.0 / 2.5f64.sqrt()).abs() < 1e-9);
+        assert_eq!(
+            RegimeClassifier::calculate_z_score(5.0, &h, PhysicsField::Entropy),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,521 @@
+// E:\MBCT\trading-core\src\physics\rolling.rs
+// THE ALLIANCE - Rolling Statistics Engine
+// O(1) pro Heartbeat statt Neuberechnung über die ganze Historie: Welford Mittelwert/Varianz,
+// rollierende OLS-Steigung und Up/Down-Summen. Die laufenden Summen werden einmal pro
+// Fensterlänge exakt neu berechnet (amortisiert O(1)), damit sich kein Float-Drift aufbaut.
+
+use super::physicist::PhysicsState;
+use super::regime::{MarketRegime, RegimeState};
+use std::collections::VecDeque;
+
+// ==== Typisierte Felder ====
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhysicsField {
+    Price,
+    Spread,
+    Entropy,
+    Pressure,
+    Temperature,
+    Nrg,
+    TotalVolume,
+}
+
+impl PhysicsField {
+    pub const ALL: [PhysicsField; 7] = [
+        PhysicsField::Price,
+        PhysicsField::Spread,
+        PhysicsField::Entropy,
+        PhysicsField::Pressure,
+        PhysicsField::Temperature,
+        PhysicsField::Nrg,
+        PhysicsField::TotalVolume,
+    ];
+
+    pub fn value(&self, state: &PhysicsState) -> f64 {
+        match self {
+            PhysicsField::Price => state.price,
+            PhysicsField::Spread => state.spread,
+            PhysicsField::Entropy => state.entropy,
+            PhysicsField::Pressure => state.pressure,
+            PhysicsField::Temperature => state.temperature,
+            PhysicsField::Nrg => state.nrg,
+            PhysicsField::TotalVolume => state.total_volume,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+// ==== Mittelwert / Varianz ====
+
+/// Welford über ein gleitendes Fenster
+#[derive(Debug, Clone)]
+pub struct RollingStats {
+    window: usize,
+    values: VecDeque<f64>,
+    mean: f64,
+    m2: f64,
+    since_resync: usize,
+}
+
+impl RollingStats {
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            window,
+            values: VecDeque::with_capacity(window + 1),
+            mean: 0.0,
+            m2: 0.0,
+            since_resync: 0,
+        }
+    }
+
+    pub fn push(&mut self, x: f64) {
+        if self.values.len() == self.window {
+            let old = self.values.pop_front().unwrap_or(x);
+            self.values.push_back(x);
+            let old_mean = self.mean;
+            self.mean += (x - old) / self.window as f64;
+            self.m2 += (x - old) * (x - self.mean + old - old_mean);
+        } else {
+            self.values.push_back(x);
+            let delta = x - self.mean;
+            self.mean += delta / self.values.len() as f64;
+            self.m2 += delta * (x - self.mean);
+        }
+
+        self.since_resync += 1;
+        if self.since_resync >= self.window {
+            self.resync();
+        }
+    }
+
+    fn resync(&mut self) {
+        let n = self.values.len() as f64;
+        self.mean = self.values.iter().sum::<f64>() / n;
+        self.m2 = self.values.iter().map(|v| (v - self.mean).powi(2)).sum();
+        self.since_resync = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Stichprobenvarianz (n - 1), 0 bei weniger als zwei Werten
+    pub fn variance(&self) -> f64 {
+        let n = self.values.len();
+        if n < 2 {
+            0.0
+        } else {
+            (self.m2 / (n - 1) as f64).max(0.0)
+        }
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Gleiche Semantik wie RegimeClassifier::calculate_z_score
+    pub fn z_score(&self, value: f64) -> f64 {
+        let std_dev = self.std_dev();
+        if self.values.len() < 2 || std_dev < 1e-9 {
+            0.0
+        } else {
+            (value - self.mean) / std_dev
+        }
+    }
+}
+
+// ==== OLS-Steigung ====
+
+/// Steigung der Regressionsgeraden über (Index, Wert) im Fenster
+#[derive(Debug, Clone)]
+pub struct RollingSlope {
+    window: usize,
+    values: VecDeque<f64>,
+    /// Σ y
+    sum_y: f64,
+    /// Σ i·y mit i = Position im Fenster
+    sum_iy: f64,
+    since_resync: usize,
+}
+
+impl RollingSlope {
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            window,
+            values: VecDeque::with_capacity(window + 1),
+            sum_y: 0.0,
+            sum_iy: 0.0,
+            since_resync: 0,
+        }
+    }
+
+    pub fn push(&mut self, y: f64) {
+        if self.values.len() == self.window {
+            // Ältesten Wert entfernen, alle Positionen rücken um eins nach vorne
+            let old = self.values.pop_front().unwrap_or(0.0);
+            self.sum_y -= old;
+            self.sum_iy -= self.sum_y;
+        }
+        self.sum_iy += self.values.len() as f64 * y;
+        self.sum_y += y;
+        self.values.push_back(y);
+
+        self.since_resync += 1;
+        if self.since_resync >= self.window {
+            self.sum_y = self.values.iter().sum();
+            self.sum_iy = self
+                .values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| i as f64 * v)
+                .sum();
+            self.since_resync = 0;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn values(&self) -> &VecDeque<f64> {
+        &self.values
+    }
+
+    pub fn slope(&self) -> f64 {
+        let n = self.values.len() as f64;
+        if n < 2.0 {
+            return 0.0;
+        }
+        let sum_i = n * (n - 1.0) / 2.0;
+        let sum_ii = (n - 1.0) * n * (2.0 * n - 1.0) / 6.0;
+        let den = n * sum_ii - sum_i * sum_i;
+        if den == 0.0 {
+            0.0
+        } else {
+            (n * self.sum_iy - sum_i * self.sum_y) / den
+        }
+    }
+}
+
+// ==== Up/Down-Summen ====
+
+/// Summe der Auf- und Abwärtsbewegungen zwischen aufeinanderfolgenden Werten im Fenster
+#[derive(Debug, Clone)]
+pub struct RollingUpDown {
+    /// Anzahl Werte im Fenster; es gibt window - 1 Differenzen
+    window: usize,
+    last: Option<f64>,
+    diffs: VecDeque<f64>,
+    ups: f64,
+    downs: f64,
+    up_count: usize,
+    down_count: usize,
+    since_resync: usize,
+}
+
+impl RollingUpDown {
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            window,
+            last: None,
+            diffs: VecDeque::with_capacity(window),
+            ups: 0.0,
+            downs: 0.0,
+            up_count: 0,
+            down_count: 0,
+            since_resync: 0,
+        }
+    }
+
+    pub fn push(&mut self, x: f64) {
+        if let Some(last) = self.last {
+            let diff = x - last;
+            self.add(diff);
+            self.diffs.push_back(diff);
+            if self.diffs.len() > self.window - 1 {
+                if let Some(old) = self.diffs.pop_front() {
+                    self.remove(old);
+                }
+            }
+        }
+        self.last = Some(x);
+
+        // Keine Bewegung mehr im Fenster: exakt 0 statt Rundungsrest
+        if self.up_count == 0 {
+            self.ups = 0.0;
+        }
+        if self.down_count == 0 {
+            self.downs = 0.0;
+        }
+
+        self.since_resync += 1;
+        if self.since_resync >= self.window {
+            let (ups, downs) = Self::sums(self.diffs.iter());
+            self.ups = ups;
+            self.downs = downs;
+            self.since_resync = 0;
+        }
+    }
+
+    fn add(&mut self, diff: f64) {
+        if diff > 0.0 {
+            self.ups += diff;
+            self.up_count += 1;
+        } else if diff < 0.0 {
+            self.downs -= diff;
+            self.down_count += 1;
+        }
+    }
+
+    fn remove(&mut self, diff: f64) {
+        if diff > 0.0 {
+            self.ups -= diff;
+            self.up_count -= 1;
+        } else if diff < 0.0 {
+            self.downs += diff;
+            self.down_count -= 1;
+        }
+    }
+
+    fn sums<'a>(diffs: impl Iterator<Item = &'a f64>) -> (f64, f64) {
+        diffs.fold((0.0, 0.0), |(ups, downs), &d| {
+            if d > 0.0 {
+                (ups + d, downs)
+            } else {
+                (ups, downs + d.abs())
+            }
+        })
+    }
+
+    pub fn ups(&self) -> f64 {
+        self.ups
+    }
+
+    pub fn downs(&self) -> f64 {
+        self.downs
+    }
+
+    /// ups / (ups + downs), 0.5 ohne Bewegung
+    pub fn symmetry(&self) -> f64 {
+        Self::ratio(self.ups, self.downs)
+    }
+
+    /// Symmetrie ohne die letzten `k` Differenzen (Stand vor k Heartbeats innerhalb des Fensters), O(k)
+    pub fn symmetry_excluding_last(&self, k: usize) -> f64 {
+        if k >= self.diffs.len() {
+            return 0.5;
+        }
+        let (ups, downs) = Self::sums(self.diffs.iter().rev().take(k));
+        Self::ratio((self.ups - ups).max(0.0), (self.downs - downs).max(0.0))
+    }
+
+    fn ratio(ups: f64, downs: f64) -> f64 {
+        let total = ups + downs;
+        if total == 0.0 {
+            0.5
+        } else {
+            ups / total
+        }
+    }
+}
+
+// ==== Inkrementeller Regime-Tracker ====
+
+/// Pro Symbol: ersetzt VecDeque<PhysicsState> + RegimeClassifier::classify + calculate_z_score.
+/// Liefert dieselben Werte wie classify über die letzten `capacity` States.
+#[derive(Debug, Clone)]
+pub struct RegimeTracker {
+    min_samples: usize,
+    prices: RollingSlope,
+    moves: RollingUpDown,
+    fields: Vec<RollingStats>,
+    last: Option<PhysicsState>,
+}
+
+impl RegimeTracker {
+    /// `capacity` = Länge der Historie, `min_samples` = window_size des RegimeClassifier
+    pub fn new(capacity: usize, min_samples: usize) -> Self {
+        Self {
+            min_samples,
+            prices: RollingSlope::new(capacity),
+            moves: RollingUpDown::new(capacity),
+            fields: PhysicsField::ALL
+                .iter()
+                .map(|_| RollingStats::new(capacity))
+                .collect(),
+            last: None,
+        }
+    }
+
+    pub fn push(&mut self, state: &PhysicsState) {
+        self.prices.push(state.price);
+        self.moves.push(state.price);
+        for field in PhysicsField::ALL {
+            self.fields[field.index()].push(field.value(state));
+        }
+        self.last = Some(state.clone());
+    }
+
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.len() >= self.min_samples
+    }
+
+    pub fn last(&self) -> Option<&PhysicsState> {
+        self.last.as_ref()
+    }
+
+    pub fn stats(&self, field: PhysicsField) -> &RollingStats {
+        &self.fields[field.index()]
+    }
+
+    pub fn z_score(&self, field: PhysicsField, value: f64) -> f64 {
+        self.stats(field).z_score(value)
+    }
+
+    pub fn regime(&self) -> RegimeState {
+        let len = self.len();
+        if len < self.min_samples || len == 0 {
+            return RegimeState {
+                regime: MarketRegime::Compression,
+                symmetry_score: 0.5,
+                slope: 0.0,
+                reversion_speed: 0.0,
+                confidence: 0.0,
+            };
+        }
+
+        let symmetry = self.moves.symmetry();
+        let reversion = if len > 5 {
+            symmetry - self.moves.symmetry_excluding_last(5)
+        } else {
+            0.0
+        };
+
+        RegimeState {
+            regime: MarketRegime::from_symmetry(symmetry),
+            symmetry_score: symmetry,
+            slope: self.prices.slope(),
+            reversion_speed: reversion,
+            confidence: 1.0 - (1.0 / (len as f64)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::regime::RegimeClassifier;
+
+    /// Deterministischer Pseudo-Random-Walk
+    fn walk(n: usize) -> Vec<PhysicsState> {
+        let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut price = 30_000.0;
+        (0..n)
+            .map(|i| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let step = ((seed >> 33) % 7) as f64 - 3.0;
+                // Plateaus, damit auch Differenzen von 0 vorkommen
+                if i % 11 != 0 {
+                    price += step * 0.5;
+                }
+                PhysicsState {
+                    price,
+                    entropy: 2.0 + step * 0.1,
+                    pressure: step * 10.0,
+                    nrg: step.abs() * 3.0,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rolling_stats_match_full_recompute() {
+        let mut stats = RollingStats::new(5);
+        let values = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0, 5.0, 3.0, 5.0];
+        for (i, &v) in values.iter().enumerate() {
+            stats.push(v);
+            let window = &values[(i + 1).saturating_sub(5)..=i];
+            let n = window.len() as f64;
+            let mean = window.iter().sum::<f64>() / n;
+            assert!((stats.mean() - mean).abs() < 1e-9);
+            if window.len() > 1 {
+                let var = window.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+                assert!((stats.variance() - var).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tracker_matches_classifier() {
+        let (capacity, min_samples) = (89, 21);
+        let classifier = RegimeClassifier::new(min_samples);
+        let mut tracker = RegimeTracker::new(capacity, min_samples);
+        let mut history = VecDeque::new();
+
+        for state in walk(600) {
+            tracker.push(&state);
+            history.push_back(state.clone());
+            if history.len() > capacity {
+                history.pop_front();
+            }
+
+            let expected = classifier.classify(&history);
+            let actual = tracker.regime();
+            assert_eq!(actual.regime, expected.regime);
+            assert!((actual.symmetry_score - expected.symmetry_score).abs() < 1e-9);
+            assert!((actual.slope - expected.slope).abs() < 1e-9);
+            assert!((actual.reversion_speed - expected.reversion_speed).abs() < 1e-9);
+            assert_eq!(actual.confidence, expected.confidence);
+
+            for field in [
+                PhysicsField::Entropy,
+                PhysicsField::Pressure,
+                PhysicsField::Nrg,
+            ] {
+                let value = field.value(&state);
+                let expected_z = RegimeClassifier::calculate_z_score(value, &history, field);
+                assert!((tracker.z_score(field, value) - expected_z).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_flat_window_returns_to_neutral() {
+        let mut moves = RollingUpDown::new(4);
+        for x in [1.0, 1.1, 1.3, 1.3, 1.3, 1.3] {
+            moves.push(x);
+        }
+        assert_eq!(moves.ups(), 0.0);
+        assert_eq!(moves.symmetry(), 0.5);
+    }
+}
@@ -0,0 +1,254 @@
+// E:\MBCT\trading-core\src\replay.rs
+// THE ALLIANCE - Market Replay Provider
+// Speist aufgezeichnete L2-Snapshots (SnapshotStore) in dieselbe Heartbeat-Schnittstelle
+// wie der Live-Collector. Die Zeit läuft auf einer simulierten Uhr, die sich an den
+// Exchange-Timestamps der Snapshots orientiert.
+
+use crate::clock::SimulatedClock;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::time::{sleep_until, Duration, Instant};
+use trading_common::data::snapshot_store::SnapshotStore;
+use trading_common::data::types::{DataResult, L2Snapshot};
+
+/// Abtastintervall des Live-Heartbeats
+pub const DEFAULT_HEARTBEAT_MS: u64 = 100;
+
+/// Lücken über dieser Länge (z.B. Collector-Ausfall) werden übersprungen statt durchgetickt
+pub const DEFAULT_MAX_GAP_MS: u64 = 30_000;
+
+// ==== Geschwindigkeit ====
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Ein simulierter Heartbeat pro echtem Heartbeat
+    Realtime,
+    /// N-fache Geschwindigkeit
+    Multiplier(f64),
+    /// Kein Warten, Takt nur über die simulierte Uhr
+    AsFastAsPossible,
+}
+
+impl ReplaySpeed {
+    fn factor(&self) -> Option<f64> {
+        match self {
+            ReplaySpeed::Realtime => Some(1.0),
+            ReplaySpeed::Multiplier(x) => Some(*x),
+            ReplaySpeed::AsFastAsPossible => None,
+        }
+    }
+}
+
+impl FromStr for ReplaySpeed {
+    type Err = String;
+
+    /// "realtime", "max" oder "<N>x" (z.B. "10x")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "realtime" | "1x" => Ok(ReplaySpeed::Realtime),
+            "max" | "fast" => Ok(ReplaySpeed::AsFastAsPossible),
+            other => other
+                .strip_suffix('x')
+                .and_then(|n| n.parse::<f64>().ok())
+                .filter(|n| *n > 0.0)
+                .map(ReplaySpeed::Multiplier)
+                .ok_or_else(|| format!("Unbekannte Replay-Geschwindigkeit: {}", s)),
+        }
+    }
+}
+
+// ==== Replay Provider ====
+
+/// Ein Heartbeat im Replay: simulierte Zeit + aktuelles Orderbuch je Symbol
+#[derive(Debug, Clone)]
+pub struct ReplayTick {
+    pub sim_time_ms: u64,
+    /// Nach Symbol sortiert, damit jeder Lauf identisch verarbeitet wird
+    pub updates: Vec<(String, L2Snapshot)>,
+}
+
+pub struct ReplayProvider {
+    source: Box<dyn Iterator<Item = DataResult<L2Snapshot>> + Send>,
+    speed: ReplaySpeed,
+    heartbeat_ms: u64,
+    max_gap_ms: u64,
+    /// Nächster noch nicht angewendeter Snapshot
+    pending: Option<L2Snapshot>,
+    books: BTreeMap<String, L2Snapshot>,
+    sim_now_ms: Option<u64>,
+    /// (Wall-Clock, Sim-Zeit) Ankerpunkt für Realtime/Nx
+    anchor: Option<(Instant, u64)>,
+    /// Wird vor jedem Heartbeat auf die simulierte Zeit gesetzt
+    clock: Option<Arc<SimulatedClock>>,
+}
+
+impl ReplayProvider {
+    pub fn new(
+        source: impl Iterator<Item = DataResult<L2Snapshot>> + Send + 'static,
+        speed: ReplaySpeed,
+    ) -> Self {
+        Self {
+            source: Box::new(source),
+            speed,
+            heartbeat_ms: DEFAULT_HEARTBEAT_MS,
+            max_gap_ms: DEFAULT_MAX_GAP_MS,
+            pending: None,
+            books: BTreeMap::new(),
+            sim_now_ms: None,
+            anchor: None,
+            clock: None,
+        }
+    }
+
+    /// Replay aller (oder der angegebenen) Symbole eines SnapshotStores im Zeitfenster
+    pub fn from_store(
+        store: &SnapshotStore,
+        symbols: &[String],
+        start_ms: u64,
+        end_ms: u64,
+        speed: ReplaySpeed,
+    ) -> DataResult<Self> {
+        Ok(Self::new(store.reader(symbols, start_ms, end_ms)?, speed))
+    }
+
+    pub fn with_heartbeat_ms(mut self, heartbeat_ms: u64) -> Self {
+        self.heartbeat_ms = heartbeat_ms.max(1);
+        self
+    }
+
+    /// Treibt die gegebene Uhr mit, damit Chronos/ShlongMachine in Replay-Zeit rechnen
+    pub fn with_clock(mut self, clock: Arc<SimulatedClock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn with_max_gap_ms(mut self, max_gap_ms: u64) -> Self {
+        self.max_gap_ms = max_gap_ms;
+        self
+    }
+
+    /// Aktuelle simulierte Zeit (Unix millis), None vor dem ersten Heartbeat
+    pub fn sim_now_ms(&self) -> Option<u64> {
+        self.sim_now_ms
+    }
+
+    /// Zeitstempel des nächsten Snapshots, lädt ihn bei Bedarf aus der Quelle
+    fn peek_time(&mut self) -> DataResult<Option<u64>> {
+        if self.pending.is_none() {
+            self.pending = self.source.next().transpose()?;
+        }
+        Ok(self.pending.as_ref().map(|s| s.time))
+    }
+
+    /// Nächster Heartbeat; None wenn die Aufzeichnung erschöpft ist
+    pub async fn next_tick(&mut self) -> DataResult<Option<ReplayTick>> {
+        let next_time = self.peek_time()?;
+        let sim_now = match (self.sim_now_ms, next_time) {
+            (None, Some(t)) => t,
+            (None, None) => return Ok(None),
+            // Aufzeichnung zu Ende: letzter Stand wurde bereits ausgeliefert
+            (Some(_), None) => return Ok(None),
+            (Some(prev), Some(t)) if t > prev + self.max_gap_ms => {
+                self.anchor = None;
+                t
+            }
+            (Some(prev), Some(_)) => prev + self.heartbeat_ms,
+        };
+        self.sim_now_ms = Some(sim_now);
+        if let Some(clock) = &self.clock {
+            clock.set_ms(sim_now);
+        }
+
+        while self.peek_time()?.is_some_and(|t| t <= sim_now) {
+            if let Some(snapshot) = self.pending.take() {
+                self.books.insert(snapshot.coin.clone(), snapshot);
+            }
+        }
+
+        self.pace(sim_now).await;
+
+        Ok(Some(ReplayTick {
+            sim_time_ms: sim_now,
+            updates: self
+                .books
+                .iter()
+                .map(|(symbol, snapshot)| (symbol.clone(), snapshot.clone()))
+                .collect(),
+        }))
+    }
+
+    async fn pace(&mut self, sim_now: u64) {
+        let Some(factor) = self.speed.factor() else {
+            return;
+        };
+        let (wall_start, sim_start) = *self.anchor.get_or_insert((Instant::now(), sim_now));
+        let sim_elapsed = sim_now.saturating_sub(sim_start) as f64 / factor;
+        sleep_until(wall_start + Duration::from_secs_f64(sim_elapsed / 1000.0)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+    use trading_common::data::types::{L2Levels, Level};
+
+    fn snapshot(coin: &str, time: u64, px: &str) -> DataResult<L2Snapshot> {
+        Ok(L2Snapshot {
+            coin: coin.to_string(),
+            time,
+            levels: L2Levels {
+                bids: vec![Level {
+                    px: px.into(),
+                    sz: "1".into(),
+                }],
+                asks: vec![],
+            },
+        })
+    }
+
+    #[test]
+    fn test_speed_parsing() {
+        assert_eq!("realtime".parse(), Ok(ReplaySpeed::Realtime));
+        assert_eq!("10x".parse(), Ok(ReplaySpeed::Multiplier(10.0)));
+        assert_eq!("MAX".parse(), Ok(ReplaySpeed::AsFastAsPossible));
+        assert!("0x".parse::<ReplaySpeed>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_heartbeats_follow_simulated_clock() {
+        let source = vec![
+            snapshot("BTC", 1_000, "1"),
+            snapshot("ETH", 1_050, "2"),
+            snapshot("BTC", 1_150, "3"),
+            // Lücke > max_gap: Sprung statt 600 leerer Heartbeats
+            snapshot("BTC", 61_000, "4"),
+        ];
+        let clock = Arc::new(SimulatedClock::default());
+        let mut replay = ReplayProvider::new(source.into_iter(), ReplaySpeed::AsFastAsPossible)
+            .with_clock(clock.clone());
+
+        let mut ticks = Vec::new();
+        while let Some(tick) = replay.next_tick().await.unwrap() {
+            let books: Vec<(String, String)> = tick
+                .updates
+                .iter()
+                .map(|(s, snap)| (s.clone(), snap.levels.bids[0].px.clone()))
+                .collect();
+            assert_eq!(clock.now_ms(), tick.sim_time_ms);
+            ticks.push((tick.sim_time_ms, books));
+        }
+
+        let b = |s: &str, px: &str| (s.to_string(), px.to_string());
+        assert_eq!(
+            ticks,
+            vec![
+                (1_000, vec![b("BTC", "1")]),
+                (1_100, vec![b("BTC", "1"), b("ETH", "2")]),
+                (1_200, vec![b("BTC", "3"), b("ETH", "2")]),
+                (61_000, vec![b("BTC", "4"), b("ETH", "2")]),
+            ]
+        );
+    }
+}
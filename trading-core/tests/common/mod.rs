@@ -0,0 +1,46 @@
+// E:\MBCT\trading-core\tests\common\mod.rs
+// Gemeinsame Fixtures der Integrationstests (Gegenstück zu orderbook::test_books)
+
+#![allow(dead_code)]
+
+use serde_json::json;
+use trading_core::exchange::{L2Levels, L2Snapshot, Level};
+
+/// Snapshot aus (px, sz)-Paaren, bestes Level zuerst
+pub fn snapshot(coin: &str, time: u64, bids: &[(&str, &str)], asks: &[(&str, &str)]) -> L2Snapshot {
+    let levels = |side: &[(&str, &str)]| {
+        side.iter()
+            .map(|(px, sz)| Level {
+                px: px.to_string(),
+                sz: sz.to_string(),
+            })
+            .collect()
+    };
+    L2Snapshot {
+        coin: coin.into(),
+        time,
+        levels: L2Levels {
+            bids: levels(bids),
+            asks: levels(asks),
+        },
+    }
+}
+
+/// l2Book-Frame im Websocket-Format ([bids, asks] statt Objekt)
+pub fn l2_frame(snapshot: &L2Snapshot) -> String {
+    let side = |levels: &[Level]| {
+        levels
+            .iter()
+            .map(|l| json!({ "px": l.px, "sz": l.sz, "n": 1 }))
+            .collect::<Vec<_>>()
+    };
+    json!({
+        "channel": "l2Book",
+        "data": {
+            "coin": snapshot.coin,
+            "time": snapshot.time,
+            "levels": [side(&snapshot.levels.bids), side(&snapshot.levels.asks)]
+        }
+    })
+    .to_string()
+}
@@ -0,0 +1,440 @@
+// E:\MBCT\trading-core\tests\mock_exchange.rs
+// Integrationstests des Order-Pfads (Connector -> Signatur -> /exchange) gegen die MockExchange
+
+mod common;
+
+use common::snapshot;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::sync::Arc;
+use tokio::time::Duration;
+use trading_core::exchange::actions::{Cloid, Grouping, OrderRef, OrderRequest, Tif, Tpsl};
+use trading_core::exchange::connector::{HyperliquidConnector, OrderStatus, DEFAULT_SLIPPAGE};
+use trading_core::exchange::market_data::HyperliquidMarketData;
+use trading_core::exchange::mock_exchange::{MockAsset, MockConfig, MockExchange};
+use trading_core::exchange::rate_limit::{RateLimitConfig, RateLimiter};
+use trading_core::exchange::traits::{AccountQueries, MarketDataStream};
+use trading_core::exchange::{ExchangeError, L2Snapshot};
+
+const PRIVATE_KEY: &str = "0xe908f86dbb4d55ac876378565aafeabc187f6690f046459397b17d9b9a19688e";
+
+fn book(time: u64, bids: &[(&str, &str)], asks: &[(&str, &str)]) -> L2Snapshot {
+    snapshot("ETH", time, bids, asks)
+}
+
+async fn setup() -> (MockExchange, HyperliquidConnector) {
+    let mock = MockExchange::start(MockConfig {
+        assets: vec![MockAsset::new("BTC", 5), MockAsset::new("ETH", 4)],
+        ..MockConfig::default()
+    })
+    .await
+    .unwrap();
+    let connector = HyperliquidConnector::with_base_url(PRIVATE_KEY, true, mock.url()).unwrap();
+    mock.fund(connector.address(), dec!(1000));
+    mock.set_book(&book(
+        1,
+        &[("2000", "1"), ("1999.5", "2")],
+        &[("2000.5", "1"), ("2001", "2")],
+    ))
+    .unwrap();
+    (mock, connector)
+}
+
+fn reject(result: anyhow::Result<impl std::fmt::Debug>) -> ExchangeError {
+    result
+        .unwrap_err()
+        .downcast_ref::<ExchangeError>()
+        .cloned()
+        .expect("typisierter ExchangeError")
+}
+
+#[tokio::test]
+async fn test_entry_exit_roundtrip() {
+    let (mock, connector) = setup().await;
+
+    // Entry: IOC gegen den besten Ask
+    connector
+        .place_market_order("ETH", true, dec!(0.5), None)
+        .await
+        .unwrap();
+    let state = connector.get_account_state().await.unwrap();
+    let position = state.position("ETH").unwrap();
+    assert_eq!(position.size(), dec!(0.5));
+    assert_eq!(position.entry_price(), Some(dec!(2000.5)));
+    assert!(position.margin_used() > Decimal::ZERO);
+
+    // Kurs steigt, Exit schließt reduce-only über den Bid
+    mock.set_book(&book(2, &[("2010", "5")], &[("2011", "5")]))
+        .unwrap();
+    connector
+        .market_close("ETH", None, DEFAULT_SLIPPAGE)
+        .await
+        .unwrap();
+    let state = connector.get_account_state().await.unwrap();
+    assert!(state.position("ETH").is_none());
+
+    let fills = connector.user_fills(connector.address()).await.unwrap();
+    assert_eq!(fills.len(), 2);
+    assert_eq!(fills[0].dir, "Close Long");
+    assert_eq!(fills[0].closed_pnl(), dec!(4.75));
+    assert!(fills.iter().all(|f| f.crossed && f.fee() > Decimal::ZERO));
+
+    // Ohne Position gibt es nichts zu schließen
+    assert!(connector
+        .market_close("ETH", None, DEFAULT_SLIPPAGE)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_resting_orders_and_rejects() {
+    let (mock, connector) = setup().await;
+
+    let oid = connector
+        .place_limit_order("ETH", true, dec!(0.01), dec!(1990), None, false)
+        .await
+        .unwrap();
+    let open = connector.open_orders(connector.address()).await.unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].oid.to_string(), oid);
+    connector.cancel_order("ETH", &oid).await.unwrap();
+    assert!(connector
+        .open_orders(connector.address())
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Bulk-Cancel aller ETH-Orders per oid
+    for px in [dec!(1990), dec!(1985)] {
+        connector
+            .place_limit_order("ETH", true, dec!(0.01), px, None, false)
+            .await
+            .unwrap();
+    }
+    assert_eq!(connector.cancel_all_orders("ETH").await.unwrap(), 2);
+    assert!(mock.open_orders(connector.address()).is_empty());
+    assert_eq!(connector.cancel_all_orders("ETH").await.unwrap(), 0);
+
+    // Post-only über dem Ask
+    let err = reject(
+        connector
+            .place_limit_order("ETH", true, dec!(0.01), dec!(2001), None, true)
+            .await,
+    );
+    assert!(
+        matches!(err, ExchangeError::PostOnlyWouldCross(_)),
+        "{:?}",
+        err
+    );
+
+    // 20 ETH bei 20x brauchen ~2000 USDC Margin
+    let err = reject(
+        connector
+            .place_market_order("ETH", true, dec!(20), None)
+            .await,
+    );
+    assert!(
+        matches!(err, ExchangeError::InsufficientMargin(_)),
+        "{:?}",
+        err
+    );
+
+    // Reduce-only ohne Position
+    let request =
+        OrderRequest::limit("ETH", false, dec!(0.01), dec!(2000), Tif::Ioc).reduce_only(true);
+    let err = reject(connector.place_order(&request).await);
+    assert!(
+        matches!(err, ExchangeError::ReduceOnlyRejected(_)),
+        "{:?}",
+        err
+    );
+
+    // Ruhende Order wird vom nächsten Buch gefüllt (als Maker zum Limit)
+    connector
+        .place_limit_order("ETH", true, dec!(0.01), dec!(2000.2), None, false)
+        .await
+        .unwrap();
+    mock.set_book(&book(2, &[("1999", "1")], &[("2000.1", "1")]))
+        .unwrap();
+    assert!(mock.open_orders(connector.address()).is_empty());
+    let fills = mock.user_fills(connector.address());
+    assert_eq!((fills[0].price(), fills[0].crossed), (dec!(2000.2), false));
+}
+
+#[tokio::test]
+async fn test_modify_and_batch_modify() {
+    let (mock, connector) = setup().await;
+
+    let oid: u64 = connector
+        .place_limit_order("ETH", true, dec!(0.01), dec!(1990), None, false)
+        .await
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    // Einzel-Modify: Antwort ohne Statusliste, Order unter neuer oid
+    let request = OrderRequest::limit("ETH", true, dec!(0.02), dec!(1991), Tif::Gtc);
+    let status = connector
+        .modify_order(OrderRef::Oid(oid), &request)
+        .await
+        .unwrap();
+    assert_eq!(status, OrderStatus::Success);
+    let open = mock.open_orders(connector.address());
+    assert_eq!(open.len(), 1);
+    assert_eq!((open[0].limit_px, open[0].sz), (dec!(1991), dec!(0.02)));
+    assert_ne!(open[0].oid, oid);
+
+    // Unbekannte Order: Ablehnung der ganzen Action
+    let err = connector
+        .modify_order(OrderRef::Oid(oid), &request)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Cannot modify"), "{}", err);
+
+    // Batch per cloid: Statusliste, Fehler pro Eintrag
+    let cloid = Cloid::from_u128(7);
+    let request = OrderRequest::limit("ETH", false, dec!(0.01), dec!(2005), Tif::Gtc)
+        .with_cloid(cloid.clone());
+    connector.place_order(&request).await.unwrap();
+    let statuses = connector
+        .batch_modify(&[
+            (
+                OrderRef::Cloid(cloid.clone()),
+                OrderRequest::limit("ETH", false, dec!(0.01), dec!(2006), Tif::Gtc)
+                    .with_cloid(cloid.clone()),
+            ),
+            (
+                OrderRef::Oid(oid),
+                OrderRequest::limit("ETH", true, dec!(0.01), dec!(1980), Tif::Gtc),
+            ),
+        ])
+        .await
+        .unwrap();
+    assert!(matches!(&statuses[0], OrderStatus::Resting(r) if r.cloid == Some(cloid.clone())));
+    assert!(statuses[1].error().is_some(), "{:?}", statuses[1]);
+
+    // Modify über den Ask füllt sofort
+    let bid = mock
+        .open_orders(connector.address())
+        .into_iter()
+        .find(|o| o.limit_px == dec!(1991))
+        .unwrap()
+        .oid;
+    let request = OrderRequest::limit("ETH", true, dec!(0.02), dec!(2001), Tif::Gtc);
+    connector
+        .modify_order(OrderRef::Oid(bid), &request)
+        .await
+        .unwrap();
+    let state = mock.clearinghouse_state(connector.address());
+    assert_eq!(state.position("ETH").unwrap().size(), dec!(0.02));
+    let open = mock.open_orders(connector.address());
+    assert_eq!((open.len(), open[0].limit_px), (1, dec!(2006)));
+}
+
+#[tokio::test]
+async fn test_trigger_orders_and_normal_tpsl() {
+    let (mock, connector) = setup().await;
+
+    // Stop-Loss ist reduce-only und braucht eine Position
+    let err = reject(
+        connector
+            .place_trigger_order("ETH", false, dec!(0.5), dec!(1950), Tpsl::Sl)
+            .await,
+    );
+    assert!(
+        matches!(err, ExchangeError::ReduceOnlyRejected(_)),
+        "{:?}",
+        err
+    );
+
+    connector
+        .market_open("ETH", true, dec!(0.5), DEFAULT_SLIPPAGE)
+        .await
+        .unwrap();
+    let status = connector
+        .place_trigger_order("ETH", false, dec!(0.5), dec!(1950), Tpsl::Sl)
+        .await
+        .unwrap();
+    assert!(status.order_id().is_some(), "{:?}", status);
+
+    // Mid 1940.5 unter dem Trigger: Stop schließt als Taker über den Bid
+    mock.set_book(&book(2, &[("1940", "5")], &[("1941", "5")]))
+        .unwrap();
+    assert!(mock
+        .clearinghouse_state(connector.address())
+        .position("ETH")
+        .is_none());
+    assert!(mock.open_orders(connector.address()).is_empty());
+    let fills = mock.user_fills(connector.address());
+    assert_eq!((fills[0].price(), fills[0].crossed), (dec!(1940), true));
+    assert_eq!(fills[0].closed_pnl(), dec!(-30.25));
+
+    // Entry ruht, TP/SL warten auf dessen Fill
+    let entry = OrderRequest::limit("ETH", true, dec!(0.1), dec!(1930), Tif::Gtc);
+    let tp = OrderRequest::trigger(
+        "ETH",
+        false,
+        dec!(0.1),
+        dec!(1960),
+        dec!(1900),
+        Tpsl::Tp,
+        true,
+    );
+    let sl = OrderRequest::trigger(
+        "ETH",
+        false,
+        dec!(0.1),
+        dec!(1900),
+        dec!(1850),
+        Tpsl::Sl,
+        true,
+    );
+    let statuses = connector
+        .bulk_orders(&[entry, tp, sl], Grouping::NormalTpsl)
+        .await
+        .unwrap();
+    assert!(matches!(statuses[0], OrderStatus::Resting(_)));
+    assert_eq!(
+        statuses[1..],
+        [OrderStatus::WaitingForFill, OrderStatus::WaitingForFill]
+    );
+
+    // Entry gefüllt: TP/SL aktiv, aber noch nicht ausgelöst
+    mock.set_book(&book(3, &[("1920", "5")], &[("1925", "5")]))
+        .unwrap();
+    let state = mock.clearinghouse_state(connector.address());
+    assert_eq!(state.position("ETH").unwrap().size(), dec!(0.1));
+    assert_eq!(mock.open_orders(connector.address()).len(), 2);
+
+    // Take-Profit löst aus und schließt die Position
+    mock.set_book(&book(4, &[("1965", "5")], &[("1966", "5")]))
+        .unwrap();
+    assert!(mock
+        .clearinghouse_state(connector.address())
+        .position("ETH")
+        .is_none());
+    let fills = mock.user_fills(connector.address());
+    assert_eq!(fills[0].price(), dec!(1965));
+    assert_eq!(fills[0].closed_pnl(), dec!(3.5));
+}
+
+#[tokio::test]
+async fn test_rejects_unknown_signer() {
+    let (mock, connector) = setup().await;
+
+    // Mainnet-Signatur gegen einen Testnet-Mock: anderer Signer, kein Account
+    let mainnet = HyperliquidConnector::with_base_url(PRIVATE_KEY, false, mock.url()).unwrap();
+    let err = reject(mainnet.set_leverage("ETH", 10).await);
+    assert!(err.to_string().contains("does not exist"), "{:?}", err);
+
+    connector.set_leverage("ETH", 10).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_polling_cannot_starve_orders() {
+    let (mock, connector) = setup().await;
+    // Kleines Budget ohne nennenswerten Nachschub
+    let limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+        capacity: 100,
+        refill_per_minute: 1,
+        normal_reserve: 10,
+        low_reserve: 60,
+        low_max_wait: Duration::from_millis(10),
+    }));
+    let connector = connector.with_rate_limiter(limiter.clone());
+
+    // Equity-Polling (Low, Gewicht 2) läuft bis zur Reserve und wird dann verworfen
+    let mut polls = 0;
+    let err = loop {
+        match connector.get_user_state(connector.address()).await {
+            Ok(_) => polls += 1,
+            Err(e) => break e,
+        }
+        assert!(polls < 100, "polling never shed");
+    };
+    assert!(matches!(
+        err.downcast_ref::<ExchangeError>(),
+        Some(ExchangeError::RateLimited(_))
+    ));
+    assert_eq!(polls, 20);
+
+    // Order-Pfad: meta (20) + l2Book (2) + Action (1) aus der Reserve
+    connector
+        .place_market_order("ETH", true, dec!(0.01), None)
+        .await
+        .unwrap();
+
+    let stats = connector.rate_limit_stats();
+    assert_eq!((stats.requests, stats.shed), (23, 1));
+    assert!(stats.remaining < 40);
+
+    let budget = connector.user_rate_limit(connector.address()).await;
+    // Low-Abfrage bei erschöpfter Reserve: ebenfalls verworfen
+    assert!(budget.is_err());
+    assert_eq!(limiter.stats().shed, 2);
+
+    let fresh = HyperliquidConnector::with_base_url(PRIVATE_KEY, true, mock.url()).unwrap();
+    let budget = fresh.user_rate_limit(fresh.address()).await.unwrap();
+    assert_eq!(budget.n_requests_used, 1);
+    assert!(budget.remaining() > 10_000 - 1);
+}
+
+#[tokio::test]
+async fn test_agent_wallet_trades_master_account() {
+    const AGENT_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    let (mock, master) = setup().await;
+    let agent = HyperliquidConnector::with_base_url(AGENT_KEY, true, mock.url())
+        .unwrap()
+        .with_account(master.address());
+
+    // Nicht freigegebener Agent hat keinen Account
+    let err = reject(
+        agent
+            .market_open("ETH", true, dec!(0.5), DEFAULT_SLIPPAGE)
+            .await,
+    );
+    assert!(err.to_string().contains("does not exist"), "{:?}", err);
+
+    master.approve_agent(agent.address(), None).await.unwrap();
+
+    // Agent signiert, Position und Exit laufen über den Master-Account
+    agent
+        .market_open("ETH", true, dec!(0.5), DEFAULT_SLIPPAGE)
+        .await
+        .unwrap();
+    assert_eq!(
+        mock.clearinghouse_state(master.address())
+            .position("ETH")
+            .unwrap()
+            .size(),
+        dec!(0.5)
+    );
+    assert!(mock
+        .clearinghouse_state(agent.address())
+        .position("ETH")
+        .is_none());
+
+    // Trait-Nutzer (PaperTrading, Default-market_close) sehen den Master-Account
+    let venue: &dyn AccountQueries = &agent;
+    assert_eq!(venue.account_address(), master.address());
+    let state = venue.account_state(venue.account_address()).await.unwrap();
+    assert_eq!(state.position("ETH").unwrap().size(), dec!(0.5));
+
+    agent
+        .market_close("ETH", None, DEFAULT_SLIPPAGE)
+        .await
+        .unwrap();
+    assert!(agent.get_open_positions().await.unwrap().is_empty());
+    assert_eq!(mock.user_fills(master.address()).len(), 2);
+}
+
+#[tokio::test]
+async fn test_public_market_data_without_key() {
+    let (mock, _) = setup().await;
+    let market_data = HyperliquidMarketData::new().with_base_url(mock.url());
+
+    let book = market_data.orderbook("ETH").await.unwrap();
+    assert_eq!(book.best_bid().unwrap().px, dec!(2000));
+    assert_eq!(book.best_ask().unwrap().px, dec!(2000.5));
+    assert!(market_data.orderbook("DOGE").await.is_err());
+}
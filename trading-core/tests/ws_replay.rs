@@ -0,0 +1,183 @@
+// E:\MBCT\trading-core\tests\ws_replay.rs
+// Integrationstests gegen den lokalen WsReplayServer - kein Netzwerkzugriff nötig
+
+mod common;
+
+use common::{l2_frame, snapshot};
+use serde_json::json;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::time::{sleep, timeout, Duration};
+use trading_core::exchange::ws::{ConnectionState, HLEvent, HyperliquidWs, WsConfig};
+use trading_core::exchange::ws_replay::{load_frames, RecordedFrame, WsReplayServer};
+use trading_core::physics::collector::Collector;
+use trading_core::replay::ReplaySpeed;
+
+fn book(time: u64, bid: &str, ask: &str) -> String {
+    l2_frame(&snapshot("BTC", time, &[(bid, "1")], &[(ask, "1")]))
+}
+
+fn fast_config() -> WsConfig {
+    WsConfig {
+        backoff_base: Duration::from_millis(10),
+        backoff_max: Duration::from_millis(20),
+        ..WsConfig::default()
+    }
+}
+
+/// Events bis zum n-ten Snapshot sammeln
+async fn collect_until_snapshots(ws: &mut HyperliquidWs, n: usize) -> Vec<HLEvent> {
+    timeout(Duration::from_secs(5), async {
+        let mut events = Vec::new();
+        let mut snapshots = 0;
+        while snapshots < n {
+            let event = ws.next_event().await.expect("ws task ended");
+            if matches!(event, HLEvent::Snapshot(_)) {
+                snapshots += 1;
+            }
+            events.push(event);
+        }
+        events
+    })
+    .await
+    .expect("timeout waiting for snapshots")
+}
+
+#[tokio::test]
+async fn test_client_survives_disconnect_and_malformed_frames() {
+    let frames = vec![
+        RecordedFrame::text(1_000, book(1_000, "100", "101")),
+        RecordedFrame::text(1_010, "{not json"),
+        RecordedFrame::text(1_020, r#"{"channel":"l2Book","data":{"coin":"BTC"}}"#),
+        RecordedFrame::Disconnect { t: 1_030 },
+        RecordedFrame::text(1_040, book(1_040, "100.5", "101")),
+    ];
+    let server = WsReplayServer::start(frames, ReplaySpeed::AsFastAsPossible)
+        .await
+        .unwrap();
+
+    let mut ws = HyperliquidWs::with_url(server.url(), fast_config())
+        .await
+        .unwrap();
+    ws.subscribe_l2("BTC").await.unwrap();
+
+    let events = collect_until_snapshots(&mut ws, 2).await;
+
+    // Kaputtes JSON wird verschluckt, unvollständige Payload kommt als Other durch
+    let others = events
+        .iter()
+        .filter(|e| matches!(e, HLEvent::Other { .. }))
+        .count();
+    assert_eq!(others, 1);
+
+    let states: Vec<ConnectionState> = events
+        .iter()
+        .filter_map(|e| match e {
+            HLEvent::Connection(s) => Some(*s),
+            _ => None,
+        })
+        .collect();
+    assert!(matches!(
+        states.as_slice(),
+        [
+            ConnectionState::Connected,
+            ConnectionState::Reconnecting { attempt: 1, .. },
+            ConnectionState::Connected
+        ]
+    ));
+
+    // Abo wurde nach dem Reconnect automatisch erneut gesendet
+    assert_eq!(server.connections(), 2);
+    let btc = json!({ "type": "l2Book", "coin": "BTC" });
+    assert_eq!(server.subscriptions(), vec![btc.clone(), btc]);
+}
+
+#[tokio::test]
+async fn test_collector_pipeline_rejects_bad_books() {
+    let frames = vec![
+        RecordedFrame::text(1, book(1, "100", "101")),
+        // Gekreuzt
+        RecordedFrame::text(2, book(2, "102", "101")),
+        // Älter als das aktuelle Buch
+        RecordedFrame::text(3, book(0, "99", "100")),
+        RecordedFrame::text(4, book(4, "100.5", "101")),
+    ];
+    let server = WsReplayServer::start(frames, ReplaySpeed::AsFastAsPossible)
+        .await
+        .unwrap();
+
+    let config = WsConfig {
+        url: Some(server.url().to_string()),
+        ..fast_config()
+    };
+    let collector = Arc::new(Collector::new(false).with_ws_config(config));
+    let stream = tokio::spawn(collector.clone().stream_provider(vec!["BTC".into()]));
+
+    timeout(Duration::from_secs(5), async {
+        while collector.book("BTC").map(|b| b.time) != Some(4) {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("collector did not reach the last book");
+
+    let book = collector.book("BTC").unwrap();
+    assert_eq!(book.seq, 2);
+    assert_eq!(collector.stats.messages_received.load(Ordering::Relaxed), 4);
+    assert_eq!(collector.stats.books_rejected.load(Ordering::Relaxed), 2);
+    assert!(collector.is_live());
+
+    stream.abort();
+}
+
+#[tokio::test]
+async fn test_recording_replays_identically() {
+    let frames = vec![
+        RecordedFrame::text(10, book(10, "100", "101")),
+        RecordedFrame::Disconnect { t: 20 },
+        RecordedFrame::text(30, book(30, "100", "102")),
+    ];
+    let server = WsReplayServer::start(frames.clone(), ReplaySpeed::Multiplier(10.0))
+        .await
+        .unwrap();
+
+    let path = std::env::temp_dir().join(format!("ws_record_{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let config = WsConfig {
+        record_path: Some(path.clone()),
+        ..fast_config()
+    };
+    let mut ws = HyperliquidWs::with_url(server.url(), config).await.unwrap();
+    ws.subscribe_l2("BTC").await.unwrap();
+    collect_until_snapshots(&mut ws, 2).await;
+    drop(ws);
+
+    // Der Schreib-Thread flusht spätestens beim Ende des WS-Tasks
+    let recorded = timeout(Duration::from_secs(5), async {
+        loop {
+            // Vor dem Flush fehlt die Datei oder endet mitten in einer Zeile
+            if let Ok(recorded) = load_frames(&path) {
+                if recorded.len() >= frames.len() {
+                    return recorded;
+                }
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("recording was not flushed");
+
+    // Inhalt identisch, Zeitstempel aus der Aufnahme
+    assert_eq!(recorded.len(), frames.len());
+    for (rec, orig) in recorded.iter().zip(&frames) {
+        match (rec, orig) {
+            (RecordedFrame::Text { data: a, .. }, RecordedFrame::Text { data: b, .. }) => {
+                assert_eq!(a, b)
+            }
+            (RecordedFrame::Disconnect { .. }, RecordedFrame::Disconnect { .. }) => {}
+            other => panic!("frame mismatch: {:?}", other),
+        }
+    }
+    assert!(recorded.windows(2).all(|w| w[0].time() <= w[1].time()));
+    std::fs::remove_file(&path).unwrap();
+}